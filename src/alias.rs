@@ -0,0 +1,274 @@
+//! User-defined command aliases from the `[alias]` table in config.toml.
+//!
+//! An alias expands to an argument list before clap parses the command line,
+//! like git aliases. Definitions are split with shell quoting rules, and `$1`,
+//! `$2`, ... are replaced by the arguments given after the alias name (`$@` by
+//! all of them). Arguments not used by a placeholder are appended.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ffi::OsString;
+
+use clap::{Arg, Command, CommandFactory};
+
+use crate::cli::Cli;
+use crate::error::{LinearError, Result};
+
+/// Replace an alias in `args` (the full command line, program name first)
+/// with its expansion. Built-in commands always win over an alias of the
+/// same name, and an alias may expand to another alias.
+pub fn expand(
+    mut args: Vec<OsString>,
+    aliases: &BTreeMap<String, String>,
+) -> Result<Vec<OsString>> {
+    if aliases.is_empty() {
+        return Ok(args);
+    }
+
+    let command = Cli::command();
+    let mut seen = BTreeSet::new();
+
+    while let Some(index) = command_index(&command, &args) {
+        let Some(name) = args[index].to_str().map(String::from) else {
+            break;
+        };
+        let Some(definition) = aliases.get(&name) else {
+            break;
+        };
+        if name == "help" || command.find_subcommand(&name).is_some() {
+            break;
+        }
+        if !seen.insert(name.clone()) {
+            return Err(LinearError::InvalidAlias {
+                name,
+                message: "expands to itself".to_string(),
+            });
+        }
+
+        let rest = args.split_off(index + 1);
+        args.pop();
+        args.extend(expand_one(&name, definition, rest)?);
+    }
+
+    Ok(args)
+}
+
+/// The CLI definition, with the aliases listed at the end of its help
+pub fn command(aliases: &BTreeMap<String, String>) -> Command {
+    let command = Cli::command();
+    if aliases.is_empty() {
+        return command;
+    }
+
+    let width = aliases.keys().map(String::len).max().unwrap_or(0);
+    let mut help = command
+        .get_after_help()
+        .map(|help| format!("{help}\n\n"))
+        .unwrap_or_default();
+    help.push_str("ALIASES (from config.toml):");
+    for (name, definition) in aliases {
+        help.push_str(&format!("\n    {name:width$}  {definition}"));
+    }
+
+    command.after_help(help)
+}
+
+/// Position of the subcommand name, skipping global options and their values
+fn command_index(command: &Command, args: &[OsString]) -> Option<usize> {
+    let takes_value = |arg: &Arg| arg.get_action().takes_values();
+    let mut index = 1;
+
+    while let Some(arg) = args.get(index) {
+        let arg = arg.to_str()?;
+        if arg == "--" {
+            return (index + 1 < args.len()).then_some(index + 1);
+        }
+
+        if let Some(long) = arg.strip_prefix("--") {
+            if !long.contains('=')
+                && command
+                    .get_arguments()
+                    .any(|a| a.get_long() == Some(long) && takes_value(a))
+            {
+                index += 1;
+            }
+        } else if let Some(shorts) = arg.strip_prefix('-').filter(|s| !s.is_empty()) {
+            // In `-qo json` the value follows; in `-ojson` it's attached
+            let value_at = shorts.chars().position(|c| {
+                command
+                    .get_arguments()
+                    .any(|a| a.get_short() == Some(c) && takes_value(a))
+            });
+            if value_at == Some(shorts.chars().count() - 1) {
+                index += 1;
+            }
+        } else {
+            return Some(index);
+        }
+        index += 1;
+    }
+
+    None
+}
+
+/// Split one definition and fill in its placeholders from `rest`
+fn expand_one(name: &str, definition: &str, rest: Vec<OsString>) -> Result<Vec<OsString>> {
+    let invalid = |message: String| LinearError::InvalidAlias {
+        name: name.to_string(),
+        message,
+    };
+
+    let words = shlex::split(definition)
+        .ok_or_else(|| invalid(format!("has unbalanced quotes: {definition}")))?;
+    if words.is_empty() {
+        return Err(invalid("is empty".to_string()));
+    }
+
+    let mut expanded = Vec::new();
+    let mut used = 0;
+    let mut all = false;
+    for word in words {
+        if word == "$@" {
+            expanded.extend(rest.iter().cloned());
+            all = true;
+            continue;
+        }
+
+        // A whole-word placeholder passes the argument through untouched
+        if let Some(n) = placeholder(&word) {
+            let arg = rest
+                .get(n - 1)
+                .ok_or_else(|| invalid(missing(n, rest.len())))?;
+            expanded.push(arg.clone());
+            used = used.max(n);
+            continue;
+        }
+
+        let mut out = String::new();
+        let mut chars = word.chars().peekable();
+        while let Some(c) = chars.next() {
+            let mut digits = String::new();
+            if c == '$' {
+                while let Some(d) = chars.next_if(char::is_ascii_digit) {
+                    digits.push(d);
+                }
+            }
+            match digits.parse::<usize>() {
+                Ok(n) if n > 0 => {
+                    let arg = rest
+                        .get(n - 1)
+                        .ok_or_else(|| invalid(missing(n, rest.len())))?;
+                    out.push_str(&arg.to_string_lossy());
+                    used = used.max(n);
+                }
+                _ => {
+                    out.push(c);
+                    out.push_str(&digits);
+                }
+            }
+        }
+        expanded.push(out.into());
+    }
+
+    if !all {
+        expanded.extend(rest.into_iter().skip(used));
+    }
+    Ok(expanded)
+}
+
+/// `n` for a word that is exactly `$n`
+fn placeholder(word: &str) -> Option<usize> {
+    word.strip_prefix('$')
+        .filter(|n| n.chars().all(|c| c.is_ascii_digit()))
+        .and_then(|n| n.parse().ok())
+        .filter(|&n| n > 0)
+}
+
+fn missing(needed: usize, given: usize) -> String {
+    format!("needs at least {needed} argument(s), got {given}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(line: &[&str], aliases: &[(&str, &str)]) -> Result<Vec<String>> {
+        let aliases = aliases
+            .iter()
+            .map(|(name, definition)| (name.to_string(), definition.to_string()))
+            .collect();
+        let args = line.iter().map(OsString::from).collect();
+        Ok(expand(args, &aliases)?
+            .into_iter()
+            .map(|arg| arg.into_string().unwrap())
+            .collect())
+    }
+
+    #[test]
+    fn test_expand() {
+        let aliases = [
+            (
+                "review",
+                r#"issues --team ENG --status "In Review" --label backend"#,
+            ),
+            ("mv", "issue update $2 --status $1"),
+            ("branch", "issue view ENG-$1"),
+            ("oss", "--profile oss $@"),
+            ("issues", "teams"),
+            ("loop", "loop"),
+        ];
+
+        assert_eq!(
+            run(
+                &["linear", "-o", "table", "review", "--limit", "5"],
+                &aliases
+            )
+            .unwrap(),
+            [
+                "linear",
+                "-o",
+                "table",
+                "issues",
+                "--team",
+                "ENG",
+                "--status",
+                "In Review",
+                "--label",
+                "backend",
+                "--limit",
+                "5"
+            ]
+        );
+        assert_eq!(
+            run(&["linear", "mv", "Done", "ENG-1", "-q"], &aliases).unwrap(),
+            ["linear", "issue", "update", "ENG-1", "--status", "Done", "-q"]
+        );
+        assert_eq!(
+            run(&["linear", "branch", "42"], &aliases).unwrap(),
+            ["linear", "issue", "view", "ENG-42"]
+        );
+        // Chained, and built-ins aren't replaced
+        assert_eq!(
+            run(&["linear", "oss", "review"], &aliases).unwrap(),
+            [
+                "linear",
+                "--profile",
+                "oss",
+                "issues",
+                "--team",
+                "ENG",
+                "--status",
+                "In Review",
+                "--label",
+                "backend"
+            ]
+        );
+        assert!(matches!(
+            run(&["linear", "mv", "Done"], &aliases),
+            Err(LinearError::InvalidAlias { .. })
+        ));
+        assert!(matches!(
+            run(&["linear", "loop"], &aliases),
+            Err(LinearError::InvalidAlias { .. })
+        ));
+    }
+}
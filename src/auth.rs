@@ -0,0 +1,408 @@
+//! OAuth2 authorization-code flow with PKCE, and token storage/refresh.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use url::Url;
+
+use crate::config::{Config, OAuthConfig};
+use crate::error::{LinearError, Result};
+
+const AUTHORIZE_URL: &str = "https://linear.app/oauth/authorize";
+const TOKEN_URL: &str = "https://api.linear.app/oauth/token";
+const REVOKE_URL: &str = "https://api.linear.app/oauth/revoke";
+pub(crate) const DEFAULT_REDIRECT_PORT: u16 = 8484;
+pub(crate) const DEFAULT_SCOPES: &str = "read,write";
+
+/// Refresh access tokens this many seconds before they expire
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// How long to wait for the browser to complete the authorization
+const LOGIN_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// How long an accepted loopback connection may take to send its request
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How a request is authenticated.
+#[derive(Clone)]
+pub enum Credentials {
+    /// Personal API key, sent as-is in the Authorization header
+    ApiKey(String),
+    /// OAuth access token, sent as a Bearer token and refreshed when expired
+    OAuth(OAuthTokens),
+}
+
+impl Credentials {
+    /// Value for the Authorization header
+    pub fn authorization(&self) -> String {
+        match self {
+            Credentials::ApiKey(key) => key.clone(),
+            Credentials::OAuth(tokens) => format!("Bearer {}", tokens.access_token),
+        }
+    }
+}
+
+/// Stored OAuth tokens (`tokens.json` next to the config file, or in the profile's directory).
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OAuthTokens {
+    pub access_token: String,
+    pub refresh_token: Option<String>,
+    /// Unix timestamp (seconds) when the access token expires
+    pub expires_at: Option<i64>,
+    pub scope: Option<String>,
+    /// Client ID the tokens were issued to (needed for refresh)
+    pub client_id: String,
+    /// Organization (workspace) the tokens belong to; keys the cache
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub organization_id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+    /// Linear returns either a comma-separated string or an array
+    scope: Option<serde_json::Value>,
+}
+
+impl OAuthTokens {
+    pub fn path() -> Result<PathBuf> {
+        Config::profile_path("tokens.json")
+    }
+
+    /// Load stored tokens, if logged in
+    pub fn load() -> Result<Option<Self>> {
+        let path = Self::path()?;
+
+        if !path.exists() {
+            return Ok(None);
+        }
+
+        let contents = std::fs::read_to_string(&path)?;
+        Ok(Some(serde_json::from_str(&contents)?))
+    }
+
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path()?;
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        write_private(&path, serde_json::to_string_pretty(self)?.as_bytes())
+    }
+
+    /// Remove stored tokens; returns whether any were present
+    pub fn delete() -> Result<bool> {
+        let path = Self::path()?;
+
+        if !path.exists() {
+            return Ok(false);
+        }
+
+        std::fs::remove_file(&path)?;
+        Ok(true)
+    }
+
+    pub fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|at| chrono::Utc::now().timestamp() + REFRESH_MARGIN_SECS >= at)
+    }
+
+    fn from_response(response: TokenResponse, client_id: &str, previous: Option<&Self>) -> Self {
+        let scope = response.scope.map(|s| match s {
+            serde_json::Value::Array(items) => items
+                .iter()
+                .filter_map(|i| i.as_str())
+                .collect::<Vec<_>>()
+                .join(","),
+            serde_json::Value::String(s) => s,
+            other => other.to_string(),
+        });
+
+        Self {
+            access_token: response.access_token,
+            // Keep the old refresh token if the server didn't rotate it
+            refresh_token: response
+                .refresh_token
+                .or_else(|| previous.and_then(|p| p.refresh_token.clone())),
+            expires_at: response
+                .expires_in
+                .map(|secs| chrono::Utc::now().timestamp() + secs),
+            scope: scope.or_else(|| previous.and_then(|p| p.scope.clone())),
+            client_id: client_id.to_string(),
+            organization_id: previous.and_then(|p| p.organization_id.clone()),
+        }
+    }
+}
+
+/// Exchange the refresh token for a new access token and persist it
+pub async fn refresh(http: &Client, tokens: &OAuthTokens) -> Result<OAuthTokens> {
+    let refresh_token = tokens
+        .refresh_token
+        .as_deref()
+        .ok_or(LinearError::OAuthExpired)?;
+
+    let response = http
+        .post(TOKEN_URL)
+        .form(&[
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token),
+            ("client_id", &tokens.client_id),
+        ])
+        .send()
+        .await?;
+
+    let refreshed = OAuthTokens::from_response(
+        token_response(response).await?,
+        &tokens.client_id,
+        Some(tokens),
+    );
+    refreshed.save()?;
+
+    Ok(refreshed)
+}
+
+/// Run the interactive authorization-code flow and store the resulting tokens
+pub async fn login(http: &Client, oauth: &OAuthConfig) -> Result<OAuthTokens> {
+    let client_id = oauth.client_id()?;
+    let port = oauth.redirect_port.unwrap_or(DEFAULT_REDIRECT_PORT);
+    let redirect_uri = format!("http://localhost:{port}/callback");
+
+    let verifier = random_token(64)?;
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()));
+    let state = random_token(16)?;
+
+    let mut authorize = Url::parse(AUTHORIZE_URL).expect("valid authorize URL");
+    authorize
+        .query_pairs_mut()
+        .append_pair("client_id", &client_id)
+        .append_pair("redirect_uri", &redirect_uri)
+        .append_pair("response_type", "code")
+        .append_pair("scope", oauth.scopes.as_deref().unwrap_or(DEFAULT_SCOPES))
+        .append_pair("state", &state)
+        .append_pair("code_challenge", &challenge)
+        .append_pair("code_challenge_method", "S256")
+        .append_pair("prompt", "consent");
+
+    // Bind before opening the browser so the redirect can't race the listener
+    let listener = TcpListener::bind(("127.0.0.1", port)).await?;
+
+    eprintln!("Opening browser to authorize linear-cli. If it doesn't open, visit:\n");
+    eprintln!("  {authorize}\n");
+    open_browser(authorize.as_str());
+
+    let code = tokio::time::timeout(LOGIN_TIMEOUT, wait_for_code(&listener, &state))
+        .await
+        .map_err(|_| LinearError::OAuthTimeout)??;
+
+    let mut form = vec![
+        ("grant_type", "authorization_code".to_string()),
+        ("code", code),
+        ("redirect_uri", redirect_uri),
+        ("client_id", client_id.clone()),
+        ("code_verifier", verifier),
+    ];
+    if let Some(secret) = &oauth.client_secret {
+        form.push(("client_secret", secret.clone()));
+    }
+
+    let response = http.post(TOKEN_URL).form(&form).send().await?;
+    let tokens = OAuthTokens::from_response(token_response(response).await?, &client_id, None);
+    tokens.save()?;
+
+    Ok(tokens)
+}
+
+/// Revoke the access token with Linear (best effort)
+pub async fn revoke(http: &Client, tokens: &OAuthTokens) -> Result<()> {
+    let response = http
+        .post(REVOKE_URL)
+        .bearer_auth(&tokens.access_token)
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(LinearError::OAuth(format!(
+            "token revocation failed (status {})",
+            response.status().as_u16()
+        )));
+    }
+
+    Ok(())
+}
+
+async fn token_response(response: reqwest::Response) -> Result<TokenResponse> {
+    let status = response.status();
+    let body = response.text().await?;
+
+    if !status.is_success() {
+        return Err(LinearError::OAuth(format!(
+            "token request failed (status {}): {body}",
+            status.as_u16()
+        )));
+    }
+
+    Ok(serde_json::from_str(&body)?)
+}
+
+/// Accept loopback connections until the OAuth redirect arrives
+async fn wait_for_code(listener: &TcpListener, expected_state: &str) -> Result<String> {
+    loop {
+        let (mut stream, _) = listener.accept().await?;
+
+        // Browsers may open speculative connections that never send anything
+        let mut buffer = vec![0u8; 8192];
+        let read = match tokio::time::timeout(REQUEST_TIMEOUT, stream.read(&mut buffer)).await {
+            Ok(Ok(read)) => read,
+            _ => continue,
+        };
+        let request = String::from_utf8_lossy(&buffer[..read]);
+
+        // Request line: "GET /callback?code=...&state=... HTTP/1.1"
+        let Some(target) = request.split_whitespace().nth(1) else {
+            continue;
+        };
+        let Ok(url) = Url::parse(&format!("http://localhost{target}")) else {
+            continue;
+        };
+        if url.path() != "/callback" {
+            respond(&mut stream, "404 Not Found", "Not found").await;
+            continue;
+        }
+
+        let param = |name: &str| {
+            url.query_pairs()
+                .find(|(k, _)| k == name)
+                .map(|(_, v)| v.into_owned())
+        };
+
+        if let Some(error) = param("error") {
+            respond(&mut stream, "400 Bad Request", "Authorization failed.").await;
+            return Err(LinearError::OAuth(format!("authorization denied: {error}")));
+        }
+
+        if param("state").as_deref() != Some(expected_state) {
+            respond(&mut stream, "400 Bad Request", "State mismatch.").await;
+            return Err(LinearError::OAuth("state mismatch in redirect".to_string()));
+        }
+
+        let code =
+            param("code").ok_or_else(|| LinearError::OAuth("redirect had no code".to_string()))?;
+
+        respond(
+            &mut stream,
+            "200 OK",
+            "Logged in to Linear. You can close this window.",
+        )
+        .await;
+
+        return Ok(code);
+    }
+}
+
+async fn respond(stream: &mut tokio::net::TcpStream, status: &str, body: &str) {
+    let response = format!(
+        "HTTP/1.1 {status}\r\nContent-Type: text/plain; charset=utf-8\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    let _ = stream.write_all(response.as_bytes()).await;
+}
+
+fn open_browser(url: &str) {
+    let result = if cfg!(target_os = "macos") {
+        std::process::Command::new("open").arg(url).spawn()
+    } else if cfg!(target_os = "windows") {
+        std::process::Command::new("cmd")
+            .args(["/C", "start", "", url])
+            .spawn()
+    } else {
+        std::process::Command::new("xdg-open").arg(url).spawn()
+    };
+
+    if result.is_err() {
+        eprintln!("Could not open a browser automatically.");
+    }
+}
+
+/// Random URL-safe token from `bytes` bytes of OS randomness
+fn random_token(bytes: usize) -> Result<String> {
+    let mut buffer = vec![0u8; bytes];
+    getrandom::getrandom(&mut buffer)
+        .map_err(|e| LinearError::OAuth(format!("no randomness available: {e}")))?;
+    Ok(URL_SAFE_NO_PAD.encode(buffer))
+}
+
+/// Write a file readable only by the current user
+pub fn write_private(path: &std::path::Path, contents: &[u8]) -> Result<()> {
+    #[cfg(unix)]
+    {
+        use std::io::Write;
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        // `mode` only applies to new files; tighten existing ones too
+        file.set_permissions(std::fs::Permissions::from_mode(0o600))?;
+        file.write_all(contents)?;
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    {
+        std::fs::write(path, contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_token_is_url_safe() {
+        let token = random_token(64).unwrap();
+        // 64 bytes -> 86 base64 chars, within PKCE's 43..=128 verifier length
+        assert_eq!(token.len(), 86);
+        assert!(token
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+    }
+
+    #[test]
+    fn test_token_response_keeps_previous_refresh_token() {
+        let previous = OAuthTokens {
+            access_token: "old".to_string(),
+            refresh_token: Some("refresh".to_string()),
+            expires_at: None,
+            scope: Some("read".to_string()),
+            client_id: "cid".to_string(),
+            organization_id: Some("org".to_string()),
+        };
+        let response: TokenResponse = serde_json::from_value(serde_json::json!({
+            "access_token": "new",
+            "expires_in": 3600,
+            "scope": ["read", "write"]
+        }))
+        .unwrap();
+
+        let tokens = OAuthTokens::from_response(response, "cid", Some(&previous));
+        assert_eq!(tokens.access_token, "new");
+        assert_eq!(tokens.refresh_token.as_deref(), Some("refresh"));
+        assert_eq!(tokens.scope.as_deref(), Some("read,write"));
+        assert_eq!(tokens.organization_id.as_deref(), Some("org"));
+        assert!(!tokens.is_expired());
+    }
+}
@@ -1,18 +1,192 @@
-use std::collections::HashMap;
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
 use std::time::{Duration, SystemTime};
 
+use clap::ValueEnum;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
 
+use crate::auth::Credentials;
 use crate::config::Config;
+use crate::error::{ErrorClass, LinearError};
 
-const CACHE_TTL_SECS: u64 = 3600; // 1 hour
+/// Version of the `cache.json` layout; older layouts are migrated on load
+const CACHE_VERSION: u32 = 3;
 
+/// Workspace the cache is keyed by, set once credentials are known
+static WORKSPACE: OnceLock<String> = OnceLock::new();
+
+/// Key the cache by the credentials and endpoint in use, so IDs fetched with
+/// one API key are never served to another workspace. OAuth logins are keyed
+/// by their organization; tokens without one leave the cache off. Until this
+/// is called `Cache::load` returns an empty cache that is never saved.
+pub fn set_workspace(credentials: &Credentials, endpoint: Option<&str>) {
+    let identity = match credentials {
+        Credentials::ApiKey(key) => format!("key:{key}"),
+        Credentials::OAuth(tokens) => match &tokens.organization_id {
+            Some(id) => format!("org:{id}"),
+            None => return,
+        },
+    };
+    let digest = Sha256::digest(format!("{}\n{identity}", endpoint.unwrap_or_default()));
+    let _ = WORKSPACE.set(format!("{digest:x}")[..16].to_string());
+}
+
+/// Short fingerprint of the workspace set by `set_workspace`
+pub fn workspace() -> Option<&'static str> {
+    WORKSPACE.get().map(String::as_str)
+}
+
+/// Kinds of cached entities, each with its own time-to-live
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Entity {
+    Teams,
+    States,
+    Cycles,
+    Labels,
+    Users,
+    Projects,
+}
+
+impl Entity {
+    pub const ALL: [Entity; 6] = [
+        Entity::Teams,
+        Entity::States,
+        Entity::Cycles,
+        Entity::Labels,
+        Entity::Users,
+        Entity::Projects,
+    ];
+
+    /// Teams, workflow states and users rarely change; labels, projects and
+    /// cycles are created as work goes on
+    pub fn ttl(self) -> Duration {
+        match self {
+            Entity::Teams | Entity::States | Entity::Users => Duration::from_secs(24 * 3600),
+            Entity::Labels | Entity::Projects | Entity::Cycles => Duration::from_secs(3600),
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Entity::Teams => "teams",
+            Entity::States => "states",
+            Entity::Cycles => "cycles",
+            Entity::Labels => "labels",
+            Entity::Users => "users",
+            Entity::Projects => "projects",
+        }
+    }
+}
+
+/// Entries of one entity kind, expired together `ttl` after the first was added
+#[derive(Serialize, Deserialize, Clone)]
+#[serde(default)]
+struct Section<T> {
+    fetched_at: u64,
+    entries: BTreeMap<String, T>,
+    /// Replaced wholesale since loading, rather than added to
+    #[serde(skip)]
+    replaced: bool,
+}
+
+impl<T> Default for Section<T> {
+    fn default() -> Self {
+        Self {
+            fetched_at: 0,
+            entries: BTreeMap::new(),
+            replaced: false,
+        }
+    }
+}
+
+impl<T: Clone> Section<T> {
+    fn is_fresh(&self, entity: Entity, now: u64) -> bool {
+        now.saturating_sub(self.fetched_at) <= entity.ttl().as_secs()
+    }
+
+    fn insert(&mut self, key: String, value: T) {
+        if self.entries.is_empty() {
+            self.fetched_at = now();
+        }
+        self.entries.insert(key, value);
+    }
+
+    fn replace(&mut self, entries: impl IntoIterator<Item = (String, T)>) {
+        self.entries = entries.into_iter().collect();
+        self.fetched_at = now();
+        self.replaced = true;
+    }
+
+    /// Apply this section's changes on top of `disk`, the copy another
+    /// process may have saved since this one was loaded
+    fn merge_into(&self, disk: &mut Section<T>) {
+        if self.replaced || disk.entries.is_empty() {
+            *disk = self.clone();
+            return;
+        }
+        if !self.entries.is_empty() {
+            // Keep the older timestamp so merged entries never outlive the TTL
+            disk.fetched_at = disk.fetched_at.min(self.fetched_at);
+            disk.entries
+                .extend(self.entries.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+    }
+
+    fn status(&self, entity: Entity, team: Option<&str>, now: u64) -> SectionStatus {
+        let age = now.saturating_sub(self.fetched_at);
+        SectionStatus {
+            entity: entity.name(),
+            team: team.map(str::to_string),
+            entries: self.entries.len(),
+            age_secs: age,
+            expires_in_secs: entity.ttl().as_secs().saturating_sub(age),
+        }
+    }
+}
+
+/// Summary of one cached section, for `linear cache status`
+#[derive(Serialize)]
+pub struct SectionStatus {
+    pub entity: &'static str,
+    pub team: Option<String>,
+    pub entries: usize,
+    pub age_secs: u64,
+    pub expires_in_secs: u64,
+}
+
+/// On-disk layout of `cache.json`: one `Cache` per workspace
 #[derive(Serialize, Deserialize, Default)]
-pub struct Cache {
-    teams: HashMap<String, CachedTeam>,
+struct CacheFile {
+    version: u32,
     #[serde(default)]
-    timestamp: u64,
+    workspaces: BTreeMap<String, Cache>,
+}
+
+/// Name-to-ID lookups for the current profile and workspace, so that
+/// resolving teams, statuses, labels, cycles, users and projects doesn't need
+/// a request. Team keys are matched case-insensitively.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Cache {
+    teams: Section<CachedTeam>,
+    /// Keyed by team key, then lowercase state name
+    states: BTreeMap<String, Section<CachedState>>,
+    /// Keyed by team key, then cycle number
+    cycles: BTreeMap<String, Section<CachedCycle>>,
+    /// Keyed by `TEAM/name` (lowercase name), or `/name` for workspace labels
+    labels: Section<CachedLabel>,
+    users: Section<CachedUser>,
+    projects: Section<CachedProject>,
+    /// Where `save` writes; unset for a cache that wasn't loaded from disk
+    #[serde(skip)]
+    path: Option<PathBuf>,
+    /// Entities invalidated since loading, which `save` clears on disk too
+    #[serde(skip)]
+    cleared: Vec<Entity>,
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -22,8 +196,64 @@ pub struct CachedTeam {
     pub name: String,
 }
 
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedState {
+    pub id: String,
+    pub name: String,
+    /// State type: "backlog", "unstarted", "started", "completed", or "canceled"
+    #[serde(rename = "type")]
+    pub state_type: String,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedCycle {
+    pub id: String,
+    pub number: i32,
+    pub name: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedLabel {
+    pub id: String,
+    pub name: String,
+    /// Team the label belongs to; `None` for workspace labels
+    pub team: Option<LabelTeam>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct LabelTeam {
+    pub key: String,
+}
+
+impl CachedLabel {
+    fn key(&self) -> String {
+        label_key(self.team.as_ref().map(|t| t.key.as_str()), &self.name)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedUser {
+    pub id: String,
+    pub name: String,
+    #[serde(rename = "displayName")]
+    pub display_name: String,
+    pub email: String,
+    /// Whether this is the authenticated user
+    #[serde(rename = "isMe", default)]
+    pub is_me: bool,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+pub struct CachedProject {
+    pub id: String,
+    pub name: String,
+}
+
 impl Cache {
     pub fn load() -> Self {
+        let Some(workspace) = workspace() else {
+            return Self::default();
+        };
         let path = match Self::cache_path() {
             Ok(p) => p,
             Err(_) => {
@@ -32,83 +262,501 @@ impl Cache {
             }
         };
 
-        if !path.exists() {
-            return Self::default();
-        }
-
-        let contents = match std::fs::read_to_string(&path) {
-            Ok(c) => c,
+        // Saves replace the file atomically, so reading needs no lock
+        let mut cache = match read_file(&path) {
+            Ok(Some(mut file)) => file.workspaces.remove(workspace).unwrap_or_default(),
+            Ok(None) => {
+                eprintln!("Warning: Cache file was written by a newer version; not using it");
+                return Self::default();
+            }
             Err(e) => {
                 eprintln!("Warning: Could not read cache file: {e}");
-                return Self::default();
+                Self::default()
             }
         };
 
-        let cache: Self = match serde_json::from_str(&contents) {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("Warning: Could not parse cache file: {e}");
-                return Self::default();
-            }
+        cache.path = Some(path);
+        cache.expire(now());
+        cache
+    }
+
+    /// Merge this cache's changes into `cache.json` under an exclusive lock,
+    /// so parallel `linear` processes neither interleave writes nor drop each
+    /// other's entries
+    pub fn save(&self) {
+        let (Some(path), Some(workspace)) = (&self.path, workspace()) else {
+            return;
+        };
+
+        if let Err(e) = self.write(path, workspace) {
+            eprintln!("Warning: Could not write cache file: {e}");
+        }
+    }
+
+    fn write(&self, path: &Path, workspace: &str) -> std::io::Result<()> {
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        let lock = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(sibling(path, "lock"))?;
+        lock.lock()?;
+
+        // A corrupt file is rebuilt; one from a newer version is left alone
+        let mut file = match read_file(path) {
+            Ok(Some(file)) => file,
+            Ok(None) => return Ok(()),
+            Err(_) => CacheFile::default(),
         };
 
-        // Check if cache is expired
-        let now = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_secs();
+        let now = now();
+        let mut merged = file.workspaces.remove(workspace).unwrap_or_default();
+        merged.expire(now);
+        self.merge_into(&mut merged);
+        file.workspaces.insert(workspace.to_string(), merged);
 
-        if now - cache.timestamp > CACHE_TTL_SECS {
-            return Self::default();
+        for cache in file.workspaces.values_mut() {
+            cache.expire(now);
         }
+        file.workspaces.retain(|_, cache| !cache.is_empty());
+        file.version = CACHE_VERSION;
 
-        cache
+        let temp = sibling(path, "tmp");
+        let contents = serde_json::to_string_pretty(&file)?;
+        std::fs::write(&temp, contents)?;
+        std::fs::rename(&temp, path)?;
+
+        // The lock is released when `lock` is dropped
+        Ok(())
     }
 
-    pub fn save(&self) {
-        let path = match Self::cache_path() {
-            Ok(p) => p,
-            Err(_) => {
-                eprintln!("Warning: Could not determine cache path for saving");
-                return;
-            }
+    fn merge_into(&self, disk: &mut Cache) {
+        for entity in &self.cleared {
+            disk.invalidate(*entity);
+        }
+
+        self.teams.merge_into(&mut disk.teams);
+        for (team, section) in &self.states {
+            section.merge_into(disk.states.entry(team.clone()).or_default());
+        }
+        for (team, section) in &self.cycles {
+            section.merge_into(disk.cycles.entry(team.clone()).or_default());
+        }
+        self.labels.merge_into(&mut disk.labels);
+        self.users.merge_into(&mut disk.users);
+        self.projects.merge_into(&mut disk.projects);
+    }
+
+    fn is_empty(&self) -> bool {
+        self.teams.entries.is_empty()
+            && self.states.values().all(|s| s.entries.is_empty())
+            && self.cycles.values().all(|s| s.entries.is_empty())
+            && self.labels.entries.is_empty()
+            && self.users.entries.is_empty()
+            && self.projects.entries.is_empty()
+    }
+
+    pub fn cache_path() -> Result<PathBuf, ()> {
+        Config::profile_path("cache.json").map_err(|_| ())
+    }
+
+    /// Delete the cache for every workspace of the current profile
+    pub fn remove() -> std::io::Result<()> {
+        let Ok(path) = Self::cache_path() else {
+            return Ok(());
         };
 
-        if let Some(parent) = path.parent() {
-            if let Err(e) = std::fs::create_dir_all(parent) {
-                eprintln!("Warning: Could not create cache directory: {e}");
-                return;
+        for file in [sibling(&path, "lock"), path] {
+            match std::fs::remove_file(&file) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e),
+                _ => {}
             }
         }
 
-        let contents = match serde_json::to_string_pretty(self) {
-            Ok(c) => c,
-            Err(e) => {
-                eprintln!("Warning: Could not serialize cache: {e}");
-                return;
-            }
-        };
+        Ok(())
+    }
 
-        if let Err(e) = std::fs::write(&path, contents) {
-            eprintln!("Warning: Could not write cache file: {e}");
+    /// Drop every section older than its entity's TTL
+    fn expire(&mut self, now: u64) {
+        if !self.teams.is_fresh(Entity::Teams, now) {
+            self.teams = Section::default();
+        }
+        self.states
+            .retain(|_, section| section.is_fresh(Entity::States, now));
+        self.cycles
+            .retain(|_, section| section.is_fresh(Entity::Cycles, now));
+        if !self.labels.is_fresh(Entity::Labels, now) {
+            self.labels = Section::default();
+        }
+        if !self.users.is_fresh(Entity::Users, now) {
+            self.users = Section::default();
+        }
+        if !self.projects.is_fresh(Entity::Projects, now) {
+            self.projects = Section::default();
         }
     }
 
-    fn cache_path() -> Result<PathBuf, ()> {
-        Config::config_path()
-            .map(|p| p.with_file_name("cache.json"))
-            .map_err(|_| ())
+    pub fn invalidate(&mut self, entity: Entity) {
+        if !self.cleared.contains(&entity) {
+            self.cleared.push(entity);
+        }
+        match entity {
+            Entity::Teams => self.teams = Section::default(),
+            Entity::States => self.states.clear(),
+            Entity::Cycles => self.cycles.clear(),
+            Entity::Labels => self.labels = Section::default(),
+            Entity::Users => self.users = Section::default(),
+            Entity::Projects => self.projects = Section::default(),
+        }
+    }
+
+    /// Forget `entities` when a mutation using their IDs was rejected, since
+    /// a cached ID may have gone stale; the next run looks them up again
+    pub fn forget_if_rejected(&mut self, error: &LinearError, entities: &[Entity]) {
+        if matches!(error.class(), ErrorClass::Validation | ErrorClass::NotFound) {
+            for entity in entities {
+                self.invalidate(*entity);
+            }
+            self.save();
+        }
+    }
+
+    pub fn status(&self) -> Vec<SectionStatus> {
+        let now = now();
+        let mut sections = vec![self.teams.status(Entity::Teams, None, now)];
+        sections.extend(
+            self.states
+                .iter()
+                .map(|(team, section)| section.status(Entity::States, Some(team), now)),
+        );
+        sections.extend(
+            self.cycles
+                .iter()
+                .map(|(team, section)| section.status(Entity::Cycles, Some(team), now)),
+        );
+        sections.push(self.labels.status(Entity::Labels, None, now));
+        sections.push(self.users.status(Entity::Users, None, now));
+        sections.push(self.projects.status(Entity::Projects, None, now));
+
+        sections.retain(|section| section.entries > 0);
+        sections
     }
 
     pub fn set_team(&mut self, team: CachedTeam) {
-        self.teams.insert(team.key.clone(), team);
-        self.timestamp = SystemTime::now()
-            .duration_since(SystemTime::UNIX_EPOCH)
-            .unwrap_or(Duration::ZERO)
-            .as_secs();
+        self.teams.insert(team.key.to_uppercase(), team);
+    }
+
+    pub fn set_teams(&mut self, teams: Vec<CachedTeam>) {
+        self.teams
+            .replace(teams.into_iter().map(|t| (t.key.to_uppercase(), t)));
+    }
+
+    pub fn teams(&self) -> impl Iterator<Item = &CachedTeam> {
+        self.teams.entries.values()
     }
 
     pub fn get_team_id(&self, key: &str) -> Option<String> {
-        self.teams.get(key).map(|t| t.id.clone())
+        self.teams
+            .entries
+            .get(&key.to_uppercase())
+            .map(|t| t.id.clone())
+    }
+
+    /// Replace a team's workflow states
+    pub fn set_states(&mut self, team_key: &str, states: Vec<CachedState>) {
+        self.states
+            .entry(team_key.to_uppercase())
+            .or_default()
+            .replace(states.into_iter().map(|s| (s.name.to_lowercase(), s)));
+    }
+
+    /// Find a team's workflow state by name (case-insensitive)
+    pub fn state(&self, team_key: &str, name: &str) -> Option<&CachedState> {
+        self.states
+            .get(&team_key.to_uppercase())?
+            .entries
+            .get(&name.to_lowercase())
+    }
+
+    /// First state of the given type ("completed", "canceled", ...)
+    pub fn state_of_type(&self, team_key: &str, state_type: &str) -> Option<&CachedState> {
+        self.states
+            .get(&team_key.to_uppercase())?
+            .entries
+            .values()
+            .find(|s| s.state_type == state_type)
+    }
+
+    pub fn set_cycle(&mut self, team_key: &str, cycle: CachedCycle) {
+        self.cycles
+            .entry(team_key.to_uppercase())
+            .or_default()
+            .insert(cycle.number.to_string(), cycle);
+    }
+
+    pub fn set_cycles(&mut self, team_key: &str, cycles: Vec<CachedCycle>) {
+        self.cycles
+            .entry(team_key.to_uppercase())
+            .or_default()
+            .replace(cycles.into_iter().map(|c| (c.number.to_string(), c)));
+    }
+
+    pub fn cycle_id(&self, team_key: &str, number: i32) -> Option<String> {
+        let section = self.cycles.get(&team_key.to_uppercase())?;
+        section
+            .entries
+            .get(&number.to_string())
+            .map(|c| c.id.clone())
+    }
+
+    pub fn set_label(&mut self, label: CachedLabel) {
+        self.labels.insert(label.key(), label);
+    }
+
+    pub fn set_labels(&mut self, labels: Vec<CachedLabel>) {
+        self.labels
+            .replace(labels.into_iter().map(|l| (l.key(), l)));
+    }
+
+    /// Find a label ID by name (case-insensitive) for an issue in `team_key`:
+    /// the team's own label, else a workspace label. Without a team only
+    /// workspace labels match.
+    pub fn label_id(&self, team_key: Option<&str>, name: &str) -> Option<String> {
+        team_key
+            .and_then(|key| self.labels.entries.get(&label_key(Some(key), name)))
+            .or_else(|| self.labels.entries.get(&label_key(None, name)))
+            .map(|l| l.id.clone())
+    }
+
+    pub fn set_user(&mut self, user: CachedUser) {
+        self.users.insert(user.id.clone(), user);
+    }
+
+    pub fn set_users(&mut self, users: Vec<CachedUser>) {
+        self.users
+            .replace(users.into_iter().map(|u| (u.id.clone(), u)));
+    }
+
+    pub fn viewer_id(&self) -> Option<String> {
+        self.users
+            .entries
+            .values()
+            .find(|u| u.is_me)
+            .map(|u| u.id.clone())
+    }
+
+    /// Find a user ID by name, display name or email (case-insensitive)
+    pub fn user_id(&self, query: &str) -> Option<String> {
+        let query = query.to_lowercase();
+        self.users
+            .entries
+            .values()
+            .find(|u| {
+                u.name.to_lowercase() == query
+                    || u.display_name.to_lowercase() == query
+                    || u.email.to_lowercase() == query
+            })
+            .map(|u| u.id.clone())
+    }
+
+    pub fn set_project(&mut self, project: CachedProject) {
+        self.projects.insert(project.name.to_lowercase(), project);
+    }
+
+    pub fn set_projects(&mut self, projects: Vec<CachedProject>) {
+        self.projects
+            .replace(projects.into_iter().map(|p| (p.name.to_lowercase(), p)));
+    }
+
+    /// Find a project ID by name (case-insensitive)
+    pub fn project_id(&self, name: &str) -> Option<String> {
+        self.projects
+            .entries
+            .get(&name.to_lowercase())
+            .map(|p| p.id.clone())
+    }
+}
+
+/// Read `cache.json`, migrating older layouts. `None` means the file was
+/// written by a newer version and must not be touched.
+fn read_file(path: &Path) -> std::io::Result<Option<CacheFile>> {
+    let contents = match std::fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(Some(CacheFile::default()))
+        }
+        Err(e) => return Err(e),
+    };
+
+    let value: Value = serde_json::from_str(&contents)?;
+    let Some(value) = migrate(value) else {
+        return Ok(None);
+    };
+
+    Ok(Some(serde_json::from_value(value)?))
+}
+
+/// Bring a parsed cache file up to `CACHE_VERSION`, or `None` if it is newer
+fn migrate(mut value: Value) -> Option<Value> {
+    let version = value.get("version").and_then(Value::as_u64).unwrap_or(1);
+
+    match version {
+        // Version 1 files (no "version" field) weren't keyed by workspace,
+        // so there is no telling whose IDs they hold; start afresh
+        1 => Some(serde_json::json!({ "version": CACHE_VERSION })),
+        // Version 2 keyed labels by name alone, mixing up teams' labels
+        2 => {
+            if let Some(workspaces) = value.get_mut("workspaces").and_then(Value::as_object_mut) {
+                for cache in workspaces.values_mut() {
+                    if let Some(cache) = cache.as_object_mut() {
+                        cache.remove("labels");
+                    }
+                }
+            }
+            value["version"] = CACHE_VERSION.into();
+            Some(value)
+        }
+        v if v == u64::from(CACHE_VERSION) => Some(value),
+        _ => None,
+    }
+}
+
+/// Labels with the same name may exist in several teams and the workspace
+fn label_key(team_key: Option<&str>, name: &str) -> String {
+    format!(
+        "{}/{}",
+        team_key.unwrap_or_default().to_uppercase(),
+        name.to_lowercase()
+    )
+}
+
+/// `cache.json` -> `cache.json.<extension>` in the same directory
+fn sibling(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(extension);
+    path.with_file_name(name)
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::ZERO)
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(name: &str) -> CachedLabel {
+        CachedLabel {
+            id: format!("id-{name}"),
+            name: name.to_string(),
+            team: None,
+        }
+    }
+
+    #[test]
+    fn test_sections_expire_by_entity_ttl() {
+        let mut cache = Cache::default();
+        cache.set_label(label("Bug"));
+        cache.set_states(
+            "ENG",
+            vec![CachedState {
+                id: "s1".to_string(),
+                name: "Done".to_string(),
+                state_type: "completed".to_string(),
+            }],
+        );
+
+        // Two hours on, labels (1h) have expired but states (24h) haven't
+        cache.expire(now() + 2 * 3600);
+
+        assert_eq!(cache.label_id(None, "bug"), None);
+        assert_eq!(
+            cache.state("ENG", "done").map(|s| s.id.as_str()),
+            Some("s1")
+        );
+    }
+
+    #[test]
+    fn test_migrate() {
+        // Unversioned caches can't be attributed to a workspace
+        let v1 = serde_json::json!({ "labels": { "fetched_at": 1, "entries": {} } });
+        assert_eq!(
+            migrate(v1),
+            Some(serde_json::json!({ "version": CACHE_VERSION }))
+        );
+
+        let current = serde_json::json!({ "version": CACHE_VERSION, "workspaces": {} });
+        assert_eq!(migrate(current.clone()), Some(current));
+
+        // Labels keyed by name alone are dropped; the rest is kept
+        let v2 = serde_json::json!({ "version": 2, "workspaces": { "w": {
+            "labels": { "fetched_at": 1, "entries": {} },
+            "users": { "fetched_at": 1, "entries": {} }
+        } } });
+        assert_eq!(
+            migrate(v2),
+            Some(
+                serde_json::json!({ "version": CACHE_VERSION, "workspaces": { "w": {
+                "users": { "fetched_at": 1, "entries": {} }
+            } } })
+            )
+        );
+
+        assert_eq!(migrate(serde_json::json!({ "version": 99 })), None);
+    }
+
+    #[test]
+    fn test_labels_are_scoped_by_team() {
+        let mut cache = Cache::default();
+        for (team, id) in [(Some("ENG"), "eng-bug"), (Some("OPS"), "ops-bug")] {
+            cache.set_label(CachedLabel {
+                id: id.to_string(),
+                name: "Bug".to_string(),
+                team: team.map(|key| LabelTeam {
+                    key: key.to_string(),
+                }),
+            });
+        }
+        cache.set_label(label("Chore"));
+
+        assert_eq!(
+            cache.label_id(Some("ops"), "bug").as_deref(),
+            Some("ops-bug")
+        );
+        assert_eq!(
+            cache.label_id(Some("ENG"), "BUG").as_deref(),
+            Some("eng-bug")
+        );
+        assert_eq!(cache.label_id(Some("WEB"), "bug"), None);
+        assert_eq!(cache.label_id(None, "bug"), None);
+        assert_eq!(
+            cache.label_id(Some("OPS"), "chore").as_deref(),
+            Some("id-Chore")
+        );
+    }
+
+    #[test]
+    fn test_save_merges_with_concurrent_writes() {
+        // Another process cached labels and states after this one loaded
+        let mut disk = Cache::default();
+        disk.set_label(label("Bug"));
+        disk.set_label(label("Feature"));
+        disk.set_states("ENG", Vec::new());
+
+        let mut ours = Cache::default();
+        ours.set_label(label("Chore"));
+        ours.invalidate(Entity::States);
+        ours.merge_into(&mut disk);
+
+        assert!(disk.label_id(None, "bug").is_some());
+        assert!(disk.label_id(None, "chore").is_some());
+        assert!(disk.states.is_empty());
     }
 }
@@ -0,0 +1,176 @@
+//! Record/replay of GraphQL traffic for offline and deterministic runs.
+//!
+//! A cassette is a JSON file holding request/response pairs. In record mode
+//! every request sent by `LinearClient::query` is appended to the file; in
+//! replay mode requests are answered from the file without touching the network.
+
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{LinearError, Result};
+
+/// Whether a cassette is being written or read.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    Record,
+    Replay,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct CassetteFile {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct Interaction {
+    request: RecordedRequest,
+    response: RecordedResponse,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
+struct RecordedRequest {
+    query: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    variables: Option<serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Clone)]
+struct RecordedResponse {
+    status: u16,
+    /// Response body; stored as parsed JSON when possible for readability
+    body: serde_json::Value,
+}
+
+pub struct Cassette {
+    path: PathBuf,
+    mode: CassetteMode,
+    interactions: Vec<Interaction>,
+    /// Replay bookkeeping: which interactions have already been served
+    used: Vec<bool>,
+}
+
+impl Cassette {
+    /// Open a cassette. Replay requires the file to exist; record starts empty.
+    pub fn open(path: impl Into<PathBuf>, mode: CassetteMode) -> Result<Self> {
+        let path = path.into();
+
+        let interactions = match mode {
+            CassetteMode::Record => Vec::new(),
+            CassetteMode::Replay => {
+                let contents =
+                    std::fs::read_to_string(&path).map_err(|e| LinearError::CassetteRead {
+                        path: path.clone(),
+                        source: e,
+                    })?;
+                serde_json::from_str::<CassetteFile>(&contents)?.interactions
+            }
+        };
+
+        Ok(Self {
+            used: vec![false; interactions.len()],
+            path,
+            mode,
+            interactions,
+        })
+    }
+
+    /// Build a cassette from `LINEAR_CASSETTE` and `LINEAR_CASSETTE_MODE` env vars.
+    ///
+    /// Mode defaults to `replay` when only a path is given.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Ok(path) = std::env::var("LINEAR_CASSETTE") else {
+            return Ok(None);
+        };
+
+        let mode = match std::env::var("LINEAR_CASSETTE_MODE") {
+            Ok(m) => m.parse()?,
+            Err(_) => CassetteMode::Replay,
+        };
+
+        Self::open(path, mode).map(Some)
+    }
+
+    pub fn mode(&self) -> CassetteMode {
+        self.mode
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Find the next unused interaction matching this request.
+    ///
+    /// Interactions are served in recorded order, so repeated identical
+    /// requests replay their successive responses.
+    pub fn replay(
+        &mut self,
+        query: &str,
+        variables: Option<&serde_json::Value>,
+    ) -> Option<(u16, String)> {
+        let position = self.interactions.iter().enumerate().position(|(i, int)| {
+            !self.used[i]
+                && int.request.query == query
+                && int.request.variables.as_ref() == variables
+        })?;
+
+        self.used[position] = true;
+        let response = &self.interactions[position].response;
+        let body = match &response.body {
+            serde_json::Value::String(s) => s.clone(),
+            other => other.to_string(),
+        };
+
+        Some((response.status, body))
+    }
+
+    /// Append an interaction and flush the cassette to disk.
+    pub fn record(
+        &mut self,
+        query: &str,
+        variables: Option<&serde_json::Value>,
+        status: u16,
+        body: &str,
+    ) -> Result<()> {
+        let body = serde_json::from_str(body)
+            .unwrap_or_else(|_| serde_json::Value::String(body.to_string()));
+
+        self.interactions.push(Interaction {
+            request: RecordedRequest {
+                query: query.to_string(),
+                variables: variables.cloned(),
+            },
+            response: RecordedResponse { status, body },
+        });
+        self.used.push(true);
+
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            if !parent.as_os_str().is_empty() {
+                std::fs::create_dir_all(parent)?;
+            }
+        }
+
+        let file = CassetteFile {
+            interactions: self.interactions.clone(),
+        };
+        std::fs::write(&self.path, serde_json::to_string_pretty(&file)?)?;
+
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for CassetteMode {
+    type Err = LinearError;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "record" => Ok(CassetteMode::Record),
+            "replay" => Ok(CassetteMode::Replay),
+            _ => Err(LinearError::InvalidCassetteMode(s.to_string())),
+        }
+    }
+}
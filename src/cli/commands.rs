@@ -1,11 +1,16 @@
+use std::ffi::OsString;
 use std::path::PathBuf;
 
 use clap::{Args, Parser, Subcommand, ValueEnum};
 use clap_complete::Shell;
+use serde::{Deserialize, Serialize};
 
+use crate::cache::Entity;
+use crate::pagination::PageLimit;
 use crate::types::{IssueRelationType, Priority};
 
-#[derive(Debug, Clone, Copy, Default, ValueEnum)]
+#[derive(Debug, Clone, Copy, Default, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum OutputFormat {
     Table,
     #[default]
@@ -13,6 +18,32 @@ pub enum OutputFormat {
     Compact,
 }
 
+/// Order of `issues` results, newest first
+#[derive(Debug, Clone, Copy, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueSort {
+    Created,
+    Updated,
+}
+
+/// Columns `issues` can show in table output
+#[derive(Debug, Clone, Copy, PartialEq, ValueEnum, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IssueColumn {
+    Id,
+    Title,
+    Status,
+    Priority,
+    Estimate,
+    Assignee,
+    Team,
+    Project,
+    Cycle,
+    Labels,
+    Created,
+    Updated,
+}
+
 #[derive(Parser)]
 #[command(name = "linear")]
 #[command(about = "A CLI for Linear issue tracking", version)]
@@ -26,9 +57,9 @@ pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
 
-    /// Output format (table, json, compact)
-    #[arg(long, short = 'o', global = true, value_enum, default_value = "json")]
-    pub format: OutputFormat,
+    /// Output format (table, json, compact) [default: `format` in config.toml, else json]
+    #[arg(long, short = 'o', global = true, value_enum)]
+    pub format: Option<OutputFormat>,
 
     /// Output as JSON (alias for --format json)
     #[arg(long, global = true, hide = true)]
@@ -41,17 +72,51 @@ pub struct Cli {
     /// Show detailed error information
     #[arg(long, short, global = true)]
     pub verbose: bool,
+
+    /// Use a named profile from config.toml ([profiles.<name>])
+    #[arg(long, global = true, env = "LINEAR_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Trace GraphQL requests, timing and retries to stderr (secrets redacted)
+    #[arg(long, global = true, env = "LINEAR_DEBUG")]
+    pub debug: bool,
+
+    /// Write debug trace to this file instead of stderr (implies --debug)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub debug_log: Option<PathBuf>,
 }
 
 impl Cli {
-    /// Get the effective output format, considering --json flag
-    pub fn output_format(&self) -> OutputFormat {
+    /// Output format given on the command line, considering --json flag
+    pub fn format_flag(&self) -> Option<OutputFormat> {
         if self.json {
-            OutputFormat::Json
+            Some(OutputFormat::Json)
         } else {
             self.format
         }
     }
+
+    /// Output format picked out of the raw arguments, for errors raised
+    /// before they are parsed (e.g. a broken alias)
+    pub fn raw_format_flag(args: &[OsString]) -> Option<OutputFormat> {
+        let mut format = None;
+        let mut args = args.iter().skip(1).map(|arg| arg.to_string_lossy());
+        while let Some(arg) = args.next() {
+            let value = match arg.as_ref() {
+                "--" => break,
+                "--json" => return Some(OutputFormat::Json),
+                "--format" | "-o" => args.next().map(|value| value.into_owned()),
+                arg => arg
+                    .strip_prefix("--format=")
+                    .or_else(|| arg.strip_prefix("-o"))
+                    .map(|value| value.trim_start_matches('=').to_string()),
+            };
+            if let Some(value) = value {
+                format = OutputFormat::from_str(&value, true).ok().or(format);
+            }
+        }
+        format
+    }
 }
 
 #[derive(Subcommand)]
@@ -76,28 +141,53 @@ pub enum Commands {
         after_help = "EXAMPLES:
     linear issues --mine
     linear issues --team ENG --status \"In Progress\"
-    linear issues --project \"Backend\" --limit 50"
+    linear issues --project \"Backend\" --limit 50
+    linear issues --any-project --mine
+    linear issues --label bug --label backend --sort updated
+    linear issues --columns id,title,assignee,updated
+    linear issues --view triage
+    linear issues --team ENG --offline"
     )]
     Issues(IssueListArgs),
+    /// Manage saved issue filters, used with `linear issues --view NAME`
+    #[command(after_help = "EXAMPLES:
+    linear view save triage --team ENG --status Triage --sort created
+    linear view save standup --mine --columns id,title,status --limit 20
+    linear view list
+    linear view show triage
+    linear issues --view triage --limit 100
+    linear view delete triage")]
+    View {
+        #[command(subcommand)]
+        action: ViewCommands,
+    },
     /// List teams
     #[command(
         alias = "t",
         after_help = "EXAMPLES:
     linear teams
-    linear teams --format json"
+    linear teams --format json
+    linear teams --all"
     )]
-    Teams,
+    Teams {
+        #[command(flatten)]
+        page: PageArgs,
+    },
     /// List projects
     #[command(
         alias = "p",
         after_help = "EXAMPLES:
     linear projects
-    linear projects --team ENG"
+    linear projects --team ENG
+    linear projects --all"
     )]
     Projects {
         /// Filter by team key (e.g., ENG)
         #[arg(long)]
         team: Option<String>,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
     /// Manage cycles/sprints
     #[command(after_help = "EXAMPLES:
@@ -118,13 +208,84 @@ pub enum Commands {
         alias = "l",
         after_help = "EXAMPLES:
     linear labels
-    linear labels --team ENG"
+    linear labels --team ENG
+    linear labels --all"
     )]
     Labels {
         /// Filter by team key (e.g., ENG)
         #[arg(long)]
         team: Option<String>,
+
+        #[command(flatten)]
+        page: PageArgs,
+    },
+    /// Send a raw GraphQL query or mutation and print the JSON response
+    #[command(after_help = "EXAMPLES:
+    linear api 'query { viewer { id name } }'
+    linear api --file issue.graphql -F id=ENG-123
+    echo 'query { teams { nodes { key } } }' | linear api
+    linear api -F input.teamId=abc -f input.title=Bug \\
+        'mutation($input: IssueCreateInput!) { issueCreate(input: $input) { success } }'
+    linear api --paginate issues \\
+        'query($first: Int, $after: String) { issues(first: $first, after: $after) {
+            nodes { identifier } pageInfo { hasNextPage endCursor } } }'")]
+    Api(ApiArgs),
+    /// Log in with OAuth, or inspect/remove stored credentials
+    #[command(after_help = "EXAMPLES:
+    linear auth login
+    linear auth status
+    linear auth logout")]
+    Auth {
+        #[command(subcommand)]
+        action: AuthCommands,
     },
+    /// Inspect, refresh or clear the cache of teams, states, labels and other lookups
+    #[command(after_help = "EXAMPLES:
+    linear cache status
+    linear cache refresh
+    linear cache refresh labels projects
+    linear cache clear")]
+    Cache {
+        #[command(subcommand)]
+        action: CacheCommands,
+    },
+    /// View and change settings in config.toml
+    #[command(after_help = "EXAMPLES:
+    linear config list
+    linear config get default_team
+    linear config set default_team ENG
+    linear config set network.ca_certs ~/corp.pem,~/extra.pem
+    linear --profile oss config set default_team OSS
+    linear config unset api_url
+    linear config edit")]
+    Config {
+        #[command(subcommand)]
+        action: ConfigCommands,
+    },
+    /// Mirror issues and comments locally for use with --offline
+    #[command(after_help = "EXAMPLES:
+    linear sync --team ENG
+    linear sync
+    linear sync --team ENG --full
+    linear issues --team ENG --status \"In Progress\" --offline
+    linear issue view ENG-123 --offline")]
+    Sync(SyncArgs),
+    /// Full-text search over mirrored issues and comments
+    #[command(after_help = "EXAMPLES:
+    linear search --local login bug
+    linear search --local '\"login page\"' label:bug
+    linear search --local 'title:\"rate limit\"' --team ENG
+    linear search --local crash OR panic
+    linear search --local auth*
+
+Words must all match; OR matches either side. Fields: id, title,
+description, label, comment.")]
+    Search(SearchArgs),
+    /// Show API rate-limit budget
+    #[command(after_help = "EXAMPLES:
+    linear limits
+    linear limits --format table")]
+    Limits,
     /// Generate shell completions
     #[command(after_help = "EXAMPLES:
     linear completions bash > ~/.bash_completion.d/linear
@@ -134,10 +295,88 @@ pub enum Commands {
         /// Shell to generate completions for
         shell: Shell,
     },
-    /// Initialize configuration file interactively
+    /// Set up the config file: check the API key, then pick a default team
     #[command(after_help = "EXAMPLES:
-    linear init")]
-    Init,
+    linear init
+    linear --profile oss init
+    linear init --api-key lin_api_xxx --team ENG
+    echo \"$LINEAR_KEY\" | linear init --api-key - --team ENG
+
+With --api-key nothing is prompted for, e.g. when provisioning a container.")]
+    Init(InitArgs),
+}
+
+#[derive(Subcommand)]
+pub enum CacheCommands {
+    /// Show what is cached and when it expires
+    Status,
+    /// Fetch entities from Linear and replace the cached copies
+    Refresh {
+        /// Entities to refresh (all if omitted)
+        #[arg(value_enum)]
+        entities: Vec<Entity>,
+    },
+    /// Delete cached entities
+    Clear {
+        /// Entities to clear (all if omitted)
+        #[arg(value_enum)]
+        entities: Vec<Entity>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommands {
+    /// Print a setting's effective value and where it came from
+    Get {
+        /// Dotted key, e.g. default_team or network.proxy
+        key: String,
+    },
+    /// Set a value in config.toml (in the selected profile for per-profile keys)
+    Set {
+        /// Dotted key, e.g. default_team or network.proxy
+        key: String,
+        /// New value; lists are comma-separated
+        value: String,
+    },
+    /// Remove a value from config.toml
+    Unset {
+        /// Dotted key, e.g. default_team or network.proxy
+        key: String,
+    },
+    /// List every setting with its effective value and source
+    List,
+    /// Open config.toml in $VISUAL or $EDITOR, then check it
+    Edit,
+    /// Print the path of config.toml
+    Path,
+}
+
+#[derive(Subcommand)]
+pub enum ViewCommands {
+    /// List saved views
+    List,
+    /// Show a saved view's filters
+    Show {
+        /// View name
+        name: String,
+    },
+    /// Save the given filters as a view in config.toml, replacing any of the same name
+    Save(ViewSaveArgs),
+    /// Remove a saved view from config.toml
+    Delete {
+        /// View name
+        name: String,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum AuthCommands {
+    /// Log in via the browser (OAuth2 with PKCE)
+    Login,
+    /// Show the active credentials and the user they belong to
+    Status,
+    /// Revoke and delete stored OAuth tokens
+    Logout,
 }
 
 #[derive(Subcommand)]
@@ -155,7 +394,8 @@ pub enum IssueCommands {
         alias = "v",
         visible_alias = "show",
         after_help = "EXAMPLES:
-    linear issue view ENG-123"
+    linear issue view ENG-123
+    linear issue view ENG-123 --offline"
     )]
     View(IssueViewArgs),
     /// Download everything from an issue (metadata, comments, images, attachments)
@@ -163,7 +403,8 @@ pub enum IssueCommands {
         alias = "dl",
         after_help = "EXAMPLES:
     linear issue download ENG-123 --output ./ENG-123/
-    linear issue download ENG-123 --output ./backup/"
+    linear issue download ENG-123 --output ./backup/
+    linear issue download ENG-123 --output ./backup/ --verify"
     )]
     Download(DownloadAllArgs),
     /// Manage images in issue descriptions
@@ -207,10 +448,19 @@ pub enum IssueCommands {
     },
     /// List comments on an issue
     #[command(after_help = "EXAMPLES:
-    linear issue comments ENG-123")]
+    linear issue comments ENG-123
+    linear issue comments ENG-123 --all
+    linear issue comments ENG-123 --offline")]
     Comments {
         /// Issue identifier (e.g., ENG-123)
         id: String,
+
+        /// Read from the local mirror (see `linear sync`) instead of the API
+        #[arg(long)]
+        offline: bool,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
     /// Add or edit a comment on an issue
     #[command(after_help = "EXAMPLES:
@@ -219,10 +469,14 @@ pub enum IssueCommands {
     Comment(CommentArgs),
     /// List issue relations (blocks, blocked by, duplicates, related, parent, children)
     #[command(after_help = "EXAMPLES:
-    linear issue relations ENG-123")]
+    linear issue relations ENG-123
+    linear issue relations ENG-123 --all")]
     Relations {
         /// Issue identifier (e.g., ENG-123) or UUID
         id: String,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
     /// Create a relation between two issues
     #[command(after_help = "EXAMPLES:
@@ -261,6 +515,9 @@ pub enum IssueCommands {
     Labels {
         /// Issue identifier (e.g., ENG-123) or UUID
         id: String,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
     /// Add a label to an issue
     #[command(after_help = "EXAMPLES:
@@ -291,11 +548,15 @@ pub enum AttachmentCommands {
     #[command(
         alias = "ls",
         after_help = "EXAMPLES:
-    linear issue attachments list ENG-123"
+    linear issue attachments list ENG-123
+    linear issue attachments list ENG-123 --limit 10"
     )]
     List {
         /// Issue identifier (e.g., ENG-123)
         id: String,
+
+        #[command(flatten)]
+        page: PageArgs,
     },
     /// Download attachments from an issue
     #[command(
@@ -339,15 +600,105 @@ pub enum CycleCommands {
     },
 }
 
+#[derive(Args)]
+pub struct ApiArgs {
+    /// GraphQL document; read from stdin if omitted or "-"
+    #[arg(conflicts_with = "file")]
+    pub query: Option<String>,
+
+    /// Read the GraphQL document from a file
+    #[arg(long, value_name = "PATH")]
+    pub file: Option<PathBuf>,
+
+    /// Variable with a JSON-typed value (numbers, true/false, null, arrays,
+    /// objects); @path reads a file. Dotted keys nest: input.title=Bug
+    #[arg(short = 'F', long = "field", value_name = "KEY=VALUE")]
+    pub field: Vec<String>,
+
+    /// Variable with a string value
+    #[arg(short = 'f', long = "raw-field", value_name = "KEY=VALUE")]
+    pub raw_field: Vec<String>,
+
+    /// Follow pageInfo cursors on the connection at this path (e.g. "issues"
+    /// or "team.issues") and print all of its nodes as one array
+    #[arg(long, value_name = "PATH")]
+    pub paginate: Option<String>,
+}
+
+/// `--limit` / `--all`, shared by every list command
+#[derive(Args, Clone, Copy)]
+pub struct PageArgs {
+    /// Maximum number of results to show [default: 50]
+    #[arg(long, short, value_parser = clap::value_parser!(u32).range(1..))]
+    pub limit: Option<u32>,
+
+    /// Fetch all results (may be slow for large result sets)
+    #[arg(long)]
+    pub all: bool,
+}
+
+impl PageArgs {
+    pub fn page_limit(&self) -> PageLimit {
+        self.page_limit_or(DEFAULT_LIMIT)
+    }
+
+    /// Like `page_limit`, for a command with its own default
+    pub fn page_limit_or(&self, default: u32) -> PageLimit {
+        if self.all {
+            PageLimit::All
+        } else {
+            PageLimit::First(self.limit.unwrap_or(default) as usize)
+        }
+    }
+}
+
+/// Results shown when no --limit is given
+const DEFAULT_LIMIT: u32 = 50;
+
+/// `linear issues` has always shown fewer results by default
+const DEFAULT_ISSUE_LIMIT: u32 = 25;
+
 #[derive(Args, Clone)]
 pub struct CycleListArgs {
     /// Filter by team key (e.g., ENG)
     #[arg(long)]
     pub team: Option<String>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
 }
 
 #[derive(Args, Clone)]
+#[command(mut_arg("limit", |arg| arg.help("Maximum number of results to show [default: 25]")))]
 pub struct IssueListArgs {
+    #[command(flatten)]
+    pub filter: IssueFilterArgs,
+
+    /// Start from a saved view (see `linear view`); other flags override it
+    #[arg(long)]
+    pub view: Option<String>,
+
+    /// Read from the local mirror (see `linear sync`) instead of the API
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Don't filter by the configured default_project
+    #[arg(long, conflicts_with = "project")]
+    pub any_project: bool,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+impl IssueListArgs {
+    pub fn page_limit(&self) -> PageLimit {
+        self.page.page_limit_or(DEFAULT_ISSUE_LIMIT)
+    }
+}
+
+/// Filters shared by `issues` and `view save`
+#[derive(Args, Clone, Default)]
+pub struct IssueFilterArgs {
     /// Show only my issues
     #[arg(long)]
     pub mine: bool,
@@ -364,21 +715,75 @@ pub struct IssueListArgs {
     #[arg(long)]
     pub project: Option<String>,
 
-    /// Filter by label name
+    /// Filter by label name (can be specified multiple times; all must match)
     #[arg(long)]
-    pub label: Option<String>,
+    pub label: Vec<String>,
 
     /// Filter by cycle name
     #[arg(long)]
     pub cycle: Option<String>,
 
-    /// Maximum number of issues to show (default: 25, max: 250)
-    #[arg(long, short, default_value = "25")]
-    pub limit: u32,
+    /// Order by creation or last update, newest first
+    #[arg(long, value_enum)]
+    pub sort: Option<IssueSort>,
+
+    /// Table columns, comma-separated [default: id,title,status,priority,estimate,assignee]
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub columns: Vec<IssueColumn>,
+}
 
-    /// Fetch all results (may be slow for large result sets)
+#[derive(Args)]
+pub struct ViewSaveArgs {
+    /// View name
+    pub name: String,
+
+    #[command(flatten)]
+    pub filter: IssueFilterArgs,
+
+    /// Maximum number of results to show
+    #[arg(long, short, value_parser = clap::value_parser!(u32).range(1..))]
+    pub limit: Option<u32>,
+}
+
+#[derive(Args)]
+pub struct InitArgs {
+    /// API key to save without prompting (\"-\" reads it from stdin)
+    #[arg(long, value_name = "KEY")]
+    pub api_key: Option<String>,
+
+    /// Default team key, instead of choosing from a list
     #[arg(long)]
-    pub all: bool,
+    pub team: Option<String>,
+}
+
+#[derive(Args)]
+pub struct SearchArgs {
+    /// Words, "phrases" and field:term filters to search for
+    #[arg(required = true)]
+    pub query: Vec<String>,
+
+    /// Search the local mirror (see `linear sync`); there is no online search yet
+    #[arg(long, required = true)]
+    pub local: bool,
+
+    /// Filter by team key (e.g., ENG)
+    #[arg(long)]
+    pub team: Option<String>,
+
+    #[command(flatten)]
+    pub page: PageArgs,
+}
+
+#[derive(Args)]
+pub struct SyncArgs {
+    /// Team keys to sync (can be specified multiple times; defaults to the
+    /// teams already mirrored, then to default_team)
+    #[arg(long)]
+    pub team: Vec<String>,
+
+    /// Discard the mirrored issues and comments and fetch everything again
+    #[arg(long)]
+    pub full: bool,
 }
 
 #[derive(Args)]
@@ -415,7 +820,7 @@ pub struct IssueCreateArgs {
     #[arg(long)]
     pub cycle: Option<i32>,
 
-    /// Assign to user (ID or "me")
+    /// Assign to user (ID, name, email or "me")
     #[arg(long)]
     pub assignee: Option<String>,
 }
@@ -441,7 +846,7 @@ pub struct IssueUpdateArgs {
     #[arg(long, value_enum)]
     pub priority: Option<Priority>,
 
-    /// Assign to user (ID or "me")
+    /// Assign to user (ID, name, email or "me")
     #[arg(long)]
     pub assignee: Option<String>,
 
@@ -527,6 +932,10 @@ pub struct IssueViewArgs {
     /// Output directory for downloaded images (required with --fetch-images)
     #[arg(long, requires = "fetch_images")]
     pub output: Option<PathBuf>,
+
+    /// Read from the local mirror (see `linear sync`) instead of the API
+    #[arg(long, conflicts_with = "fetch_images")]
+    pub offline: bool,
 }
 
 #[derive(Args)]
@@ -551,6 +960,10 @@ pub struct DownloadAllArgs {
     /// Output directory for downloaded content
     #[arg(long)]
     pub output: PathBuf,
+
+    /// Check files in the output directory against its manifest instead of downloading
+    #[arg(long)]
+    pub verify: bool,
 }
 
 #[derive(Args)]
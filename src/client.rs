@@ -1,13 +1,34 @@
+use std::sync::Mutex;
+use std::time::{Duration, Instant, SystemTime};
+
 use reqwest::Client;
-use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde::{de::DeserializeOwned, de::IgnoredAny, Deserialize, Serialize};
+
+use crate::auth::{self, Credentials};
+use crate::cassette::{Cassette, CassetteMode};
+use crate::config::Config;
+use crate::download;
+use crate::error::{GraphQLError, GraphQLErrorKind, LinearError, Result};
+use crate::http;
+use crate::rate_limit::{self, RateLimitStatus};
+use crate::trace;
 
-use crate::error::{LinearError, Result};
+pub const DEFAULT_API_ENDPOINT: &str = "https://api.linear.app/graphql";
 
-const API_ENDPOINT: &str = "https://api.linear.app/graphql";
+/// Retries after the first attempt for retryable failures
+const MAX_RETRIES: u32 = 4;
+const BASE_BACKOFF: Duration = Duration::from_millis(500);
+const MAX_BACKOFF: Duration = Duration::from_secs(30);
+/// Longest we'll sleep waiting for a rate-limit window to reset before giving up
+const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
 
 pub struct LinearClient {
     http: Client,
-    api_key: String,
+    credentials: Mutex<Credentials>,
+    endpoint: String,
+    cassette: Option<Mutex<Cassette>>,
+    rate_limit: Mutex<Option<RateLimitStatus>>,
+    download_concurrency: usize,
 }
 
 #[derive(Serialize)]
@@ -23,58 +44,474 @@ struct GraphQLResponse<T> {
     errors: Option<Vec<GraphQLError>>,
 }
 
-#[derive(Deserialize, Debug)]
-struct GraphQLError {
-    message: String,
+/// Raw HTTP outcome of a single attempt
+struct RawResponse {
+    status: u16,
+    body: String,
+    retry_after: Option<Duration>,
 }
 
 impl LinearClient {
-    pub fn new(api_key: String) -> Self {
+    pub fn new(credentials: Credentials) -> Self {
         Self {
             http: Client::new(),
-            api_key,
+            credentials: Mutex::new(credentials),
+            endpoint: DEFAULT_API_ENDPOINT.to_string(),
+            cassette: None,
+            rate_limit: Mutex::new(None),
+            download_concurrency: download::DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// A client using the endpoint and network settings in `config`
+    pub fn from_config(credentials: Credentials, config: &Config) -> Result<Self> {
+        let mut client = Self::new(credentials).with_http(http::build_client(&config.network)?);
+        if let Some(url) = config.api_url() {
+            client = client.with_endpoint(url);
+        }
+        if let Some(limit) = config.network.max_concurrent_downloads {
+            client = client.with_download_concurrency(limit);
         }
+        Ok(client)
+    }
+
+    /// Use a preconfigured HTTP client (proxy, CA certificates, timeouts)
+    pub fn with_http(mut self, http: Client) -> Self {
+        self.http = http;
+        self
+    }
+
+    /// Send requests to a different GraphQL endpoint (e.g. a staging proxy or mock)
+    pub fn with_endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = endpoint.into();
+        self
+    }
+
+    /// Limit how many files download commands fetch at once
+    pub fn with_download_concurrency(mut self, limit: usize) -> Self {
+        self.download_concurrency = limit.max(1);
+        self
     }
 
+    /// Record requests to, or replay responses from, a cassette file
+    pub fn with_cassette(mut self, cassette: Cassette) -> Self {
+        self.cassette = Some(Mutex::new(cassette));
+        self
+    }
+
+    /// Run a GraphQL operation, retrying rate-limited and transient failures
+    /// with exponential backoff.
     pub async fn query<T: DeserializeOwned>(
         &self,
         query: &str,
         variables: Option<serde_json::Value>,
     ) -> Result<T> {
-        let request = GraphQLRequest { query, variables };
+        let is_mutation = is_mutation(query);
+        let operation = operation_name(query);
+        let mut attempt = 0;
+        let mut reauthenticated = false;
+
+        loop {
+            if let Some(wait) = self.rate_limit().and_then(|r| r.wait_until_reset()) {
+                if wait <= MAX_RATE_LIMIT_WAIT {
+                    trace::log(format!(
+                        "{operation}: rate limit exhausted, waiting {wait:?}"
+                    ));
+                    tokio::time::sleep(wait).await;
+                }
+            }
+
+            if trace::is_enabled() {
+                let variables = variables
+                    .as_ref()
+                    .map(|v| trace::redact_variables(v).to_string())
+                    .unwrap_or_else(|| "{}".to_string());
+                trace::log(format!("-> {operation} variables={variables}"));
+            }
+
+            let started = Instant::now();
+            let (result, retry_after) = match self.send(query, variables.clone()).await {
+                Ok(raw) => {
+                    trace::log(format!(
+                        "<- {operation} status={} in {}ms",
+                        raw.status,
+                        started.elapsed().as_millis()
+                    ));
+                    (parse_response(raw.status, &raw.body), raw.retry_after)
+                }
+                Err(e) => {
+                    trace::log(format!(
+                        "<- {operation} failed after {}ms: {e}",
+                        started.elapsed().as_millis()
+                    ));
+                    (Err(e), None)
+                }
+            };
+
+            match result {
+                // An OAuth token can be revoked or expire early; refresh once and retry
+                Err(e)
+                    if !reauthenticated && e.is_authentication_failure() && self.can_refresh() =>
+                {
+                    trace::log(format!(
+                        "{operation}: authentication failed, refreshing token"
+                    ));
+                    self.refresh_credentials().await?;
+                    reauthenticated = true;
+                }
+                Err(e) if attempt < MAX_RETRIES && e.is_retryable(is_mutation) => {
+                    let delay = retry_after
+                        .or_else(|| match e {
+                            LinearError::RateLimited => {
+                                self.rate_limit().and_then(|r| r.wait_until_reset())
+                            }
+                            _ => None,
+                        })
+                        .unwrap_or_else(|| backoff(attempt));
+
+                    if delay > MAX_RATE_LIMIT_WAIT {
+                        trace::log(format!(
+                            "{operation}: not retrying, wait of {delay:?} exceeds limit"
+                        ));
+                        return Err(e);
+                    }
+
+                    trace::log(format!(
+                        "{operation}: retry {}/{MAX_RETRIES} in {delay:?} after: {e}",
+                        attempt + 1
+                    ));
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                }
+                Err(e) => {
+                    if attempt > 0 || e.is_retryable(is_mutation) {
+                        trace::log(format!("{operation}: giving up after {attempt} retries"));
+                    }
+                    return Err(e);
+                }
+                Ok(data) => return Ok(data),
+            }
+        }
+    }
+
+    /// Send a request (or replay it from the cassette), returning the raw response
+    async fn send(&self, query: &str, variables: Option<serde_json::Value>) -> Result<RawResponse> {
+        if let Some(cassette) = &self.cassette {
+            let mut cassette = cassette.lock().unwrap_or_else(|e| e.into_inner());
+            if cassette.mode() == CassetteMode::Replay {
+                let (status, body) =
+                    cassette.replay(query, variables.as_ref()).ok_or_else(|| {
+                        LinearError::CassetteMismatch {
+                            path: cassette.path().to_path_buf(),
+                            operation: operation_name(query).to_string(),
+                        }
+                    })?;
+                trace::log(format!("{} replayed from cassette", operation_name(query)));
+                return Ok(RawResponse {
+                    status,
+                    body,
+                    retry_after: None,
+                });
+            }
+        }
+
+        if self.needs_refresh() {
+            trace::log("access token expired, refreshing");
+            self.refresh_credentials().await?;
+        }
+
+        let request = GraphQLRequest {
+            query,
+            variables: variables.clone(),
+        };
 
         let response = self
             .http
-            .post(API_ENDPOINT)
-            .header("Authorization", &self.api_key)
+            .post(&self.endpoint)
+            .header("Authorization", self.authorization())
             .header("Content-Type", "application/json")
             .json(&request)
             .send()
             .await?;
 
-        if !response.status().is_success() {
-            return Err(LinearError::ApiError {
-                status: response.status().as_u16(),
-                message: response
-                    .text()
-                    .await
-                    .unwrap_or_else(|_| "<failed to read response body>".to_string()),
-            });
+        let status = response.status().as_u16();
+        let retry_after = rate_limit::retry_after(response.headers());
+        if let Some(limits) = RateLimitStatus::from_headers(response.headers()) {
+            trace::log(format!(
+                "rate limit: requests {}/{}, complexity {}/{} (query cost {})",
+                limits.requests_remaining.unwrap_or_default(),
+                limits.requests_limit.unwrap_or_default(),
+                limits.complexity_remaining.unwrap_or_default(),
+                limits.complexity_limit.unwrap_or_default(),
+                limits.complexity.unwrap_or_default()
+            ));
+            *self.rate_limit.lock().unwrap_or_else(|e| e.into_inner()) = Some(limits);
+        }
+
+        let body = response.text().await?;
+
+        if let Some(cassette) = &self.cassette {
+            let mut cassette = cassette.lock().unwrap_or_else(|e| e.into_inner());
+            cassette.record(query, variables.as_ref(), status, &body)?;
+        }
+
+        Ok(RawResponse {
+            status,
+            body,
+            retry_after,
+        })
+    }
+
+    /// Rate-limit headers from the most recent response, if Linear sent any
+    pub fn rate_limit(&self) -> Option<RateLimitStatus> {
+        self.rate_limit
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .clone()
+    }
+
+    /// Authorization header value, also used for authenticated downloads
+    pub fn authorization(&self) -> String {
+        self.credentials
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .authorization()
+    }
+
+    fn oauth_tokens(&self) -> Option<auth::OAuthTokens> {
+        match &*self.credentials.lock().unwrap_or_else(|e| e.into_inner()) {
+            Credentials::OAuth(tokens) => Some(tokens.clone()),
+            Credentials::ApiKey(_) => None,
+        }
+    }
+
+    fn needs_refresh(&self) -> bool {
+        self.oauth_tokens().is_some_and(|t| t.is_expired())
+    }
+
+    fn can_refresh(&self) -> bool {
+        self.oauth_tokens()
+            .is_some_and(|t| t.refresh_token.is_some())
+    }
+
+    /// Refresh the OAuth access token and persist the new tokens
+    async fn refresh_credentials(&self) -> Result<()> {
+        let Some(tokens) = self.oauth_tokens() else {
+            return Ok(());
+        };
+
+        let refreshed = auth::refresh(&self.http, &tokens).await?;
+        *self.credentials.lock().unwrap_or_else(|e| e.into_inner()) = Credentials::OAuth(refreshed);
+
+        Ok(())
+    }
+
+    /// Shared HTTP client for downloads and uploads
+    pub fn http(&self) -> &Client {
+        &self.http
+    }
+
+    /// How many files download commands may fetch at once
+    pub fn download_concurrency(&self) -> usize {
+        self.download_concurrency
+    }
+}
+
+/// Turn a raw HTTP response into data or a typed error
+fn parse_response<T: DeserializeOwned>(status: u16, body: &str) -> Result<T> {
+    if !(200..300).contains(&status) {
+        // Linear reports GraphQL errors (including rate limiting) with 4xx statuses
+        if let Ok(GraphQLResponse::<IgnoredAny> {
+            errors: Some(errors),
+            ..
+        }) = serde_json::from_str(body)
+        {
+            return Err(graphql_error(errors));
         }
 
-        let gql_response: GraphQLResponse<T> = response.json().await?;
+        return Err(LinearError::ApiError {
+            status,
+            message: body.to_string(),
+        });
+    }
+
+    let gql_response: GraphQLResponse<T> = serde_json::from_str(body)?;
+
+    if let Some(errors) = gql_response.errors {
+        return Err(graphql_error(errors));
+    }
+
+    gql_response.data.ok_or(LinearError::EmptyResponse)
+}
+
+fn graphql_error(errors: Vec<GraphQLError>) -> LinearError {
+    if errors
+        .iter()
+        .any(|e| e.kind() == GraphQLErrorKind::RateLimited)
+    {
+        return LinearError::RateLimited;
+    }
+
+    LinearError::GraphQL { errors }
+}
+
+/// Exponential backoff with jitter: half the capped delay plus a random share of the other half
+fn backoff(attempt: u32) -> Duration {
+    let capped = BASE_BACKOFF
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(MAX_BACKOFF);
+    let half = capped / 2;
+
+    let nanos = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos() as u64;
+    let jitter_ms = nanos % (half.as_millis() as u64 + 1);
+
+    half + Duration::from_millis(jitter_ms)
+}
 
-        if let Some(errors) = gql_response.errors {
-            return Err(LinearError::GraphQL {
-                messages: errors.into_iter().map(|e| e.message).collect(),
-            });
+/// Extract the operation name from a GraphQL document (e.g. "ListIssues")
+pub fn operation_name(query: &str) -> &str {
+    query
+        .split_whitespace()
+        .skip_while(|word| !matches!(*word, "query" | "mutation" | "subscription"))
+        .nth(1)
+        .map(|name| name.split(['(', '{']).next().unwrap_or(name))
+        .filter(|name| !name.is_empty())
+        .unwrap_or("anonymous")
+}
+
+/// Whether a GraphQL document may run a mutation. Errs towards yes: comments
+/// are skipped, but a `mutation` anywhere else counts, whichever operation of
+/// a multi-operation document is selected.
+pub fn is_mutation(query: &str) -> bool {
+    query
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default())
+        .flat_map(|line| line.split(|c: char| !(c.is_alphanumeric() || c == '_')))
+        .any(|word| word == "mutation")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pagination::PageLimit;
+    use serde_json::json;
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("linear-cli-{}-{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_operation_name() {
+        assert_eq!(
+            operation_name("query ListIssues($a: Int) { x }"),
+            "ListIssues"
+        );
+        assert_eq!(operation_name("\nmutation CreateIssue{ x }"), "CreateIssue");
+        assert_eq!(operation_name("{ viewer { id } }"), "anonymous");
+    }
+
+    #[test]
+    fn test_is_mutation() {
+        assert!(is_mutation("mutation M { x }"));
+        assert!(is_mutation("# archive it\nmutation M { x }"));
+        assert!(is_mutation("query Q { x }\nmutation M { y }"));
+        assert!(!is_mutation("query Q { x } # not a mutation"));
+        assert!(!is_mutation("query Mutations { mutationLog { id } }"));
+    }
+
+    #[test]
+    fn test_backoff_is_bounded() {
+        for attempt in 0..10 {
+            let delay = backoff(attempt);
+            assert!(delay <= MAX_BACKOFF);
+            assert!(
+                delay
+                    >= BASE_BACKOFF
+                        .saturating_mul(2u32.pow(attempt))
+                        .min(MAX_BACKOFF)
+                        / 2
+            );
         }
+    }
+
+    #[test]
+    fn test_parse_response_classifies_rate_limit() {
+        let body =
+            r#"{"errors":[{"message":"Rate limit exceeded","extensions":{"code":"RATELIMITED"}}]}"#;
+        let result = parse_response::<serde_json::Value>(400, body);
+        assert!(matches!(result, Err(LinearError::RateLimited)));
 
-        gql_response.data.ok_or(LinearError::EmptyResponse)
+        let result = parse_response::<serde_json::Value>(502, "Bad Gateway");
+        assert!(matches!(
+            result,
+            Err(LinearError::ApiError { status: 502, .. })
+        ));
     }
 
-    /// Get the API key for authenticated downloads
-    pub fn api_key(&self) -> &str {
-        &self.api_key
+    #[tokio::test]
+    async fn test_replay_serves_recorded_responses_in_order() {
+        let path = temp_path("replay.json");
+        let query = "query Viewer { viewer { id } }";
+        let cassette = json!({
+            "interactions": [
+                {
+                    "request": { "query": query },
+                    "response": { "status": 200, "body": { "data": { "viewer": { "id": "u1" } } } }
+                },
+                {
+                    "request": { "query": query },
+                    "response": { "status": 200, "body": { "data": { "viewer": { "id": "u2" } } } }
+                }
+            ]
+        });
+        std::fs::write(&path, cassette.to_string()).unwrap();
+
+        let client = LinearClient::new(Credentials::ApiKey(String::new()))
+            .with_cassette(Cassette::open(&path, CassetteMode::Replay).unwrap());
+
+        let first: serde_json::Value = client.query(query, None).await.unwrap();
+        let second: serde_json::Value = client.query(query, None).await.unwrap();
+        assert_eq!(first["viewer"]["id"], "u1");
+        assert_eq!(second["viewer"]["id"], "u2");
+
+        let exhausted = client.query::<serde_json::Value>(query, None).await;
+        assert!(matches!(
+            exhausted,
+            Err(LinearError::CassetteMismatch { ref operation, .. }) if operation == "Viewer"
+        ));
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[tokio::test]
+    async fn test_replay_runs_command_handlers() {
+        let path = temp_path("teams.json");
+        let cassette = json!({
+            "interactions": [{
+                "request": {
+                    "query": crate::commands::teams::LIST_TEAMS_QUERY,
+                    "variables": { "first": 50 }
+                },
+                "response": {
+                    "status": 200,
+                    "body": { "data": { "teams": { "nodes": [
+                        { "id": "t1", "key": "ENG", "name": "Engineering" }
+                    ] } } }
+                }
+            }]
+        });
+        std::fs::write(&path, cassette.to_string()).unwrap();
+
+        let client = LinearClient::new(Credentials::ApiKey(String::new()))
+            .with_cassette(Cassette::open(&path, CassetteMode::Replay).unwrap());
+
+        crate::commands::teams::list(&client, PageLimit::First(50))
+            .await
+            .unwrap();
+
+        std::fs::remove_file(&path).ok();
     }
 }
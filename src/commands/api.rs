@@ -0,0 +1,186 @@
+use std::io::Read;
+
+use serde_json::{Map, Value};
+
+use crate::cache::{Cache, Entity};
+use crate::cli::ApiArgs;
+use crate::client::{self, LinearClient};
+use crate::error::{LinearError, Result};
+use crate::output;
+use crate::pagination::{self, PageLimit};
+use crate::responses::Connection;
+
+/// Send an arbitrary GraphQL document and print the `data` object as JSON
+pub async fn run(client: &LinearClient, args: ApiArgs) -> Result<()> {
+    let query = read_query(&args)?;
+    let variables = parse_fields(&args.field, &args.raw_field)?;
+
+    let data = match &args.paginate {
+        Some(path) => {
+            if !query.contains("$after") {
+                return Err(LinearError::ApiUsage(
+                    "--paginate requires the query to take $first and $after and select pageInfo { hasNextPage endCursor }".to_string(),
+                ));
+            }
+
+            let nodes =
+                pagination::fetch(client, &query, variables, PageLimit::All, |data: Value| {
+                    connection_at(data, path)
+                })
+                .await?;
+            Value::Array(nodes)
+        }
+        None => client.query(&query, variables).await?,
+    };
+
+    // A raw mutation may have created, renamed or deleted anything cached
+    if client::is_mutation(&query) {
+        let mut cache = Cache::load();
+        for entity in Entity::ALL {
+            cache.invalidate(entity);
+        }
+        cache.save();
+    }
+
+    output::print_item(&data, |data| {
+        println!(
+            "{}",
+            serde_json::to_string_pretty(data)
+                .unwrap_or_else(|_| "<serialization error>".to_string())
+        );
+    });
+
+    Ok(())
+}
+
+/// Query from the argument, `--file`, or stdin (when neither is given or the argument is "-")
+fn read_query(args: &ApiArgs) -> Result<String> {
+    let query = match (&args.query, &args.file) {
+        (Some(query), _) if query != "-" => query.clone(),
+        (_, Some(path)) => std::fs::read_to_string(path).map_err(|e| LinearError::FileRead {
+            path: path.display().to_string(),
+            source: e,
+        })?,
+        _ => {
+            let mut query = String::new();
+            std::io::stdin().read_to_string(&mut query)?;
+            query
+        }
+    };
+
+    if query.trim().is_empty() {
+        return Err(LinearError::ApiUsage("empty query".to_string()));
+    }
+
+    Ok(query)
+}
+
+/// Build variables from `-F key=value` (JSON-typed) and `-f key=value` (string) fields.
+/// Dotted keys build nested objects: `-F input.title=Bug`.
+fn parse_fields(typed: &[String], raw: &[String]) -> Result<Option<Value>> {
+    let mut variables = Map::new();
+
+    for (field, is_typed) in typed
+        .iter()
+        .map(|f| (f, true))
+        .chain(raw.iter().map(|f| (f, false)))
+    {
+        let (key, value) = field.split_once('=').ok_or_else(|| {
+            LinearError::ApiUsage(format!("field '{field}' must be in key=value form"))
+        })?;
+
+        let value = if !is_typed {
+            Value::String(value.to_string())
+        } else if let Some(path) = value.strip_prefix('@') {
+            Value::String(
+                std::fs::read_to_string(path).map_err(|e| LinearError::FileRead {
+                    path: path.to_string(),
+                    source: e,
+                })?,
+            )
+        } else {
+            // Numbers, booleans, null, arrays and objects keep their JSON type
+            serde_json::from_str(value).unwrap_or_else(|_| Value::String(value.to_string()))
+        };
+
+        insert_path(&mut variables, key, value)?;
+    }
+
+    Ok((!variables.is_empty()).then_some(Value::Object(variables)))
+}
+
+fn insert_path(map: &mut Map<String, Value>, key: &str, value: Value) -> Result<()> {
+    match key.split_once('.') {
+        None => {
+            map.insert(key.to_string(), value);
+        }
+        Some((head, rest)) => {
+            let child = map
+                .entry(head)
+                .or_insert_with(|| Value::Object(Map::new()))
+                .as_object_mut()
+                .ok_or_else(|| {
+                    LinearError::ApiUsage(format!("field '{head}' is both a value and an object"))
+                })?;
+            insert_path(child, rest, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the connection at a dotted path in the response data (e.g. "team.issues")
+fn connection_at(data: Value, path: &str) -> Result<Connection<Value>> {
+    let mut value = data;
+    for segment in path.split('.') {
+        value = match value {
+            Value::Object(mut map) => map.remove(segment),
+            _ => None,
+        }
+        .ok_or_else(|| LinearError::ApiUsage(format!("no '{path}' in the response")))?;
+    }
+
+    serde_json::from_value(value)
+        .map_err(|_| LinearError::ApiUsage(format!("'{path}' is not a connection with nodes")))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_parse_fields() {
+        let typed = vec![
+            "id=ENG-1".to_string(),
+            "first=10".to_string(),
+            "input.archived=false".to_string(),
+            "input.labelIds=[\"a\"]".to_string(),
+        ];
+        let raw = vec!["input.title=42".to_string()];
+
+        assert_eq!(
+            parse_fields(&typed, &raw).unwrap(),
+            Some(json!({
+                "id": "ENG-1",
+                "first": 10,
+                "input": { "archived": false, "labelIds": ["a"], "title": "42" }
+            }))
+        );
+        assert_eq!(parse_fields(&[], &[]).unwrap(), None);
+        assert!(parse_fields(&["novalue".to_string()], &[]).is_err());
+    }
+
+    #[test]
+    fn test_connection_at() {
+        let data = json!({ "team": { "issues": { "nodes": [{ "id": "1" }] } } });
+        assert_eq!(
+            connection_at(data.clone(), "team.issues")
+                .unwrap()
+                .nodes
+                .len(),
+            1
+        );
+        assert!(connection_at(data, "team.projects").is_err());
+    }
+}
@@ -8,9 +8,13 @@ use url::Url;
 
 use crate::cli::{AttachUrlArgs, DownloadAttachmentsArgs, UploadFileArgs};
 use crate::client::LinearClient;
+use crate::commands::images::{ensure_all_succeeded, DownloadResult};
+use crate::download::{self, Fetched, Manifest, Progress};
 use crate::error::{LinearError, Result};
 use crate::output::{self, format_date_only, truncate};
+use crate::pagination::{self, PageLimit};
 use crate::responses::Connection;
+use crate::trace;
 use crate::types::Attachment;
 
 #[derive(Tabled)]
@@ -34,9 +38,9 @@ impl From<&Attachment> for AttachmentRow {
 }
 
 const LIST_ATTACHMENTS_QUERY: &str = r#"
-query ListAttachments($issueId: String!) {
+query ListAttachments($issueId: String!, $first: Int, $after: String) {
     issue(id: $issueId) {
-        attachments {
+        attachments(first: $first, after: $after) {
             nodes {
                 id
                 title
@@ -44,6 +48,10 @@ query ListAttachments($issueId: String!) {
                 subtitle
                 createdAt
             }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
         }
     }
 }
@@ -150,17 +158,29 @@ struct CreateAttachmentResponse {
     attachment_create: AttachmentResult,
 }
 
-pub async fn list(client: &LinearClient, issue_id: &str) -> Result<()> {
-    let variables = json!({ "issueId": issue_id });
-    let response: AttachmentsResponse = client
-        .query(LIST_ATTACHMENTS_QUERY, Some(variables))
-        .await?;
+/// Fetch attachments for an issue
+async fn fetch_attachments(
+    client: &LinearClient,
+    issue_id: &str,
+    limit: PageLimit,
+) -> Result<Vec<Attachment>> {
+    pagination::fetch(
+        client,
+        LIST_ATTACHMENTS_QUERY,
+        Some(json!({ "issueId": issue_id })),
+        limit,
+        |response: AttachmentsResponse| {
+            response
+                .issue
+                .map(|issue| issue.attachments)
+                .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))
+        },
+    )
+    .await
+}
 
-    let attachments = response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))?
-        .attachments
-        .nodes;
+pub async fn list(client: &LinearClient, issue_id: &str, limit: PageLimit) -> Result<()> {
+    let attachments = fetch_attachments(client, issue_id, limit).await?;
 
     if attachments.is_empty() {
         output::print_message(&format!("No attachments found for {issue_id}"));
@@ -244,8 +264,8 @@ pub async fn upload_file(client: &LinearClient, args: UploadFileArgs) -> Result<
     let upload_info = upload_response.file_upload.upload_file;
 
     // Step 2: Upload file to the signed URL
-    let http_client = reqwest::Client::new();
-    let mut request = http_client
+    let mut request = client
+        .http()
         .put(&upload_info.upload_url)
         .body(file_data)
         .header("Content-Type", &content_type);
@@ -255,7 +275,22 @@ pub async fn upload_file(client: &LinearClient, args: UploadFileArgs) -> Result<
         request = request.header(&header.key, &header.value);
     }
 
+    if trace::is_enabled() {
+        let headers: Vec<String> = upload_info
+            .headers
+            .iter()
+            .map(|h| format!("{}: {}", h.key, trace::redact_header(&h.key, &h.value)))
+            .collect();
+        trace::log(format!(
+            "PUT {} ({} bytes) headers=[{}]",
+            trace::redact_url(&upload_info.upload_url),
+            file_size,
+            headers.join(", ")
+        ));
+    }
+
     let upload_result = request.send().await?;
+    trace::log(format!("PUT upload status={}", upload_result.status()));
 
     if !upload_result.status().is_success() {
         return Err(LinearError::UploadFailed {
@@ -365,12 +400,12 @@ fn generate_attachment_filename(issue_id: &str, attachment: &Attachment, index:
 /// Download a single attachment
 async fn download_attachment(
     http: &Client,
-    api_key: &str,
+    authorization: &str,
     attachment: &Attachment,
-    output_dir: &Path,
-    issue_id: &str,
-    index: usize,
-) -> Result<PathBuf> {
+    file_path: PathBuf,
+    manifest: &Manifest,
+    progress: &Progress,
+) -> Result<(PathBuf, Fetched)> {
     let url_str = attachment
         .url
         .as_ref()
@@ -385,26 +420,18 @@ async fn download_attachment(
 
     // Add auth header for Linear-hosted attachments
     if is_linear_url(url_str) {
-        request = request.header("Authorization", api_key);
+        request = request.header("Authorization", authorization);
     }
 
-    let response = request.send().await?;
-
-    if !response.status().is_success() {
-        return Err(LinearError::AttachmentDownloadFailed {
+    let fetched = download::fetch(request, url_str, &file_path, manifest, progress, |status| {
+        LinearError::AttachmentDownloadFailed {
             url: url_str.clone(),
-            status: response.status().as_u16(),
-        });
-    }
-
-    let bytes = response.bytes().await?;
-
-    let filename = generate_attachment_filename(issue_id, attachment, index);
-    let file_path = output_dir.join(&filename);
-
-    std::fs::write(&file_path, &bytes)?;
+            status,
+        }
+    })
+    .await?;
 
-    Ok(file_path)
+    Ok((file_path, fetched))
 }
 
 pub async fn download(client: &LinearClient, args: DownloadAttachmentsArgs) -> Result<()> {
@@ -413,17 +440,7 @@ pub async fn download(client: &LinearClient, args: DownloadAttachmentsArgs) -> R
         std::fs::create_dir_all(&args.output)?;
     }
 
-    // Fetch attachments
-    let variables = json!({ "issueId": args.id });
-    let response: AttachmentsResponse = client
-        .query(LIST_ATTACHMENTS_QUERY, Some(variables))
-        .await?;
-
-    let attachments = response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(args.id.clone()))?
-        .attachments
-        .nodes;
+    let attachments = fetch_attachments(client, &args.id, PageLimit::All).await?;
 
     if attachments.is_empty() {
         return Err(LinearError::NoAttachments(args.id.clone()));
@@ -445,63 +462,130 @@ pub async fn download(client: &LinearClient, args: DownloadAttachmentsArgs) -> R
             .collect()
     };
 
-    let http = Client::new();
-    let api_key = client.api_key();
-
-    for (index, attachment) in attachments_to_download {
-        match download_attachment(&http, api_key, attachment, &args.output, &args.id, index).await {
-            Ok(path) => {
+    let manifest = Manifest::load(&args.output)?;
+    let results = download_attachments(
+        client,
+        attachments_to_download,
+        &args.output,
+        &args.id,
+        &manifest,
+    )
+    .await;
+
+    for result in &results {
+        match result {
+            DownloadResult::Success { index, path } => {
                 output::print_message(&format!(
                     "Downloaded attachment {} to {}",
                     index,
                     path.display()
                 ));
             }
-            Err(e) => {
+            DownloadResult::Skipped { index, path } => {
+                output::print_message(&format!(
+                    "Attachment {} unchanged at {}",
+                    index,
+                    path.display()
+                ));
+            }
+            DownloadResult::Failed { index, error, .. } => {
                 eprintln!(
                     "Failed to download attachment {} ({}): {}",
-                    index, attachment.title, e
+                    index,
+                    attachments[index - 1].title,
+                    error
                 );
             }
         }
     }
 
-    Ok(())
+    ensure_all_succeeded(&results)
 }
 
-/// Download all attachments to a directory (returns count of successful downloads)
+/// Download all attachments to a directory, returning the outcome of each download
 pub async fn download_to_dir(
     client: &LinearClient,
     issue_id: &str,
     output_dir: &Path,
-) -> Result<usize> {
-    // Fetch attachments
-    let variables = json!({ "issueId": issue_id });
-    let response: AttachmentsResponse = client
-        .query(LIST_ATTACHMENTS_QUERY, Some(variables))
-        .await?;
-
-    let attachments = match response.issue {
-        Some(issue) => issue.attachments.nodes,
-        None => return Ok(0),
+    manifest: &Manifest,
+) -> Result<Vec<DownloadResult>> {
+    let attachments = match fetch_attachments(client, issue_id, PageLimit::All).await {
+        Err(LinearError::IssueNotFound(_)) => return Ok(Vec::new()),
+        result => result?,
     };
 
-    if attachments.is_empty() {
-        return Ok(0);
-    }
+    let numbered = attachments
+        .iter()
+        .enumerate()
+        .map(|(i, a)| (i + 1, a))
+        .collect();
+    let results = download_attachments(client, numbered, output_dir, issue_id, manifest).await;
 
-    let http = Client::new();
-    let api_key = client.api_key();
-    let mut success_count = 0;
+    Ok(results)
+}
 
-    for (index, attachment) in attachments.iter().enumerate() {
-        if download_attachment(&http, api_key, attachment, output_dir, issue_id, index + 1)
-            .await
-            .is_ok()
-        {
-            success_count += 1;
-        }
-    }
+/// Download numbered attachments concurrently, in order
+async fn download_attachments(
+    client: &LinearClient,
+    attachments: Vec<(usize, &Attachment)>,
+    output_dir: &Path,
+    issue_id: &str,
+    manifest: &Manifest,
+) -> Vec<DownloadResult> {
+    let filenames = download::unique_filenames(
+        attachments
+            .iter()
+            .map(|&(index, attachment)| {
+                (
+                    index,
+                    generate_attachment_filename(issue_id, attachment, index),
+                )
+            })
+            .collect(),
+    );
+    let attachments: Vec<_> = attachments.into_iter().zip(filenames).collect();
+
+    let http = client.http();
+    let authorization = client.authorization();
+    let progress = Progress::new(attachments.len());
+
+    let results = download::bounded(
+        attachments,
+        client.download_concurrency(),
+        |((index, attachment), filename)| {
+            let (authorization, progress) = (&authorization, &progress);
+            async move {
+                let file_path = output_dir.join(filename);
+                let outcome = download_attachment(
+                    http,
+                    authorization,
+                    attachment,
+                    file_path,
+                    manifest,
+                    progress,
+                )
+                .await;
+                let url = attachment.url.as_deref().unwrap_or_default();
+                DownloadResult::from_outcome(index, url, outcome)
+            }
+        },
+    )
+    .await;
+    progress.finish();
 
-    Ok(success_count)
+    results
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<AttachmentsResponse>(LIST_ATTACHMENTS_QUERY);
+        schema::assert_response::<AttachUrlResponse>(ATTACH_URL_MUTATION);
+        schema::assert_response::<FileUploadResponse>(FILE_UPLOAD_MUTATION);
+        schema::assert_response::<CreateAttachmentResponse>(CREATE_ATTACHMENT_MUTATION);
+    }
 }
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+
+use crate::auth::{self, Credentials, OAuthTokens};
+use crate::cache::Cache;
+use crate::client::LinearClient;
+use crate::config::{self, Config};
+use crate::error::{LinearError, Result};
+use crate::http;
+use crate::output::{self, format_date};
+
+const VIEWER_QUERY: &str = r#"
+query Viewer {
+    viewer {
+        id
+        name
+        email
+    }
+}
+"#;
+
+const ORGANIZATION_QUERY: &str = r#"
+query Organization {
+    viewer {
+        organization {
+            id
+        }
+    }
+}
+"#;
+
+#[derive(Deserialize)]
+struct ViewerResponse {
+    viewer: ViewerInfo,
+}
+
+#[derive(Deserialize, Serialize)]
+struct ViewerInfo {
+    id: String,
+    name: String,
+    email: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct OrganizationResponse {
+    viewer: OrganizationViewer,
+}
+
+#[derive(Deserialize)]
+struct OrganizationViewer {
+    organization: OrganizationId,
+}
+
+#[derive(Deserialize)]
+struct OrganizationId {
+    id: String,
+}
+
+#[derive(Serialize)]
+struct AuthStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    profile: Option<String>,
+    /// "env_api_key", "oauth", "api_key_command", "keyring", "config_api_key", or "none"
+    method: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<ViewerInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    scope: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<String>,
+}
+
+/// Log in with OAuth and store the tokens
+pub async fn login(config: &Config) -> Result<()> {
+    let http = http::build_client(&config.network)?;
+    auth::login(&http, &config.oauth).await?;
+
+    // The new tokens may belong to another workspace than the cached IDs. The
+    // mirror needs no reset: its file is keyed by the organization stored below
+    Cache::remove()?;
+
+    let client = LinearClient::from_config(
+        Credentials::OAuth(
+            OAuthTokens::load()?.ok_or(LinearError::OAuth("tokens were not saved".to_string()))?,
+        ),
+        config,
+    )?;
+    let response: ViewerResponse = client.query(VIEWER_QUERY, None).await?;
+    store_organization(&client).await?;
+
+    output::print_message(&format!(
+        "Logged in as {}{}",
+        response.viewer.name,
+        response
+            .viewer
+            .email
+            .map(|e| format!(" <{e}>"))
+            .unwrap_or_default()
+    ));
+
+    if std::env::var("LINEAR_API_KEY").is_ok() {
+        eprintln!("Warning: LINEAR_API_KEY is set and takes precedence over the OAuth login");
+    }
+
+    Ok(())
+}
+
+/// Show which credentials are in use and who they belong to
+pub async fn status(config: &Config) -> Result<()> {
+    let tokens = OAuthTokens::load()?;
+
+    let method = if std::env::var("LINEAR_API_KEY").is_ok() {
+        "env_api_key"
+    } else if tokens.is_some() {
+        "oauth"
+    } else {
+        config.api_key_source().unwrap_or("none")
+    };
+
+    let user = if method == "none" {
+        None
+    } else {
+        let client = LinearClient::from_config(config.credentials()?, config)?;
+        let response: ViewerResponse = client.query(VIEWER_QUERY, None).await?;
+        Some(response.viewer)
+    };
+
+    let oauth = tokens.filter(|_| method == "oauth");
+    let status = AuthStatus {
+        profile: config::profile(),
+        method,
+        user,
+        scope: oauth.as_ref().and_then(|t| t.scope.clone()),
+        expires_at: oauth
+            .as_ref()
+            .and_then(|t| t.expires_at)
+            .and_then(|at| chrono::DateTime::from_timestamp(at, 0))
+            .map(|dt| dt.to_rfc3339()),
+    };
+
+    output::print_item(&status, |status| {
+        let method = match status.method {
+            "env_api_key" => "API key (LINEAR_API_KEY)",
+            "oauth" => "OAuth",
+            "api_key_command" => "API key (api_key_command)",
+            "keyring" => "API key (system keyring)",
+            "config_api_key" => "API key (config file)",
+            _ => "Not logged in",
+        };
+        if let Some(profile) = &status.profile {
+            println!("Profile: {profile}");
+        }
+        println!("Auth:    {method}");
+
+        if let Some(user) = &status.user {
+            println!(
+                "User:    {}{}",
+                user.name,
+                user.email
+                    .as_ref()
+                    .map(|e| format!(" <{e}>"))
+                    .unwrap_or_default()
+            );
+        }
+        if let Some(scope) = &status.scope {
+            println!("Scope:   {scope}");
+        }
+        if let Some(expires_at) = &status.expires_at {
+            println!("Expires: {}", format_date(expires_at));
+        }
+    });
+
+    Ok(())
+}
+
+/// Revoke and delete stored OAuth tokens
+pub async fn logout(config: &Config) -> Result<()> {
+    let Some(tokens) = OAuthTokens::load()? else {
+        output::print_message("Not logged in with OAuth");
+        return Ok(());
+    };
+
+    let http = http::build_client(&config.network)?;
+    if let Err(e) = auth::revoke(&http, &tokens).await {
+        eprintln!("Warning: could not revoke token: {e}");
+    }
+
+    OAuthTokens::delete()?;
+    output::print_message("Logged out");
+
+    Ok(())
+}
+
+/// Look up the organization the stored OAuth tokens belong to and save it with
+/// them, so cached IDs are keyed by workspace rather than by OAuth app
+pub async fn store_organization(client: &LinearClient) -> Result<OAuthTokens> {
+    let response: OrganizationResponse = client.query(ORGANIZATION_QUERY, None).await?;
+
+    // Reload: the query may have refreshed (and saved) the tokens
+    let mut tokens =
+        OAuthTokens::load()?.ok_or(LinearError::OAuth("tokens were not saved".to_string()))?;
+    tokens.organization_id = Some(response.viewer.organization.id);
+    tokens.save()?;
+
+    Ok(tokens)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<ViewerResponse>(VIEWER_QUERY);
+        schema::assert_response::<OrganizationResponse>(ORGANIZATION_QUERY);
+    }
+}
@@ -0,0 +1,354 @@
+use serde::Deserialize;
+use serde_json::json;
+use tabled::Tabled;
+
+use crate::cache::{
+    Cache, CachedCycle, CachedLabel, CachedProject, CachedState, CachedTeam, CachedUser, Entity,
+    SectionStatus,
+};
+use crate::client::LinearClient;
+use crate::error::Result;
+use crate::output;
+use crate::pagination::{self, PageLimit};
+use crate::responses::Connection;
+
+const CACHE_TEAMS_QUERY: &str = r#"
+query CacheTeams($first: Int, $after: String) {
+    teams(first: $first, after: $after) {
+        nodes {
+            id
+            key
+            name
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+const CACHE_STATES_QUERY: &str = r#"
+query CacheStates($teamId: String!, $first: Int, $after: String) {
+    team(id: $teamId) {
+        states(first: $first, after: $after) {
+            nodes {
+                id
+                name
+                type
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
+        }
+    }
+}
+"#;
+
+const CACHE_CYCLES_QUERY: &str = r#"
+query CacheCycles($teamId: String!, $first: Int, $after: String) {
+    team(id: $teamId) {
+        cycles(first: $first, after: $after) {
+            nodes {
+                id
+                number
+                name
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
+        }
+    }
+}
+"#;
+
+const CACHE_LABELS_QUERY: &str = r#"
+query CacheLabels($first: Int, $after: String) {
+    issueLabels(first: $first, after: $after) {
+        nodes {
+            id
+            name
+            team {
+                key
+            }
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+const CACHE_USERS_QUERY: &str = r#"
+query CacheUsers($first: Int, $after: String) {
+    users(first: $first, after: $after) {
+        nodes {
+            id
+            name
+            displayName
+            email
+            isMe
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+const CACHE_PROJECTS_QUERY: &str = r#"
+query CacheProjects($first: Int, $after: String) {
+    projects(first: $first, after: $after) {
+        nodes {
+            id
+            name
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+#[derive(Deserialize)]
+struct TeamsResponse {
+    teams: Connection<CachedTeam>,
+}
+
+#[derive(Deserialize)]
+struct TeamStatesResponse {
+    team: TeamStates,
+}
+
+#[derive(Deserialize)]
+struct TeamStates {
+    states: Connection<CachedState>,
+}
+
+#[derive(Deserialize)]
+struct TeamCyclesResponse {
+    team: TeamCycles,
+}
+
+#[derive(Deserialize)]
+struct TeamCycles {
+    cycles: Connection<CachedCycle>,
+}
+
+#[derive(Deserialize)]
+struct LabelsResponse {
+    #[serde(rename = "issueLabels")]
+    issue_labels: Connection<CachedLabel>,
+}
+
+#[derive(Deserialize)]
+struct UsersResponse {
+    users: Connection<CachedUser>,
+}
+
+#[derive(Deserialize)]
+struct ProjectsResponse {
+    projects: Connection<CachedProject>,
+}
+
+#[derive(Tabled)]
+struct SectionRow {
+    #[tabled(rename = "Entity")]
+    entity: String,
+    #[tabled(rename = "Team")]
+    team: String,
+    #[tabled(rename = "Entries")]
+    entries: usize,
+    #[tabled(rename = "Age")]
+    age: String,
+    #[tabled(rename = "Expires In")]
+    expires_in: String,
+}
+
+impl From<&SectionStatus> for SectionRow {
+    fn from(section: &SectionStatus) -> Self {
+        Self {
+            entity: section.entity.to_string(),
+            team: section.team.clone().unwrap_or_default(),
+            entries: section.entries,
+            age: format_duration(section.age_secs),
+            expires_in: format_duration(section.expires_in_secs),
+        }
+    }
+}
+
+/// Show what is cached for the current profile
+pub fn status() {
+    let sections = Cache::load().status();
+
+    if sections.is_empty() {
+        output::print_message("Cache is empty");
+        return;
+    }
+
+    output::print_table(
+        &sections,
+        |section| SectionRow::from(section),
+        |section| {
+            format!(
+                "{} | {} | {}",
+                section.entity,
+                section.team.as_deref().unwrap_or("-"),
+                section.entries
+            )
+        },
+    );
+}
+
+/// Re-fetch `entities` (everything if empty) and replace the cached copies
+pub async fn refresh(client: &LinearClient, entities: &[Entity]) -> Result<()> {
+    let entities = if entities.is_empty() {
+        &Entity::ALL[..]
+    } else {
+        entities
+    };
+    let mut cache = Cache::load();
+    let mut counts = Vec::new();
+
+    // States and cycles are fetched per team, so they need the teams too
+    let per_team = entities.contains(&Entity::States) || entities.contains(&Entity::Cycles);
+    if entities.contains(&Entity::Teams) || (per_team && cache.teams().next().is_none()) {
+        let teams = pagination::fetch(
+            client,
+            CACHE_TEAMS_QUERY,
+            None,
+            PageLimit::All,
+            |r: TeamsResponse| Ok(r.teams),
+        )
+        .await?;
+        counts.push(format!("{} teams", teams.len()));
+        cache.set_teams(teams);
+    }
+
+    let teams: Vec<CachedTeam> = cache.teams().cloned().collect();
+    if entities.contains(&Entity::States) {
+        let mut total = 0;
+        for team in &teams {
+            let variables = json!({ "teamId": team.id });
+            let states = pagination::fetch(
+                client,
+                CACHE_STATES_QUERY,
+                Some(variables),
+                PageLimit::All,
+                |r: TeamStatesResponse| Ok(r.team.states),
+            )
+            .await?;
+            total += states.len();
+            cache.set_states(&team.key, states);
+        }
+        counts.push(format!("{total} states"));
+    }
+    if entities.contains(&Entity::Cycles) {
+        let mut total = 0;
+        for team in &teams {
+            let variables = json!({ "teamId": team.id });
+            let cycles = pagination::fetch(
+                client,
+                CACHE_CYCLES_QUERY,
+                Some(variables),
+                PageLimit::All,
+                |r: TeamCyclesResponse| Ok(r.team.cycles),
+            )
+            .await?;
+            total += cycles.len();
+            cache.set_cycles(&team.key, cycles);
+        }
+        counts.push(format!("{total} cycles"));
+    }
+    if entities.contains(&Entity::Labels) {
+        let labels = pagination::fetch(
+            client,
+            CACHE_LABELS_QUERY,
+            None,
+            PageLimit::All,
+            |r: LabelsResponse| Ok(r.issue_labels),
+        )
+        .await?;
+        counts.push(format!("{} labels", labels.len()));
+        cache.set_labels(labels);
+    }
+    if entities.contains(&Entity::Users) {
+        let users = pagination::fetch(
+            client,
+            CACHE_USERS_QUERY,
+            None,
+            PageLimit::All,
+            |r: UsersResponse| Ok(r.users),
+        )
+        .await?;
+        counts.push(format!("{} users", users.len()));
+        cache.set_users(users);
+    }
+    if entities.contains(&Entity::Projects) {
+        let projects = pagination::fetch(
+            client,
+            CACHE_PROJECTS_QUERY,
+            None,
+            PageLimit::All,
+            |r: ProjectsResponse| Ok(r.projects),
+        )
+        .await?;
+        counts.push(format!("{} projects", projects.len()));
+        cache.set_projects(projects);
+    }
+
+    cache.save();
+    output::print_message(&format!("Cached {}", counts.join(", ")));
+
+    Ok(())
+}
+
+/// Delete `entities` from the cache, or the whole cache file if empty
+pub fn clear(entities: &[Entity]) -> Result<()> {
+    if entities.is_empty() {
+        Cache::remove()?;
+        output::print_message("Cleared cache");
+        return Ok(());
+    }
+
+    let mut cache = Cache::load();
+    for entity in entities {
+        cache.invalidate(*entity);
+    }
+    cache.save();
+
+    let names: Vec<_> = entities.iter().map(|e| e.name()).collect();
+    output::print_message(&format!("Cleared cached {}", names.join(", ")));
+
+    Ok(())
+}
+
+/// Render seconds as e.g. "45s", "12m" or "3h 5m"
+fn format_duration(secs: u64) -> String {
+    match secs {
+        0..=59 => format!("{secs}s"),
+        60..=3599 => format!("{}m", secs / 60),
+        _ => format!("{}h {}m", secs / 3600, secs % 3600 / 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<TeamsResponse>(CACHE_TEAMS_QUERY);
+        schema::assert_response::<TeamStatesResponse>(CACHE_STATES_QUERY);
+        schema::assert_response::<TeamCyclesResponse>(CACHE_CYCLES_QUERY);
+        schema::assert_response::<LabelsResponse>(CACHE_LABELS_QUERY);
+        schema::assert_response::<UsersResponse>(CACHE_USERS_QUERY);
+        schema::assert_response::<ProjectsResponse>(CACHE_PROJECTS_QUERY);
+    }
+}
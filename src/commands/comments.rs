@@ -5,7 +5,9 @@ use tabled::Tabled;
 use crate::cli::CommentArgs;
 use crate::client::LinearClient;
 use crate::error::{LinearError, Result};
+use crate::mirror::Mirror;
 use crate::output::{self, format_relative, truncate};
+use crate::pagination::{self, PageLimit};
 use crate::responses::Connection;
 
 #[derive(Tabled)]
@@ -36,9 +38,9 @@ impl CommentRow {
 }
 
 const LIST_COMMENTS_QUERY: &str = r#"
-query ListComments($issueId: String!) {
+query ListComments($issueId: String!, $first: Int, $after: String) {
     issue(id: $issueId) {
-        comments {
+        comments(first: $first, after: $after) {
             nodes {
                 id
                 body
@@ -48,6 +50,10 @@ query ListComments($issueId: String!) {
                     name
                 }
             }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
         }
     }
 }
@@ -120,29 +126,52 @@ struct CommentMutationResult {
 }
 
 /// Fetch comments for an issue (returns the list for programmatic use)
-pub async fn fetch_comments(client: &LinearClient, issue_id: &str) -> Result<Vec<Comment>> {
-    let variables = json!({ "issueId": issue_id });
-    let response: CommentsResponse = client.query(LIST_COMMENTS_QUERY, Some(variables)).await?;
-
-    let comments = response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))?
-        .comments
-        .nodes;
+pub async fn fetch_comments(
+    client: &LinearClient,
+    issue_id: &str,
+    limit: PageLimit,
+) -> Result<Vec<Comment>> {
+    pagination::fetch(
+        client,
+        LIST_COMMENTS_QUERY,
+        Some(json!({ "issueId": issue_id })),
+        limit,
+        |response: CommentsResponse| {
+            response
+                .issue
+                .map(|issue| issue.comments)
+                .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))
+        },
+    )
+    .await
+}
 
-    Ok(comments)
+pub async fn list(client: &LinearClient, issue_id: &str, limit: PageLimit) -> Result<()> {
+    let comments = fetch_comments(client, issue_id, limit).await?;
+    print_comments(issue_id, &comments);
+    Ok(())
 }
 
-pub async fn list(client: &LinearClient, issue_id: &str) -> Result<()> {
-    let comments = fetch_comments(client, issue_id).await?;
+/// `list` answered from the mirror filled by `linear sync`
+pub fn list_offline(issue_id: &str, limit: PageLimit) -> Result<()> {
+    let mirror = Mirror::open_existing()?;
+    let issue = mirror
+        .issue(issue_id)?
+        .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))?;
+
+    let comments = pagination::limit(mirror.comments(&issue.id)?, limit);
+    print_comments(issue_id, &comments);
+    Ok(())
+}
 
+fn print_comments(issue_id: &str, comments: &[Comment]) {
     if comments.is_empty() {
         output::print_message(&format!("No comments on {issue_id}"));
-        return Ok(());
+        return;
     }
 
     output::print_table(
-        &comments,
+        comments,
         |comment| {
             let idx = comments.iter().position(|c| c.id == comment.id).unwrap_or(0);
             CommentRow::new(idx, comment)
@@ -157,8 +186,6 @@ pub async fn list(client: &LinearClient, issue_id: &str) -> Result<()> {
             format!("[{}] {}: {}", idx, author, truncate(&comment.body, 50))
         },
     );
-
-    Ok(())
 }
 
 pub async fn add(client: &LinearClient, args: CommentArgs) -> Result<()> {
@@ -179,7 +206,7 @@ pub async fn add(client: &LinearClient, args: CommentArgs) -> Result<()> {
 }
 
 pub async fn edit(client: &LinearClient, issue_id: &str, index: usize, body: &str) -> Result<()> {
-    let comments = fetch_comments(client, issue_id).await?;
+    let comments = fetch_comments(client, issue_id, PageLimit::All).await?;
 
     let comment = comments.get(index).ok_or(LinearError::CommentNotFound {
         index,
@@ -202,3 +229,16 @@ pub async fn edit(client: &LinearClient, issue_id: &str, index: usize, body: &st
     output::print_message(&format!("Updated comment {} on {}", index, issue_id));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<CommentsResponse>(LIST_COMMENTS_QUERY);
+        schema::assert_response::<CreateCommentResponse>(CREATE_COMMENT_MUTATION);
+        schema::assert_response::<UpdateCommentResponse>(UPDATE_COMMENT_MUTATION);
+    }
+}
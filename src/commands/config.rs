@@ -0,0 +1,513 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use clap::ValueEnum;
+use serde::Serialize;
+use tabled::Tabled;
+use toml_edit::{Array, DocumentMut, Item, Table, TableLike, Value};
+
+use crate::cli::OutputFormat;
+use crate::config::{self, Config, Setting, ValueKind, SETTINGS};
+use crate::error::{LinearError, Result};
+use crate::output;
+
+const REDACTED: &str = "<redacted>";
+
+/// Setting values given as command-line flags, keyed by setting
+pub struct Flags(BTreeMap<&'static str, String>);
+
+impl Flags {
+    pub fn new(format: Option<OutputFormat>) -> Self {
+        let mut values = BTreeMap::new();
+        if let Some(format) = format.and_then(|f| f.to_possible_value()) {
+            values.insert("format", format.get_name().to_string());
+        }
+        Self(values)
+    }
+}
+
+/// Where an effective value came from, highest precedence first
+#[derive(Serialize, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum Source {
+    Flag,
+    Env,
+    Repo,
+    File,
+    Default,
+    Unset,
+}
+
+#[derive(Serialize)]
+struct Entry {
+    key: String,
+    value: Option<serde_json::Value>,
+    source: Source,
+    /// The flag, environment variable, `.linear.toml` or profile table that
+    /// set the value
+    #[serde(skip_serializing_if = "Option::is_none")]
+    origin: Option<String>,
+}
+
+impl Entry {
+    fn display_value(&self) -> String {
+        match &self.value {
+            Some(serde_json::Value::String(s)) => s.clone(),
+            Some(value) => value.to_string(),
+            None => "-".to_string(),
+        }
+    }
+
+    fn redact(mut self) -> Self {
+        if self.value.is_some() && config::setting(&self.key).is_some_and(|s| s.secret) {
+            self.value = Some(REDACTED.into());
+        }
+        self
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Source::Flag => "flag",
+            Source::Env => "env",
+            Source::Repo => "repo",
+            Source::File => "file",
+            Source::Default => "default",
+            Source::Unset => "unset",
+        })
+    }
+}
+
+#[derive(Tabled)]
+struct EntryRow {
+    #[tabled(rename = "Key")]
+    key: String,
+    #[tabled(rename = "Value")]
+    value: String,
+    #[tabled(rename = "Source")]
+    source: String,
+}
+
+impl From<&Entry> for EntryRow {
+    fn from(entry: &Entry) -> Self {
+        Self {
+            key: entry.key.clone(),
+            value: entry.display_value(),
+            source: describe_source(entry),
+        }
+    }
+}
+
+/// The files a value can come from
+struct Files {
+    global: DocumentMut,
+    /// The nearest `.linear.toml`, if any
+    repo: Option<(PathBuf, DocumentMut)>,
+}
+
+impl Files {
+    fn read() -> Result<Self> {
+        let global = config::read_document(&Config::config_path()?)?;
+        let repo = match config::repo_config_path() {
+            Some(path) => {
+                let document = config::read_document(&path)?;
+                Some((path, document))
+            }
+            None => None,
+        };
+        Ok(Self { global, repo })
+    }
+}
+
+#[derive(Serialize)]
+struct ConfigPath {
+    path: PathBuf,
+}
+
+/// A key given to `config get|set|unset` and the table it lives in
+struct Target {
+    setting: &'static Setting,
+    /// `[profiles.<name>]`, when the key is per-profile and one is selected
+    /// (or the key was given as `profiles.<name>.<key>`)
+    profile: Option<String>,
+}
+
+impl Target {
+    fn parse(key: &str) -> Result<Self> {
+        let unknown = || LinearError::UnknownConfigKey {
+            key: key.to_string(),
+            hint: config::suggest(key),
+        };
+
+        if let Some((name, rest)) = key
+            .strip_prefix("profiles.")
+            .and_then(|k| k.split_once('.'))
+        {
+            let setting = config::setting(rest)
+                .filter(|s| s.per_profile)
+                .ok_or_else(unknown)?;
+            return Ok(Self {
+                setting,
+                profile: Some(name.to_string()),
+            });
+        }
+
+        let setting = config::setting(key).ok_or_else(unknown)?;
+        Ok(Self {
+            setting,
+            profile: config::profile().filter(|_| setting.per_profile),
+        })
+    }
+
+    /// The key as written in config.toml
+    fn path(&self) -> String {
+        match &self.profile {
+            Some(name) => format!("profiles.{name}.{}", self.setting.key),
+            None => self.setting.key.to_string(),
+        }
+    }
+
+    /// The effective value: flag, then environment, then `.linear.toml`,
+    /// then config.toml, then default
+    fn resolve(&self, files: &Files, flags: &Flags) -> Entry {
+        let setting = self.setting;
+        let entry = |value, source, origin: Option<String>| Entry {
+            key: setting.key.to_string(),
+            value,
+            source,
+            origin,
+        };
+
+        if let (Some(flag), Some(value)) = (setting.flag, flags.0.get(setting.key)) {
+            return entry(
+                Some(typed(setting.kind, value)),
+                Source::Flag,
+                Some(flag.to_string()),
+            );
+        }
+        if let Some((var, value)) = setting
+            .env
+            .and_then(|var| std::env::var(var).ok().map(|value| (var, value)))
+        {
+            return entry(
+                Some(typed(setting.kind, &value)),
+                Source::Env,
+                Some(var.to_string()),
+            );
+        }
+        if let Some((path, document)) = files.repo.as_ref().filter(|_| setting.repo) {
+            if let Some(value) = lookup(document, setting.key) {
+                return entry(
+                    Some(json(value)),
+                    Source::Repo,
+                    Some(path.display().to_string()),
+                );
+            }
+        }
+        if let Some(value) = lookup(&files.global, &self.path()) {
+            let origin = self.profile.as_ref().map(|name| format!("profiles.{name}"));
+            return entry(Some(json(value)), Source::File, origin);
+        }
+        match setting.default {
+            Some(default) => entry(Some(typed(setting.kind, default)), Source::Default, None),
+            None => entry(None, Source::Unset, None),
+        }
+    }
+}
+
+/// Print a setting's effective value and where it came from
+pub fn get(key: &str, flags: &Flags) -> Result<()> {
+    let target = Target::parse(key)?;
+    let entry = target.resolve(&Files::read()?, flags);
+
+    output::print_item(&entry, |entry| {
+        println!("{} ({})", entry.display_value(), describe_source(entry));
+    });
+
+    Ok(())
+}
+
+/// List every known setting with its effective value and source
+pub fn list(flags: &Flags) -> Result<()> {
+    let files = Files::read()?;
+    let profile = config::profile();
+
+    let entries: Vec<Entry> = SETTINGS
+        .iter()
+        .map(|setting| {
+            let target = Target {
+                setting,
+                profile: profile.clone().filter(|_| setting.per_profile),
+            };
+            target.resolve(&files, flags).redact()
+        })
+        .collect();
+
+    output::print_table(
+        &entries,
+        |entry| EntryRow::from(entry),
+        |entry| {
+            format!(
+                "{} = {} ({})",
+                entry.key,
+                entry.display_value(),
+                describe_source(entry)
+            )
+        },
+    );
+
+    Ok(())
+}
+
+/// Set a value in config.toml, keeping comments and layout
+pub fn set(key: &str, raw: &str) -> Result<()> {
+    let target = Target::parse(key)?;
+
+    let path = Config::config_path()?;
+    let mut document = config::read_document(&path)?;
+    assign(&mut document, &target, raw, &path)?;
+    config::write_document(&path, &document)?;
+
+    let shown = if target.setting.secret { REDACTED } else { raw };
+    output::print_message(&format!("Set {} = {shown}", target.path()));
+    Ok(())
+}
+
+/// Remove a value from config.toml
+pub fn unset(key: &str) -> Result<()> {
+    let target = Target::parse(key)?;
+
+    let path = Config::config_path()?;
+    let mut document = config::read_document(&path)?;
+    let (table, name) = parent_table(&mut document, &target, &path)?;
+
+    if table.remove(name).is_none() {
+        output::print_message(&format!(
+            "{} is not set in {}",
+            target.path(),
+            path.display()
+        ));
+        return Ok(());
+    }
+
+    config::write_document(&path, &document)?;
+    output::print_message(&format!("Unset {}", target.path()));
+    Ok(())
+}
+
+/// Open config.toml in the user's editor, then load it to report mistakes
+pub fn edit() -> Result<()> {
+    let path = Config::config_path()?;
+    if !path.exists() {
+        config::write_document(&path, &DocumentMut::new())?;
+    }
+
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| {
+            if cfg!(target_os = "windows") {
+                "notepad".to_string()
+            } else {
+                "vi".to_string()
+            }
+        });
+
+    // Run through the shell so EDITOR can carry arguments (e.g. "code --wait")
+    let status = if cfg!(target_os = "windows") {
+        Command::new("cmd")
+            .args(["/C", &format!("{editor} \"{}\"", path.display())])
+            .status()
+    } else {
+        Command::new("sh")
+            .args(["-c", &format!("{editor} \"$1\""), "sh"])
+            .arg(&path)
+            .status()
+    }
+    .map_err(|e| LinearError::ConfigEdit {
+        path: path.clone(),
+        message: format!("could not run `{editor}`: {e}"),
+    })?;
+
+    if !status.success() {
+        return Err(LinearError::ConfigEdit {
+            path,
+            message: format!("`{editor}` exited with {status}"),
+        });
+    }
+
+    // Loading reports syntax and type errors, and warns about unknown keys
+    Config::load()?;
+    output::print_message(&format!("Checked {}", path.display()));
+    Ok(())
+}
+
+/// Print the path of config.toml
+pub fn path() -> Result<()> {
+    let path = ConfigPath {
+        path: Config::config_path()?,
+    };
+    output::print_item(&path, |p| println!("{}", p.path.display()));
+    Ok(())
+}
+
+fn describe_source(entry: &Entry) -> String {
+    match &entry.origin {
+        Some(origin) => format!("{} {origin}", entry.source),
+        None => entry.source.to_string(),
+    }
+}
+
+/// The value at a dotted `path` in `document`, if set
+fn lookup<'a>(document: &'a DocumentMut, path: &str) -> Option<&'a Value> {
+    let mut item = document.as_item();
+    for part in path.split('.') {
+        item = item.get(part)?;
+    }
+    item.as_value()
+}
+
+/// Set `target` to `raw` in `document`, keeping an end-of-line comment
+fn assign(document: &mut DocumentMut, target: &Target, raw: &str, path: &Path) -> Result<()> {
+    let mut value = parse(target, raw)?;
+    let (table, name) = parent_table(document, target, path)?;
+
+    // Replacing the item in place keeps the key and the comments above it
+    match table.get_mut(name) {
+        Some(Item::Value(old)) => {
+            *value.decor_mut() = old.decor().clone();
+            *old = value;
+        }
+        _ => {
+            table.insert(name, Item::Value(value));
+        }
+    }
+    Ok(())
+}
+
+/// The table holding `target`'s key (created if missing), and the key's name in it
+fn parent_table<'a>(
+    document: &'a mut DocumentMut,
+    target: &Target,
+    path: &Path,
+) -> Result<(&'a mut dyn TableLike, &'static str)> {
+    let mut table: &mut dyn TableLike =
+        config::target_table(document, target.profile.as_deref(), path)?;
+
+    let mut parts: Vec<&'static str> = target.setting.key.split('.').collect();
+    let name = parts.pop().unwrap_or(target.setting.key);
+    for part in parts {
+        if table.get(part).is_none() {
+            table.insert(part, Item::Table(Table::new()));
+        }
+        table = table
+            .get_mut(part)
+            .and_then(Item::as_table_like_mut)
+            .ok_or_else(|| LinearError::ConfigEdit {
+                path: path.to_path_buf(),
+                message: format!("`{part}` is not a table"),
+            })?;
+    }
+
+    Ok((table, name))
+}
+
+/// Check `raw` against the setting's type and convert it to a TOML value
+fn parse(target: &Target, raw: &str) -> Result<Value> {
+    let invalid = |message: String| LinearError::InvalidConfigValue {
+        key: target.path(),
+        message,
+    };
+
+    Ok(match target.setting.kind {
+        ValueKind::String => raw.into(),
+        ValueKind::Bool => match raw.to_ascii_lowercase().as_str() {
+            "true" | "yes" | "on" | "1" => true.into(),
+            "false" | "no" | "off" | "0" => false.into(),
+            _ => return Err(invalid(format!("expected true or false, got '{raw}'"))),
+        },
+        ValueKind::Integer(min, max) => match raw.parse::<i64>() {
+            Ok(n) if (min..=max).contains(&n) => n.into(),
+            _ => {
+                return Err(invalid(format!(
+                    "expected a whole number from {min} to {max}, got '{raw}'"
+                )))
+            }
+        },
+        ValueKind::List => raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .collect::<Array>()
+            .into(),
+        ValueKind::Choice(choices) => match choices.iter().find(|c| c.eq_ignore_ascii_case(raw)) {
+            Some(choice) => (*choice).into(),
+            None => {
+                return Err(invalid(format!(
+                    "expected one of {}, got '{raw}'",
+                    choices.join(", ")
+                )))
+            }
+        },
+    })
+}
+
+/// A flag, environment or default value as JSON of the setting's type
+fn typed(kind: ValueKind, raw: &str) -> serde_json::Value {
+    match kind {
+        ValueKind::Bool => raw
+            .parse()
+            .map_or_else(|_| raw.into(), serde_json::Value::Bool),
+        ValueKind::Integer(..) => raw.parse::<i64>().map_or_else(|_| raw.into(), Into::into),
+        ValueKind::List => raw
+            .split(',')
+            .map(str::trim)
+            .filter(|item| !item.is_empty())
+            .collect(),
+        ValueKind::String | ValueKind::Choice(_) => raw.into(),
+    }
+}
+
+fn json(value: &Value) -> serde_json::Value {
+    match value {
+        Value::String(s) => s.value().as_str().into(),
+        Value::Integer(n) => (*n.value()).into(),
+        Value::Float(n) => (*n.value()).into(),
+        Value::Boolean(b) => (*b.value()).into(),
+        Value::Array(items) => items.iter().map(json).collect(),
+        Value::Datetime(_) | Value::InlineTable(_) => value.to_string().trim().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_preserves_comments_and_validates() {
+        let mut document: DocumentMut = "# Linear settings\ndefault_team = \"ENG\" # main team\n\n[network]\nproxy = \"http://proxy:3128\"\n"
+            .parse()
+            .unwrap();
+        let path = Path::new("config.toml");
+
+        let target = Target::parse("default_team").unwrap();
+        assign(&mut document, &target, "OPS", path).unwrap();
+        let target = Target::parse("network.connect_timeout_secs").unwrap();
+        assign(&mut document, &target, "30", path).unwrap();
+
+        assert_eq!(
+            document.to_string(),
+            "# Linear settings\ndefault_team = \"OPS\" # main team\n\n[network]\nproxy = \"http://proxy:3128\"\nconnect_timeout_secs = 30\n"
+        );
+
+        assert!(parse(&target, "0").is_err());
+        assert!(parse(&Target::parse("keyring").unwrap(), "maybe").is_err());
+        assert!(matches!(
+            Target::parse("default_tem"),
+            Err(LinearError::UnknownConfigKey { hint, .. }) if hint.contains("default_team")
+        ));
+        assert!(Target::parse("profiles.oss.network.proxy").is_err());
+    }
+}
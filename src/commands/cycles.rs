@@ -7,6 +7,7 @@ use crate::client::LinearClient;
 use crate::config::Config;
 use crate::error::{LinearError, Result};
 use crate::output::{self, format_date_only};
+use crate::pagination;
 use crate::responses::Connection;
 use crate::types::Cycle;
 
@@ -34,8 +35,8 @@ impl From<&Cycle> for CycleRow {
 }
 
 const LIST_CYCLES_QUERY: &str = r#"
-query ListCycles($filter: CycleFilter) {
-    cycles(filter: $filter) {
+query ListCycles($filter: CycleFilter, $first: Int, $after: String) {
+    cycles(filter: $filter, first: $first, after: $after) {
         nodes {
             id
             name
@@ -43,6 +44,10 @@ query ListCycles($filter: CycleFilter) {
             startsAt
             endsAt
         }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
     }
 }
 "#;
@@ -95,31 +100,6 @@ struct Team {
     name: String,
 }
 
-/// Resolve a cycle number to its ID for a given team.
-pub async fn resolve_cycle_id(
-    client: &LinearClient,
-    team_key: &str,
-    cycle_number: i32,
-) -> Result<String> {
-    let variables = Some(json!({
-        "filter": {
-            "team": {
-                "key": { "eq": team_key }
-            }
-        }
-    }));
-
-    let response: CyclesResponse = client.query(LIST_CYCLES_QUERY, variables).await?;
-
-    response
-        .cycles
-        .nodes
-        .iter()
-        .find(|c| c.number == cycle_number)
-        .map(|c| c.id.clone())
-        .ok_or_else(|| LinearError::CycleNotFound(cycle_number.to_string()))
-}
-
 pub async fn list(client: &LinearClient, config: &Config, args: CycleListArgs) -> Result<()> {
     let team_key = config.resolve_team(args.team.as_deref());
 
@@ -133,10 +113,17 @@ pub async fn list(client: &LinearClient, config: &Config, args: CycleListArgs) -
         })
     });
 
-    let response: CyclesResponse = client.query(LIST_CYCLES_QUERY, variables).await?;
+    let cycles = pagination::fetch(
+        client,
+        LIST_CYCLES_QUERY,
+        variables,
+        args.page.page_limit(),
+        |response: CyclesResponse| Ok(response.cycles),
+    )
+    .await?;
 
     output::print_table(
-        &response.cycles.nodes,
+        &cycles,
         |cycle| CycleRow::from(cycle),
         |cycle| {
             format!(
@@ -174,3 +161,15 @@ pub async fn view(client: &LinearClient, id: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<CyclesResponse>(LIST_CYCLES_QUERY);
+        schema::assert_response::<CycleResponse>(GET_CYCLE_QUERY);
+    }
+}
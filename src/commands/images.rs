@@ -8,6 +8,7 @@ use url::Url;
 
 use crate::cli::DownloadImagesArgs;
 use crate::client::LinearClient;
+use crate::download::{self, Fetched, Manifest, Progress};
 use crate::error::{LinearError, Result};
 use crate::output;
 
@@ -79,10 +80,12 @@ fn is_linear_url(url: &str) -> bool {
 }
 
 /// Generate a filename for a downloaded image
-fn generate_filename(issue_id: &str, image: &MarkdownImage, url: &Url) -> String {
+fn generate_filename(issue_id: &str, image: &MarkdownImage) -> String {
     // Try to extract extension from URL path
+    let url = Url::parse(&image.url).ok();
     let extension = url
-        .path_segments()
+        .as_ref()
+        .and_then(|url| url.path_segments())
         .and_then(|mut segs| segs.next_back())
         .and_then(|filename| {
             let parts: Vec<&str> = filename.rsplitn(2, '.').collect();
@@ -119,45 +122,45 @@ fn generate_filename(issue_id: &str, image: &MarkdownImage, url: &Url) -> String
 /// Download a single image
 async fn download_image(
     http: &Client,
-    api_key: &str,
+    authorization: &str,
     image: &MarkdownImage,
-    output_dir: &Path,
-    issue_id: &str,
-) -> Result<PathBuf> {
+    file_path: PathBuf,
+    manifest: &Manifest,
+    progress: &Progress,
+) -> Result<(PathBuf, Fetched)> {
     let url = Url::parse(&image.url).map_err(|_| LinearError::InvalidUrl(image.url.clone()))?;
 
     let mut request = http.get(url.clone());
 
     // Add auth header for Linear-hosted images
     if is_linear_url(&image.url) {
-        request = request.header("Authorization", api_key);
+        request = request.header("Authorization", authorization);
     }
 
-    let response = request.send().await?;
-
-    if !response.status().is_success() {
-        return Err(LinearError::ImageDownloadFailed {
+    let fetched = download::fetch(
+        request,
+        &image.url,
+        &file_path,
+        manifest,
+        progress,
+        |status| LinearError::ImageDownloadFailed {
             url: image.url.clone(),
-            status: response.status().as_u16(),
-        });
-    }
-
-    let bytes = response.bytes().await?;
-
-    let filename = generate_filename(issue_id, image, &url);
-    let file_path = output_dir.join(&filename);
-
-    std::fs::write(&file_path, &bytes)?;
+            status,
+        },
+    )
+    .await?;
 
-    Ok(file_path)
+    Ok((file_path, fetched))
 }
 
 /// Generate a filename for a downloaded link
-fn generate_link_filename(issue_id: &str, link: &MarkdownLink, url: &Url) -> String {
+fn generate_link_filename(issue_id: &str, link: &MarkdownLink) -> String {
     // Try to extract filename from URL path
+    let url = Url::parse(&link.url).ok();
     let url_filename = url
-        .path_segments()
-        .and_then(|segs| segs.last())
+        .as_ref()
+        .and_then(|url| url.path_segments())
+        .and_then(|mut segs| segs.next_back())
         .filter(|s| !s.is_empty());
 
     // Use link text if it looks like a filename, otherwise use URL filename
@@ -191,45 +194,44 @@ fn generate_link_filename(issue_id: &str, link: &MarkdownLink, url: &Url) -> Str
 /// Download a single link
 async fn download_link(
     http: &Client,
-    api_key: &str,
+    authorization: &str,
     link: &MarkdownLink,
-    output_dir: &Path,
-    issue_id: &str,
-) -> Result<PathBuf> {
+    file_path: PathBuf,
+    manifest: &Manifest,
+    progress: &Progress,
+) -> Result<(PathBuf, Fetched)> {
     let url = Url::parse(&link.url).map_err(|_| LinearError::InvalidUrl(link.url.clone()))?;
 
     let mut request = http.get(url.clone());
 
     // Add auth header for Linear-hosted links
     if is_linear_url(&link.url) {
-        request = request.header("Authorization", api_key);
+        request = request.header("Authorization", authorization);
     }
 
-    let response = request.send().await?;
-
-    if !response.status().is_success() {
-        return Err(LinearError::AttachmentDownloadFailed {
+    let fetched = download::fetch(
+        request,
+        &link.url,
+        &file_path,
+        manifest,
+        progress,
+        |status| LinearError::AttachmentDownloadFailed {
             url: link.url.clone(),
-            status: response.status().as_u16(),
-        });
-    }
-
-    let bytes = response.bytes().await?;
-
-    let filename = generate_link_filename(issue_id, link, &url);
-    let file_path = output_dir.join(&filename);
-
-    std::fs::write(&file_path, &bytes)?;
+            status,
+        },
+    )
+    .await?;
 
-    Ok(file_path)
+    Ok((file_path, fetched))
 }
 
 /// Download all links from issue description
 pub async fn download_links(
-    api_key: &str,
+    client: &LinearClient,
     description: &str,
     issue_id: &str,
     output_dir: &Path,
+    manifest: &Manifest,
 ) -> Result<Vec<DownloadResult>> {
     // Ensure output directory exists
     if !output_dir.exists() {
@@ -252,24 +254,33 @@ pub async fn download_links(
         return Ok(vec![]);
     }
 
-    let http = Client::new();
-
-    let mut results = Vec::new();
-
-    for link in &links_to_download {
-        let result = match download_link(&http, api_key, link, output_dir, issue_id).await {
-            Ok(path) => DownloadResult::Success {
-                index: link.index,
-                path,
-            },
-            Err(e) => DownloadResult::Failed {
-                index: link.index,
-                url: link.url.clone(),
-                error: e.to_string(),
-            },
-        };
-        results.push(result);
-    }
+    let filenames = download::unique_filenames(
+        links_to_download
+            .iter()
+            .map(|link| (link.index, generate_link_filename(issue_id, link)))
+            .collect(),
+    );
+    let links_to_download: Vec<_> = links_to_download.into_iter().zip(filenames).collect();
+
+    let http = client.http();
+    let authorization = client.authorization();
+    let progress = Progress::new(links_to_download.len());
+
+    let results = download::bounded(
+        links_to_download,
+        client.download_concurrency(),
+        |(link, filename)| {
+            let (authorization, progress) = (&authorization, &progress);
+            async move {
+                let file_path = output_dir.join(filename);
+                let outcome =
+                    download_link(http, authorization, &link, file_path, manifest, progress).await;
+                DownloadResult::from_outcome(link.index, &link.url, outcome)
+            }
+        },
+    )
+    .await;
+    progress.finish();
 
     Ok(results)
 }
@@ -281,6 +292,11 @@ pub enum DownloadResult {
         index: usize,
         path: PathBuf,
     },
+    /// Already downloaded by an earlier run, per the manifest
+    Skipped {
+        index: usize,
+        path: PathBuf,
+    },
     Failed {
         index: usize,
         url: String,
@@ -289,18 +305,49 @@ pub enum DownloadResult {
 }
 
 impl DownloadResult {
+    /// Result for item `index` (fetched from `url`) given how its download went
+    pub fn from_outcome(index: usize, url: &str, outcome: Result<(PathBuf, Fetched)>) -> Self {
+        match outcome {
+            Ok((path, Fetched::Downloaded)) => DownloadResult::Success { index, path },
+            Ok((path, Fetched::Unchanged)) => DownloadResult::Skipped { index, path },
+            Err(e) => DownloadResult::Failed {
+                index,
+                url: url.to_string(),
+                error: e.to_string(),
+            },
+        }
+    }
+
     pub fn is_success(&self) -> bool {
-        matches!(self, DownloadResult::Success { .. })
+        matches!(
+            self,
+            DownloadResult::Success { .. } | DownloadResult::Skipped { .. }
+        )
     }
 }
 
+/// Turn any failed downloads into a partial-failure error
+pub fn ensure_all_succeeded(results: &[DownloadResult]) -> Result<()> {
+    let failed = results.iter().filter(|r| !r.is_success()).count();
+
+    if failed > 0 {
+        return Err(LinearError::PartialFailure {
+            failed,
+            total: results.len(),
+        });
+    }
+
+    Ok(())
+}
+
 /// Download all images (or specific index) from issue description
 pub async fn download_images(
-    api_key: &str,
+    client: &LinearClient,
     description: &str,
     issue_id: &str,
     output_dir: &Path,
     index: Option<usize>,
+    manifest: &Manifest,
 ) -> Result<Vec<DownloadResult>> {
     // Ensure output directory exists
     if !output_dir.exists() {
@@ -314,11 +361,20 @@ pub async fn download_images(
         return Ok(vec![]);
     }
 
+    // Named before filtering, so an image keeps its file name with --index
+    let filenames = download::unique_filenames(
+        images
+            .iter()
+            .map(|image| (image.index, generate_filename(issue_id, image)))
+            .collect(),
+    );
+    let images = images.into_iter().zip(filenames);
+
     // Filter to specific index if provided
     let images_to_download: Vec<_> = if let Some(idx) = index {
-        images.into_iter().filter(|img| img.index == idx).collect()
+        images.filter(|(img, _)| img.index == idx).collect()
     } else {
-        images
+        images.collect()
     };
 
     if images_to_download.is_empty() {
@@ -327,24 +383,26 @@ pub async fn download_images(
         }
     }
 
-    let http = Client::new();
-
-    let mut results = Vec::new();
-
-    for image in &images_to_download {
-        let result = match download_image(&http, api_key, image, output_dir, issue_id).await {
-            Ok(path) => DownloadResult::Success {
-                index: image.index,
-                path,
-            },
-            Err(e) => DownloadResult::Failed {
-                index: image.index,
-                url: image.url.clone(),
-                error: e.to_string(),
-            },
-        };
-        results.push(result);
-    }
+    let http = client.http();
+    let authorization = client.authorization();
+    let progress = Progress::new(images_to_download.len());
+
+    let results = download::bounded(
+        images_to_download,
+        client.download_concurrency(),
+        |(image, filename)| {
+            let (authorization, progress) = (&authorization, &progress);
+            async move {
+                let file_path = output_dir.join(filename);
+                let outcome =
+                    download_image(http, authorization, &image, file_path, manifest, progress)
+                        .await;
+                DownloadResult::from_outcome(image.index, &image.url, outcome)
+            }
+        },
+    )
+    .await;
+    progress.finish();
 
     Ok(results)
 }
@@ -395,12 +453,14 @@ pub async fn download_images_command(
         return Ok(());
     }
 
+    let manifest = Manifest::load(&args.output)?;
     let results = download_images(
-        client.api_key(),
+        client,
         description,
         &issue.identifier,
         &args.output,
         args.index,
+        &manifest,
     )
     .await?;
 
@@ -414,7 +474,7 @@ pub async fn download_images_command(
 
     print_download_results(&results);
 
-    Ok(())
+    ensure_all_succeeded(&results)
 }
 
 pub fn print_download_results(results: &[DownloadResult]) {
@@ -426,6 +486,9 @@ pub fn print_download_results(results: &[DownloadResult]) {
             DownloadResult::Success { index, path, .. } => {
                 output::print_message(&format!("Downloaded image {} to {}", index, path.display()));
             }
+            DownloadResult::Skipped { index, path } => {
+                output::print_message(&format!("Image {} unchanged at {}", index, path.display()));
+            }
             DownloadResult::Failed { index, url, error } => {
                 eprintln!("Failed to download image {} ({}): {}", index, url, error);
             }
@@ -447,6 +510,7 @@ pub fn print_download_results(results: &[DownloadResult]) {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::schema;
 
     #[test]
     fn test_parse_markdown_images_empty() {
@@ -491,6 +555,24 @@ More text
         assert_eq!(images[1].index, 2);
     }
 
+    #[test]
+    fn test_images_with_the_same_alt_text_get_separate_files() {
+        let images = parse_markdown_images(
+            "![screenshot](https://uploads.linear.app/a.png)\n\
+             ![screenshot](https://uploads.linear.app/b.png)",
+        );
+        let filenames = download::unique_filenames(
+            images
+                .iter()
+                .map(|image| (image.index, generate_filename("ENG-1", image)))
+                .collect(),
+        );
+        assert_eq!(
+            filenames,
+            ["ENG-1__screenshot.png", "ENG-1__screenshot_2.png"]
+        );
+    }
+
     #[test]
     fn test_is_linear_url() {
         assert!(is_linear_url("https://uploads.linear.app/abc123"));
@@ -543,4 +625,9 @@ Our korean translator gets a black screen on launch.
         assert_eq!(links[0].text, "logs.zip");
         assert_eq!(links[1].text, "another.txt");
     }
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<IssueResponse>(GET_ISSUE_QUERY);
+    }
 }
@@ -1,71 +1,272 @@
 use std::io::{self, Write};
 
-use crate::config::Config;
-use crate::error::{LinearError, Result};
-
-pub async fn run() -> Result<()> {
-    let config_path = Config::config_path()?;
+use serde::Deserialize;
+use toml_edit::value;
 
-    if config_path.exists() {
-        print!(
-            "Config file already exists at {}. Overwrite? [y/N] ",
-            config_path.display()
-        );
-        io::stdout().flush()?;
+use crate::auth::Credentials;
+use crate::cassette::Cassette;
+use crate::cli::InitArgs;
+use crate::client::LinearClient;
+use crate::commands::teams::LIST_TEAMS_QUERY;
+use crate::config::{self, Config};
+use crate::error::{LinearError, Result};
+use crate::pagination::{self, PageLimit};
+use crate::responses::Connection;
+use crate::secret;
+use crate::types::Team;
 
-        let mut input = String::new();
-        io::stdin().read_line(&mut input)?;
+/// Keys holding the API key; replaced together so stale credentials don't linger
+const CREDENTIAL_KEYS: &[&str] = &["api_key", "api_key_command", "keyring"];
 
-        if !input.trim().eq_ignore_ascii_case("y") {
-            println!("Aborted.");
-            return Ok(());
+const INIT_VIEWER_QUERY: &str = r#"
+query InitViewer {
+    viewer {
+        name
+        email
+        organization {
+            name
+            urlKey
         }
     }
+}
+"#;
+
+#[derive(Deserialize)]
+struct ViewerResponse {
+    viewer: Viewer,
+}
 
-    println!("Linear CLI Configuration");
-    println!("========================\n");
+#[derive(Deserialize)]
+struct Viewer {
+    name: String,
+    email: String,
+    organization: Organization,
+}
 
-    // Get API key
-    print!("Enter your Linear API key (create one at https://linear.app/settings/api): ");
-    io::stdout().flush()?;
+#[derive(Deserialize)]
+struct Organization {
+    name: String,
+    #[serde(rename = "urlKey")]
+    url_key: String,
+}
+
+#[derive(Deserialize)]
+struct TeamsResponse {
+    teams: Connection<Team>,
+}
+
+/// Set up config.toml (or the selected profile): check the API key against
+/// Linear, then choose the default team. Other settings are left as they are.
+pub async fn run(args: InitArgs) -> Result<()> {
+    let config_path = Config::config_path()?;
+    let profile = config::profile();
+    let config = Config::load_or_new_profile()?;
+    let interactive = args.api_key.is_none();
+
+    let mut document = config::read_document(&config_path)?;
+    let table = config::target_table(&mut document, profile.as_deref(), &config_path)?;
+    let configured = table.iter().any(|(key, _)| CREDENTIAL_KEYS.contains(&key));
+    let current_team = table
+        .get("default_team")
+        .and_then(|item| item.as_str())
+        .map(String::from);
 
-    let mut api_key = String::new();
-    io::stdin().read_line(&mut api_key)?;
-    let api_key = api_key.trim();
+    if interactive {
+        println!("Linear CLI Configuration");
+        println!("========================\n");
+        if configured {
+            let target = match &profile {
+                Some(name) => format!("profile '{name}' in"),
+                None => "settings in".to_string(),
+            };
+            println!("Updating {target} {}\n", config_path.display());
+        }
+    }
 
+    // None keeps the key that's already configured
+    let new_key = match args.api_key.as_deref() {
+        Some("-") => Some(read_line()?),
+        Some(key) => Some(key.trim().to_string()),
+        None => {
+            let hint = if configured {
+                " [Enter to keep the current key]"
+            } else {
+                ""
+            };
+            let key = prompt(&format!(
+                "Linear API key (create one at https://linear.app/settings/api){hint}: "
+            ))?;
+            (!key.is_empty() || !configured).then_some(key)
+        }
+    };
+    let api_key = match &new_key {
+        Some(key) => key.clone(),
+        None => config.stored_api_key()?,
+    };
     if api_key.is_empty() {
         return Err(LinearError::MissingApiKey);
     }
 
-    // Get default team (optional)
-    print!("Enter default team key (e.g., ENG) [optional]: ");
-    io::stdout().flush()?;
+    let mut client = LinearClient::from_config(Credentials::ApiKey(api_key), &config)?;
+    if let Some(cassette) = Cassette::from_env()? {
+        client = client.with_cassette(cassette);
+    }
 
-    let mut default_team = String::new();
-    io::stdin().read_line(&mut default_team)?;
-    let default_team = default_team.trim();
+    let viewer = client
+        .query::<ViewerResponse>(INIT_VIEWER_QUERY, None)
+        .await?
+        .viewer;
+    println!(
+        "Authenticated as {} <{}> in workspace {} (linear.app/{})",
+        viewer.name, viewer.email, viewer.organization.name, viewer.organization.url_key
+    );
 
-    // Create config directory if it doesn't exist
-    if let Some(parent) = config_path.parent() {
-        std::fs::create_dir_all(parent).map_err(|e| LinearError::ConfigRead {
-            path: config_path.clone(),
-            source: e,
-        })?;
-    }
+    let teams = pagination::fetch(
+        &client,
+        LIST_TEAMS_QUERY,
+        None,
+        PageLimit::All,
+        |response: TeamsResponse| Ok(response.teams),
+    )
+    .await?;
+
+    // A team from another workspace (after switching keys) isn't kept
+    let current_team = match current_team {
+        Some(key) if pick_team(&teams, &key).is_none() => {
+            eprintln!("Warning: default team {key} is not in this workspace; removing it");
+            None
+        }
+        current => current,
+    };
+    let default_team = match args.team {
+        Some(key) => Some(
+            pick_team(&teams, &key)
+                .ok_or(LinearError::TeamNotFound(key))?
+                .key
+                .clone(),
+        ),
+        None if interactive => choose_team(&teams, current_team.as_deref())?,
+        None => current_team,
+    };
 
-    // Write config file
-    let mut config_content = format!("api_key = \"{api_key}\"\n");
-    if !default_team.is_empty() {
-        config_content.push_str(&format!("default_team = \"{default_team}\"\n"));
+    let table = config::target_table(&mut document, profile.as_deref(), &config_path)?;
+    if let Some(api_key) = &new_key {
+        for key in CREDENTIAL_KEYS {
+            table.remove(key);
+        }
+
+        // Prefer the OS keyring; fall back to the (owner-only) config file
+        match secret::keyring_set(api_key) {
+            Ok(()) => {
+                println!("API key stored in the system keyring");
+                table.insert("keyring", value(true));
+            }
+            Err(e) => {
+                eprintln!("Warning: could not use the system keyring ({e}); storing the API key in the config file");
+                table.insert("api_key", value(api_key));
+            }
+        }
+    }
+    if let Some(key) = &default_team {
+        table.insert("default_team", value(key));
+    } else {
+        table.remove("default_team");
     }
 
-    std::fs::write(&config_path, config_content).map_err(|e| LinearError::ConfigRead {
-        path: config_path.clone(),
-        source: e,
-    })?;
+    config::write_document(&config_path, &document)?;
 
     println!("\nConfig saved to {}", config_path.display());
-    println!("You can now use 'linear' commands!");
+    if let Some(key) = &default_team {
+        println!("Default team: {key}");
+    }
+    match &profile {
+        Some(name) => println!("Use it with 'linear --profile {name}' or LINEAR_PROFILE={name}"),
+        None => println!("You can now use 'linear' commands!"),
+    }
 
     Ok(())
 }
+
+/// Ask for the default team by number or key. Enter keeps `current`.
+fn choose_team(teams: &[Team], current: Option<&str>) -> Result<Option<String>> {
+    if teams.is_empty() {
+        return Ok(current.map(String::from));
+    }
+
+    println!("\nTeams:");
+    for (i, team) in teams.iter().enumerate() {
+        println!("  {:>2}) {:<8} {}", i + 1, team.key, team.name);
+    }
+    let hint = match current {
+        Some(key) => format!("Enter to keep {key}"),
+        None => "Enter for none".to_string(),
+    };
+
+    loop {
+        let input = prompt(&format!("Default team (number or key) [{hint}]: "))?;
+        if input.is_empty() {
+            return Ok(current.map(String::from));
+        }
+        match pick_team(teams, &input) {
+            Some(team) => return Ok(Some(team.key.clone())),
+            None => println!(
+                "No team '{input}'; enter a number from 1 to {} or a team key",
+                teams.len()
+            ),
+        }
+    }
+}
+
+/// A team by its 1-based number in `teams` or by key (any case)
+fn pick_team<'a>(teams: &'a [Team], input: &str) -> Option<&'a Team> {
+    match input.parse::<usize>() {
+        Ok(n) => n.checked_sub(1).and_then(|i| teams.get(i)),
+        Err(_) => teams
+            .iter()
+            .find(|team| team.key.eq_ignore_ascii_case(input)),
+    }
+}
+
+fn prompt(message: &str) -> Result<String> {
+    print!("{message}");
+    io::stdout().flush()?;
+    read_line()
+}
+
+/// One trimmed line from stdin (empty at end of input)
+fn read_line() -> Result<String> {
+    let mut line = String::new();
+    io::stdin().read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_pick_team() {
+        let team = |key: &str| Team {
+            id: format!("id-{key}"),
+            key: key.to_string(),
+            name: format!("Team {key}"),
+        };
+        let teams = [team("ENG"), team("OPS")];
+
+        assert_eq!(pick_team(&teams, "2").map(|t| t.key.as_str()), Some("OPS"));
+        assert_eq!(
+            pick_team(&teams, "eng").map(|t| t.key.as_str()),
+            Some("ENG")
+        );
+        assert!(pick_team(&teams, "0").is_none());
+        assert!(pick_team(&teams, "3").is_none());
+        assert!(pick_team(&teams, "WEB").is_none());
+    }
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<ViewerResponse>(INIT_VIEWER_QUERY);
+        schema::assert_response::<TeamsResponse>(LIST_TEAMS_QUERY);
+    }
+}
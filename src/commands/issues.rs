@@ -1,74 +1,44 @@
+use std::path::Path;
+
 use serde::Deserialize;
 use serde_json::json;
 use tabled::Tabled;
 
-use crate::cache::{Cache, CachedTeam};
-use crate::cli::{DownloadAllArgs, IssueCreateArgs, IssueListArgs, IssueUpdateArgs, IssueViewArgs};
+use crate::cache::{
+    Cache, CachedCycle, CachedLabel, CachedProject, CachedState, CachedTeam, CachedUser, Entity,
+};
+use crate::cli::{
+    DownloadAllArgs, IssueColumn, IssueCreateArgs, IssueFilterArgs, IssueListArgs, IssueSort,
+    IssueUpdateArgs, IssueViewArgs,
+};
 use crate::client::LinearClient;
 use crate::commands::attachments;
 use crate::commands::comments;
-use crate::commands::cycles;
-use crate::commands::images::{download_images, download_links, print_download_results};
+use crate::commands::images::{
+    download_images, download_links, ensure_all_succeeded, print_download_results, DownloadResult,
+};
 use crate::commands::labels;
+use crate::commands::views;
 use crate::config::Config;
+use crate::download::{Manifest, VerifyResult, VerifyStatus};
 use crate::error::{LinearError, Result};
-use crate::output::{self, format_date, is_json_output, status_colored, truncate};
-use crate::responses::{
-    Connection, CreatedIssue, PageInfo, TeamNode, ViewerResponse, WorkflowStateNode,
-};
+use crate::mirror::Mirror;
+use crate::output::{self, format_date, status_colored, truncate};
+use crate::pagination::{self, PageLimit};
+use crate::responses::{Connection, CreatedIssue};
 use crate::types::Issue;
 
-#[derive(Tabled)]
-struct IssueRow {
-    #[tabled(rename = "ID")]
-    id: String,
-    #[tabled(rename = "Title")]
-    title: String,
-    #[tabled(rename = "Status")]
-    status: String,
-    #[tabled(rename = "Priority")]
-    priority: String,
-    #[tabled(rename = "Estimate")]
-    estimate: String,
-    #[tabled(rename = "Assignee")]
-    assignee: String,
-}
-
-impl From<&Issue> for IssueRow {
-    fn from(issue: &Issue) -> Self {
-        let (status_name, status_color) = issue
-            .state
-            .as_ref()
-            .map(|s| (s.name.clone(), Some(s.color.clone())))
-            .unwrap_or_default();
-
-        Self {
-            id: issue.identifier.clone(),
-            title: truncate(&issue.title, 50),
-            status: if is_json_output() {
-                status_name
-            } else {
-                status_colored(&status_name, status_color.as_deref())
-            },
-            priority: if is_json_output() {
-                issue.priority.to_string()
-            } else {
-                issue.priority.colored()
-            },
-            estimate: issue
-                .estimate
-                .map(|e| e.to_string())
-                .unwrap_or_default(),
-            assignee: issue
-                .assignee
-                .as_ref()
-                .map(|u| u.name.clone())
-                .unwrap_or_default(),
-        }
-    }
-}
-
-const ISSUE_FIELDS_FRAGMENT: &str = r#"
+/// Table columns when --columns isn't given
+const DEFAULT_COLUMNS: &[IssueColumn] = &[
+    IssueColumn::Id,
+    IssueColumn::Title,
+    IssueColumn::Status,
+    IssueColumn::Priority,
+    IssueColumn::Estimate,
+    IssueColumn::Assignee,
+];
+
+pub(crate) const ISSUE_FIELDS_FRAGMENT: &str = r#"
 fragment IssueFields on Issue {
     id
     identifier
@@ -116,10 +86,10 @@ fragment IssueFields on Issue {
 }
 "#;
 
-const LIST_ISSUES_QUERY: &str = const_format::concatcp!(
+pub(crate) const LIST_ISSUES_QUERY: &str = const_format::concatcp!(
     r#"
-query ListIssues($filter: IssueFilter, $first: Int, $after: String) {
-    issues(filter: $filter, first: $first, after: $after) {
+query ListIssues($filter: IssueFilter, $orderBy: PaginationOrderBy, $first: Int, $after: String) {
+    issues(filter: $filter, orderBy: $orderBy, first: $first, after: $after) {
         nodes {
             ...IssueFields
         }
@@ -174,44 +144,144 @@ const GET_VIEWER_QUERY: &str = r#"
 query Viewer {
     viewer {
         id
+        name
+        displayName
+        email
+        isMe
     }
 }
 "#;
 
-const GET_TEAM_BY_KEY_QUERY: &str = r#"
-query GetTeam($key: String!) {
-    teams(filter: { key: { eq: $key } }) {
+// Lookups needed by `create` that aren't cached yet, batched into one request.
+// Each optional part is only evaluated when its `$with*` flag is set.
+const RESOLVE_CREATE_QUERY: &str = r#"
+query ResolveIssueCreate(
+    $teamKey: String!
+    $withTeam: Boolean!
+    $withCycle: Boolean!
+    $cycleNumber: Float
+    $withLabels: Boolean!
+    $labelFilter: IssueLabelFilter
+    $withProject: Boolean!
+    $projectName: String
+    $withUser: Boolean!
+    $userFilter: UserFilter
+    $withViewer: Boolean!
+) {
+    team: teams(filter: { key: { eq: $teamKey } }) @include(if: $withTeam) {
         nodes {
             id
             key
             name
+            cycles(filter: { number: { eq: $cycleNumber } }) @include(if: $withCycle) {
+                nodes {
+                    id
+                    number
+                    name
+                }
+            }
+        }
+    }
+    labels: issueLabels(filter: $labelFilter, first: 250) @include(if: $withLabels) {
+        nodes {
+            id
+            name
+            team {
+                key
+            }
         }
     }
+    projects(filter: { name: { eqIgnoreCase: $projectName } }) @include(if: $withProject) {
+        nodes {
+            id
+            name
+        }
+    }
+    users(filter: $userFilter) @include(if: $withUser) {
+        nodes {
+            id
+            name
+            displayName
+            email
+            isMe
+        }
+    }
+    viewer @include(if: $withViewer) {
+        id
+        name
+        displayName
+        email
+        isMe
+    }
 }
 "#;
 
-const GET_STATES_QUERY: &str = r#"
-query GetStates($teamId: ID!) {
-    workflowStates(filter: { team: { id: { eq: $teamId } } }) {
+// Lookups needed by `update` and `close`, batched the same way
+const RESOLVE_UPDATE_QUERY: &str = r#"
+query ResolveIssueUpdate(
+    $id: String!
+    $withIssue: Boolean!
+    $withStates: Boolean!
+    $withCycle: Boolean!
+    $cycleNumber: Float
+    $withLabels: Boolean!
+    $labelFilter: IssueLabelFilter
+    $withUser: Boolean!
+    $userFilter: UserFilter
+    $withViewer: Boolean!
+) {
+    issue(id: $id) @include(if: $withIssue) {
+        team {
+            id
+            key
+            name
+            states @include(if: $withStates) {
+                nodes {
+                    id
+                    name
+                    type
+                }
+            }
+            cycles(filter: { number: { eq: $cycleNumber } }) @include(if: $withCycle) {
+                nodes {
+                    id
+                    number
+                    name
+                }
+            }
+        }
+    }
+    labels: issueLabels(filter: $labelFilter, first: 250) @include(if: $withLabels) {
         nodes {
             id
             name
-            type
+            team {
+                key
+            }
         }
     }
+    users(filter: $userFilter) @include(if: $withUser) {
+        nodes {
+            id
+            name
+            displayName
+            email
+            isMe
+        }
+    }
+    viewer @include(if: $withViewer) {
+        id
+        name
+        displayName
+        email
+        isMe
+    }
 }
 "#;
 
 #[derive(Deserialize)]
-struct IssuesResponse {
-    issues: IssuesConnection,
-}
-
-#[derive(Deserialize)]
-struct IssuesConnection {
-    nodes: Vec<Issue>,
-    #[serde(rename = "pageInfo")]
-    page_info: PageInfo,
+pub(crate) struct IssuesResponse {
+    pub issues: Connection<Issue>,
 }
 
 #[derive(Deserialize)]
@@ -244,17 +314,152 @@ struct IssueUpdateResult {
 }
 
 #[derive(Deserialize)]
-struct TeamsResponse {
-    teams: Connection<TeamNode>,
+struct ViewerResponse {
+    viewer: CachedUser,
+}
+
+/// Parts left out by `@include(if: false)` are absent from the response
+#[derive(Deserialize)]
+struct ResolveCreateResponse {
+    team: Option<Connection<ResolvedTeam>>,
+    labels: Option<Connection<CachedLabel>>,
+    projects: Option<Connection<CachedProject>>,
+    users: Option<Connection<CachedUser>>,
+    viewer: Option<CachedUser>,
 }
 
 #[derive(Deserialize)]
-struct WorkflowStatesResponse {
-    #[serde(rename = "workflowStates")]
-    workflow_states: Connection<WorkflowStateNode>,
+struct ResolveUpdateResponse {
+    issue: Option<ResolvedIssue>,
+    labels: Option<Connection<CachedLabel>>,
+    users: Option<Connection<CachedUser>>,
+    viewer: Option<CachedUser>,
+}
+
+#[derive(Deserialize)]
+struct ResolvedIssue {
+    team: ResolvedTeam,
+}
+
+#[derive(Deserialize)]
+struct ResolvedTeam {
+    id: String,
+    key: String,
+    name: String,
+    states: Option<Connection<CachedState>>,
+    cycles: Option<Connection<CachedCycle>>,
+}
+
+impl ResolvedTeam {
+    /// Cache the team with whatever was fetched along with it, returning its key
+    fn store(self, cache: &mut Cache) -> String {
+        if let Some(states) = self.states {
+            cache.set_states(&self.key, states.nodes);
+        }
+        for cycle in self.cycles.into_iter().flat_map(|c| c.nodes) {
+            cache.set_cycle(&self.key, cycle);
+        }
+        cache.set_team(CachedTeam {
+            id: self.id,
+            key: self.key.clone(),
+            name: self.name,
+        });
+
+        self.key
+    }
+}
+
+/// Cache the labels and users returned by a resolve query
+fn store_lookups(
+    cache: &mut Cache,
+    labels: Option<Connection<CachedLabel>>,
+    users: Option<Connection<CachedUser>>,
+    viewer: Option<CachedUser>,
+) {
+    for label in labels.into_iter().flat_map(|c| c.nodes) {
+        cache.set_label(label);
+    }
+    for user in users.into_iter().flat_map(|c| c.nodes).chain(viewer) {
+        cache.set_user(user);
+    }
+}
+
+/// How `--assignee` names a user
+enum Assignee<'a> {
+    Me,
+    Id(&'a str),
+    /// Name, display name or email
+    Name(&'a str),
+}
+
+impl<'a> Assignee<'a> {
+    fn parse(value: &'a str) -> Self {
+        let is_uuid = value.len() == 36 && value.chars().all(|c| c.is_ascii_hexdigit() || c == '-');
+        match value {
+            "me" => Assignee::Me,
+            _ if is_uuid => Assignee::Id(value),
+            _ => Assignee::Name(value),
+        }
+    }
+
+    /// Whether resolving this assignee needs the viewer, and the user query
+    /// it needs, given what is already cached
+    fn lookup(&self, cache: &Cache) -> (bool, Option<&'a str>) {
+        match *self {
+            Assignee::Me => (cache.viewer_id().is_none(), None),
+            Assignee::Name(name) if cache.user_id(name).is_none() => (false, Some(name)),
+            _ => (false, None),
+        }
+    }
+
+    fn id(&self, cache: &Cache) -> Result<String> {
+        match *self {
+            Assignee::Me => cache
+                .viewer_id()
+                .ok_or_else(|| LinearError::UserNotFound("me".to_string())),
+            Assignee::Id(id) => Ok(id.to_string()),
+            Assignee::Name(name) => cache
+                .user_id(name)
+                .ok_or_else(|| LinearError::UserNotFound(name.to_string())),
+        }
+    }
+}
+
+/// User filter matching a name, display name or email, ignoring case
+fn user_filter(query: Option<&str>) -> serde_json::Value {
+    match query {
+        Some(query) => json!({ "or": [
+            { "name": { "eqIgnoreCase": query } },
+            { "displayName": { "eqIgnoreCase": query } },
+            { "email": { "eqIgnoreCase": query } },
+        ] }),
+        None => json!(null),
+    }
+}
+
+/// Team key of an identifier like `ENG-123`; UUIDs have none
+fn team_key_of(id: &str) -> Option<&str> {
+    let (key, number) = id.rsplit_once('-')?;
+    let valid = !key.is_empty()
+        && key.chars().all(|c| c.is_ascii_alphanumeric())
+        && !number.is_empty()
+        && number.chars().all(|c| c.is_ascii_digit());
+    valid.then_some(key)
 }
 
 pub async fn list(client: &LinearClient, config: &Config, args: IssueListArgs) -> Result<()> {
+    let mut args = views::apply(config, args)?;
+    // Only the project default narrows listing; default_labels and
+    // default_assignee apply to `issue create` alone
+    if args.filter.project.is_none() && !args.any_project {
+        args.filter.project = config.default_project.clone();
+    }
+    let page_limit = args.page_limit();
+    if args.offline {
+        return list_offline(config, &args.filter, page_limit);
+    }
+    let args = args.filter;
+
     let mut filter = serde_json::Map::new();
 
     // Team filter
@@ -278,12 +483,17 @@ pub async fn list(client: &LinearClient, config: &Config, args: IssueListArgs) -
         );
     }
 
-    // Label filter
-    if let Some(label) = &args.label {
-        filter.insert(
-            "labels".to_string(),
-            json!({ "name": { "containsIgnoreCase": label } }),
-        );
+    // Label filter: every label must match
+    let label_filter = |label: &String| json!({ "name": { "containsIgnoreCase": label } });
+    if let Some((first, rest)) = args.label.split_first() {
+        filter.insert("labels".to_string(), label_filter(first));
+        if !rest.is_empty() {
+            let rest: Vec<_> = rest
+                .iter()
+                .map(|label| json!({ "labels": label_filter(label) }))
+                .collect();
+            filter.insert("and".to_string(), json!(rest));
+        }
     }
 
     // Cycle filter
@@ -296,44 +506,116 @@ pub async fn list(client: &LinearClient, config: &Config, args: IssueListArgs) -
 
     // Mine filter
     if args.mine {
-        let viewer: ViewerResponse = client.query(GET_VIEWER_QUERY, None).await?;
-        filter.insert(
-            "assignee".to_string(),
-            json!({ "id": { "eq": viewer.viewer.id } }),
-        );
+        let mut cache = Cache::load();
+        let viewer_id = match cache.viewer_id() {
+            Some(id) => id,
+            None => {
+                let response: ViewerResponse = client.query(GET_VIEWER_QUERY, None).await?;
+                let id = response.viewer.id.clone();
+                cache.set_user(response.viewer);
+                cache.save();
+                id
+            }
+        };
+        filter.insert("assignee".to_string(), json!({ "id": { "eq": viewer_id } }));
     }
 
-    // Pagination support
-    let page_size = if args.all { 100 } else { args.limit.min(250) };
-    let mut all_issues: Vec<Issue> = Vec::new();
-    let mut cursor: Option<String> = None;
+    let order_by = args.sort.map(|sort| match sort {
+        IssueSort::Created => "createdAt",
+        IssueSort::Updated => "updatedAt",
+    });
+    let issues = pagination::fetch(
+        client,
+        LIST_ISSUES_QUERY,
+        Some(json!({ "filter": filter, "orderBy": order_by })),
+        page_limit,
+        |response: IssuesResponse| Ok(response.issues),
+    )
+    .await?;
 
-    loop {
-        let mut variables = json!({
-            "filter": filter.clone(),
-            "first": page_size
-        });
+    print_issues(&issues, &args.columns);
 
-        if let Some(ref c) = cursor {
-            variables["after"] = json!(c);
-        }
+    Ok(())
+}
 
-        let response: IssuesResponse = client.query(LIST_ISSUES_QUERY, Some(variables)).await?;
-        all_issues.extend(response.issues.nodes);
+/// `list` answered from the mirror, with the same filter semantics as the API
+fn list_offline(config: &Config, args: &IssueFilterArgs, limit: PageLimit) -> Result<()> {
+    let mirror = Mirror::open_existing()?;
 
-        if !args.all || !response.issues.page_info.has_next_page {
-            break;
+    let team_key = config.resolve_team(args.team.as_deref());
+    if let Some(key) = &team_key {
+        if mirror.team(key)?.is_none() {
+            return Err(LinearError::NotSynced(format!(
+                "team {key} is not mirrored"
+            )));
         }
+    }
 
-        cursor = response.issues.page_info.end_cursor;
-        if cursor.is_none() {
-            break;
-        }
+    let viewer_id = if args.mine {
+        let id = mirror.meta("viewer_id")?;
+        Some(id.ok_or_else(|| LinearError::NotSynced("the viewer is unknown".to_string()))?)
+    } else {
+        None
+    };
+
+    let mut issues: Vec<Issue> = mirror
+        .issues(team_key.as_deref())?
+        .into_iter()
+        .filter(|issue| {
+            let assignee = issue.assignee.as_ref().map(|u| u.id.as_str());
+            viewer_id.is_none() || assignee == viewer_id.as_deref()
+        })
+        .filter(|issue| {
+            let state = issue.state.as_ref().map(|s| s.name.as_str());
+            matches_name(state, args.status.as_deref())
+        })
+        .filter(|issue| {
+            let project = issue.project.as_ref().map(|p| p.name.as_str());
+            matches_name(project, args.project.as_deref())
+        })
+        .filter(|issue| {
+            let cycle = issue.cycle.as_ref().and_then(|c| c.name.as_deref());
+            matches_name(cycle, args.cycle.as_deref())
+        })
+        .filter(|issue| {
+            args.label.iter().all(|filter| {
+                let mut labels = issue.labels.iter().flat_map(|l| &l.nodes);
+                labels.any(|label| matches_name(Some(&label.name), Some(filter)))
+            })
+        })
+        .collect();
+
+    // The mirror returns the most recently updated first
+    if let Some(IssueSort::Created) = args.sort {
+        issues.sort_by(|a, b| b.created_at.cmp(&a.created_at));
     }
 
-    output::print_table(
-        &all_issues,
-        |issue| IssueRow::from(issue),
+    print_issues(&pagination::limit(issues, limit), &args.columns);
+
+    Ok(())
+}
+
+/// Like the API's `containsIgnoreCase`: a missing name never matches a filter
+fn matches_name(name: Option<&str>, filter: Option<&str>) -> bool {
+    match (name, filter) {
+        (_, None) => true,
+        (Some(name), Some(filter)) => name.to_lowercase().contains(&filter.to_lowercase()),
+        (None, Some(_)) => false,
+    }
+}
+
+pub(crate) fn print_issues(issues: &[Issue], columns: &[IssueColumn]) {
+    let columns = if columns.is_empty() {
+        DEFAULT_COLUMNS
+    } else {
+        columns
+    };
+    let headers: Vec<&str> = columns.iter().map(|column| header(*column)).collect();
+
+    output::print_columns(
+        issues,
+        &headers,
+        |issue| columns.iter().map(|column| cell(issue, *column)).collect(),
         |issue| {
             let status = issue.state.as_ref().map(|s| s.name.as_str()).unwrap_or("-");
             format!(
@@ -344,11 +626,78 @@ pub async fn list(client: &LinearClient, config: &Config, args: IssueListArgs) -
             )
         },
     );
+}
 
-    Ok(())
+fn header(column: IssueColumn) -> &'static str {
+    match column {
+        IssueColumn::Id => "ID",
+        IssueColumn::Title => "Title",
+        IssueColumn::Status => "Status",
+        IssueColumn::Priority => "Priority",
+        IssueColumn::Estimate => "Estimate",
+        IssueColumn::Assignee => "Assignee",
+        IssueColumn::Team => "Team",
+        IssueColumn::Project => "Project",
+        IssueColumn::Cycle => "Cycle",
+        IssueColumn::Labels => "Labels",
+        IssueColumn::Created => "Created",
+        IssueColumn::Updated => "Updated",
+    }
+}
+
+/// One table cell; tables are only printed in table format, so colors are safe
+fn cell(issue: &Issue, column: IssueColumn) -> String {
+    match column {
+        IssueColumn::Id => issue.identifier.clone(),
+        IssueColumn::Title => truncate(&issue.title, 50),
+        IssueColumn::Status => issue
+            .state
+            .as_ref()
+            .map(|s| status_colored(&s.name, Some(&s.color)))
+            .unwrap_or_default(),
+        IssueColumn::Priority => issue.priority.colored(),
+        IssueColumn::Estimate => issue.estimate.map(|e| e.to_string()).unwrap_or_default(),
+        IssueColumn::Assignee => issue
+            .assignee
+            .as_ref()
+            .map(|u| u.name.clone())
+            .unwrap_or_default(),
+        IssueColumn::Team => issue.team.key.clone(),
+        IssueColumn::Project => issue
+            .project
+            .as_ref()
+            .map(|p| p.name.clone())
+            .unwrap_or_default(),
+        IssueColumn::Cycle => issue
+            .cycle
+            .as_ref()
+            .map(|c| {
+                c.name
+                    .clone()
+                    .unwrap_or_else(|| format!("Cycle {}", c.number))
+            })
+            .unwrap_or_default(),
+        IssueColumn::Labels => issue
+            .labels
+            .iter()
+            .flat_map(|l| &l.nodes)
+            .map(|label| label.name.as_str())
+            .collect::<Vec<_>>()
+            .join(", "),
+        IssueColumn::Created => format_date(&issue.created_at),
+        IssueColumn::Updated => format_date(&issue.updated_at),
+    }
 }
 
 pub async fn view(client: &LinearClient, args: IssueViewArgs) -> Result<()> {
+    if args.offline {
+        let issue = Mirror::open_existing()?
+            .issue(&args.id)?
+            .ok_or_else(|| LinearError::IssueNotFound(args.id.clone()))?;
+        print_issue(&issue);
+        return Ok(());
+    }
+
     let variables = json!({ "id": args.id });
     let response: IssueResponse = client.query(GET_ISSUE_QUERY, Some(variables)).await?;
 
@@ -357,26 +706,35 @@ pub async fn view(client: &LinearClient, args: IssueViewArgs) -> Result<()> {
         .ok_or_else(|| LinearError::IssueNotFound(args.id.clone()))?;
 
     // Handle image fetching if requested
+    let mut image_results = Vec::new();
     if args.fetch_images {
         if let Some(output_dir) = &args.output {
             if let Some(ref description) = issue.description {
-                let results = download_images(
-                    client.api_key(),
+                let manifest = Manifest::load(output_dir)?;
+                image_results = download_images(
+                    client,
                     description,
                     &issue.identifier,
                     output_dir,
                     None,
+                    &manifest,
                 )
                 .await?;
 
-                print_download_results(&results);
+                print_download_results(&image_results);
             } else {
                 output::print_message("Issue has no description");
             }
         }
     }
 
-    output::print_item(&issue, |issue| {
+    print_issue(&issue);
+
+    ensure_all_succeeded(&image_results)
+}
+
+fn print_issue(issue: &Issue) {
+    output::print_item(issue, |issue| {
         use colored::Colorize;
 
         println!("{} - {}", issue.identifier.bold(), issue.title);
@@ -435,8 +793,6 @@ pub async fn view(client: &LinearClient, args: IssueViewArgs) -> Result<()> {
         println!("Created:  {}", format_date(&issue.created_at));
         println!("Updated:  {}", format_date(&issue.updated_at));
     });
-
-    Ok(())
 }
 
 /// Download everything from an issue (metadata, comments, images, attachments)
@@ -450,6 +806,9 @@ pub async fn download_all(client: &LinearClient, args: DownloadAllArgs) -> Resul
     std::fs::create_dir_all(&images_dir)?;
     std::fs::create_dir_all(&attachments_dir)?;
 
+    // Files already recorded here are skipped, so repeated runs are incremental
+    let manifest = Manifest::load(base_dir)?;
+
     // Fetch issue details
     let variables = json!({ "id": args.id });
     let response: IssueResponse = client.query(GET_ISSUE_QUERY, Some(variables)).await?;
@@ -466,8 +825,11 @@ pub async fn download_all(client: &LinearClient, args: DownloadAllArgs) -> Resul
     std::fs::write(&issue_file, &issue_json)?;
     output::print_message(&format!("Saved issue metadata to {}", issue_file.display()));
 
+    // Every step's outcome, so partial failures are reflected in the exit status
+    let mut results: Vec<DownloadResult> = Vec::new();
+
     // Download comments
-    let comments_result = comments::fetch_comments(client, &args.id).await;
+    let comments_result = comments::fetch_comments(client, &args.id, PageLimit::All).await;
     match comments_result {
         Ok(comments_list) => {
             if !comments_list.is_empty() {
@@ -483,6 +845,7 @@ pub async fn download_all(client: &LinearClient, args: DownloadAllArgs) -> Resul
         }
         Err(e) => {
             eprintln!("Failed to fetch comments: {}", e);
+            results.push(step_failed("comments", e));
         }
     }
 
@@ -490,112 +853,187 @@ pub async fn download_all(client: &LinearClient, args: DownloadAllArgs) -> Resul
     let description = issue.description.as_deref().unwrap_or("");
     if !description.is_empty() {
         let image_results = download_images(
-            client.api_key(),
+            client,
             description,
             &issue.identifier,
             &images_dir,
             None,
+            &manifest,
         )
         .await;
 
         match image_results {
-            Ok(results) => {
-                let success_count = results.iter().filter(|r| r.is_success()).count();
-                if success_count > 0 {
-                    output::print_message(&format!(
-                        "Downloaded {} images to {}",
-                        success_count,
-                        images_dir.display()
-                    ));
-                }
+            Ok(image_results) => {
+                report_failures("image", &image_results);
+                report_saved("images", &image_results, &images_dir);
+                results.extend(image_results);
             }
             Err(e) => {
                 eprintln!("Failed to download images: {}", e);
+                results.push(step_failed("images", e));
             }
         }
 
         // Download embedded links from description (e.g., [logs.zip](https://uploads.linear.app/...))
         let link_results = download_links(
-            client.api_key(),
+            client,
             description,
             &issue.identifier,
             &attachments_dir,
+            &manifest,
         )
         .await;
 
         match link_results {
-            Ok(results) => {
-                let success_count = results.iter().filter(|r| r.is_success()).count();
-                if success_count > 0 {
-                    output::print_message(&format!(
-                        "Downloaded {} embedded links to {}",
-                        success_count,
-                        attachments_dir.display()
-                    ));
-                }
+            Ok(link_results) => {
+                report_failures("embedded link", &link_results);
+                report_saved("embedded links", &link_results, &attachments_dir);
+                results.extend(link_results);
             }
             Err(e) => {
                 eprintln!("Failed to download embedded links: {}", e);
+                results.push(step_failed("embedded links", e));
             }
         }
     }
 
     // Download attachments
-    let attachments_result = attachments::download_to_dir(client, &args.id, &attachments_dir).await;
+    let attachments_result =
+        attachments::download_to_dir(client, &args.id, &attachments_dir, &manifest).await;
     match attachments_result {
-        Ok(count) => {
-            if count > 0 {
-                output::print_message(&format!(
-                    "Downloaded {} attachments to {}",
-                    count,
-                    attachments_dir.display()
-                ));
-            }
+        Ok(attachment_results) => {
+            report_failures("attachment", &attachment_results);
+            report_saved("attachments", &attachment_results, &attachments_dir);
+            results.extend(attachment_results);
         }
         Err(e) => {
             eprintln!("Failed to download attachments: {}", e);
+            results.push(step_failed("attachments", e));
         }
     }
 
     output::print_message(&format!("Download complete: {}", base_dir.display()));
 
+    ensure_all_succeeded(&results)
+}
+
+#[derive(Tabled)]
+struct VerifyRow {
+    #[tabled(rename = "File")]
+    path: String,
+    #[tabled(rename = "Status")]
+    status: String,
+}
+
+impl From<&VerifyResult> for VerifyRow {
+    fn from(result: &VerifyResult) -> Self {
+        Self {
+            path: result.path.clone(),
+            status: result.status.label().to_string(),
+        }
+    }
+}
+
+/// Check a download directory against its manifest (sizes and sha256) without
+/// touching the network
+pub fn verify_download(args: &DownloadAllArgs) -> Result<()> {
+    let manifest = Manifest::load(&args.output)?;
+    if manifest.is_empty() {
+        return Err(LinearError::FileNotFound(
+            manifest.path().display().to_string(),
+        ));
+    }
+
+    let results = manifest.verify()?;
+    output::print_table(
+        &results,
+        |result| VerifyRow::from(result),
+        |result| format!("{} | {}", result.path, result.status.label()),
+    );
+
+    let failed = results
+        .iter()
+        .filter(|r| r.status != VerifyStatus::Ok)
+        .count();
+    if failed > 0 {
+        return Err(LinearError::VerifyFailed {
+            failed,
+            total: results.len(),
+        });
+    }
+
     Ok(())
 }
 
+/// Record a whole download step that failed before producing per-file results
+fn step_failed(step: &str, error: LinearError) -> DownloadResult {
+    DownloadResult::Failed {
+        index: 0,
+        url: step.to_string(),
+        error: error.to_string(),
+    }
+}
+
+/// Summarize a step's downloads, counting files skipped as unchanged separately
+fn report_saved(what: &str, results: &[DownloadResult], dir: &Path) {
+    let downloaded = results
+        .iter()
+        .filter(|r| matches!(r, DownloadResult::Success { .. }))
+        .count();
+    let unchanged = results
+        .iter()
+        .filter(|r| matches!(r, DownloadResult::Skipped { .. }))
+        .count();
+
+    if downloaded > 0 {
+        output::print_message(&format!(
+            "Downloaded {} {} to {}",
+            downloaded,
+            what,
+            dir.display()
+        ));
+    }
+    if unchanged > 0 {
+        output::print_message(&format!("{} {} unchanged", unchanged, what));
+    }
+}
+
+fn report_failures(kind: &str, results: &[DownloadResult]) {
+    for result in results {
+        if let DownloadResult::Failed { index, url, error } = result {
+            eprintln!("Failed to download {} {} ({}): {}", kind, index, url, error);
+        }
+    }
+}
+
 pub async fn create(client: &LinearClient, config: &Config, args: IssueCreateArgs) -> Result<()> {
+    create_with(client, &mut Cache::load(), config, args).await
+}
+
+async fn create_with(
+    client: &LinearClient,
+    cache: &mut Cache,
+    config: &Config,
+    args: IssueCreateArgs,
+) -> Result<()> {
     let team_key = config
         .resolve_team(args.team.as_deref())
         .ok_or(LinearError::NoTeam)?;
 
-    // Try to get team ID from cache first
-    let mut cache = Cache::load();
-    let team_id = if let Some(cached_id) = cache.get_team_id(&team_key) {
-        cached_id
-    } else {
-        // Fetch from API and cache
-        let team_response: TeamsResponse = client
-            .query(GET_TEAM_BY_KEY_QUERY, Some(json!({ "key": team_key })))
-            .await?;
-
-        let team = team_response
-            .teams
-            .nodes
-            .first()
-            .ok_or_else(|| LinearError::TeamNotFound(team_key.clone()))?;
-
-        cache.set_team(CachedTeam {
-            id: team.id.clone(),
-            key: team_key.clone(),
-            name: "".to_string(), // We don't have the name in this response
-        });
-        cache.save();
-
-        team.id.clone()
+    // Defaults from `.linear.toml` or config.toml fill in what wasn't given
+    let args = IssueCreateArgs {
+        project: args.project.or_else(|| config.default_project.clone()),
+        label: if args.label.is_empty() {
+            config.default_labels.clone()
+        } else {
+            args.label
+        },
+        assignee: args.assignee.or_else(|| config.default_assignee.clone()),
+        ..args
     };
 
     let mut input = serde_json::Map::new();
     input.insert("title".to_string(), json!(args.title));
-    input.insert("teamId".to_string(), json!(team_id));
 
     if let Some(desc) = args.description {
         input.insert("description".to_string(), json!(desc));
@@ -607,31 +1045,98 @@ pub async fn create(client: &LinearClient, config: &Config, args: IssueCreateArg
         input.insert("estimate".to_string(), json!(estimate));
     }
 
-    // Handle labels
-    if !args.label.is_empty() {
-        let label_ids = labels::resolve_label_ids(client, &args.label).await?;
-        input.insert("labelIds".to_string(), json!(label_ids));
+    // Names are resolved from the cache; whatever is missing (team, cycle,
+    // labels, project, assignee) is fetched in a single request and cached
+    let assignee = args.assignee.as_deref().map(Assignee::parse);
+    let with_cycle = args
+        .cycle
+        .is_some_and(|n| cache.cycle_id(&team_key, n).is_none());
+    let with_team = with_cycle || cache.get_team_id(&team_key).is_none();
+    let missing_labels: Vec<String> = args
+        .label
+        .iter()
+        .filter(|name| cache.label_id(Some(&team_key), name).is_none())
+        .cloned()
+        .collect();
+    let missing_project = args
+        .project
+        .as_deref()
+        .filter(|name| cache.project_id(name).is_none());
+    let (with_viewer, user_query) = assignee.as_ref().map_or((false, None), |a| a.lookup(cache));
+
+    if with_team
+        || !missing_labels.is_empty()
+        || missing_project.is_some()
+        || with_viewer
+        || user_query.is_some()
+    {
+        let variables = json!({
+            "teamKey": team_key,
+            "withTeam": with_team,
+            "withCycle": with_cycle,
+            "cycleNumber": args.cycle,
+            "withLabels": !missing_labels.is_empty(),
+            "labelFilter": labels::names_filter(&missing_labels, Some(&team_key)),
+            "withProject": missing_project.is_some(),
+            "projectName": missing_project,
+            "withUser": user_query.is_some(),
+            "userFilter": user_filter(user_query),
+            "withViewer": with_viewer,
+        });
+        let resolved: ResolveCreateResponse =
+            client.query(RESOLVE_CREATE_QUERY, Some(variables)).await?;
+
+        for team in resolved.team.into_iter().flat_map(|c| c.nodes) {
+            team.store(cache);
+        }
+        for project in resolved.projects.into_iter().flat_map(|c| c.nodes) {
+            cache.set_project(project);
+        }
+        store_lookups(cache, resolved.labels, resolved.users, resolved.viewer);
+        cache.save();
     }
 
-    // Handle cycle
-    if let Some(cycle_number) = args.cycle {
-        let cycle_id = cycles::resolve_cycle_id(client, &team_key, cycle_number).await?;
+    let team_id = cache
+        .get_team_id(&team_key)
+        .ok_or_else(|| LinearError::TeamNotFound(team_key.clone()))?;
+    input.insert("teamId".to_string(), json!(team_id));
+
+    if let Some(number) = args.cycle {
+        let cycle_id = cache
+            .cycle_id(&team_key, number)
+            .ok_or_else(|| LinearError::CycleNotFound(number.to_string()))?;
         input.insert("cycleId".to_string(), json!(cycle_id));
     }
-
-    // Handle assignee
-    if let Some(assignee) = &args.assignee {
-        if assignee == "me" {
-            let viewer: ViewerResponse = client.query(GET_VIEWER_QUERY, None).await?;
-            input.insert("assigneeId".to_string(), json!(viewer.viewer.id));
-        } else {
-            input.insert("assigneeId".to_string(), json!(assignee));
-        }
+    if !args.label.is_empty() {
+        input.insert(
+            "labelIds".to_string(),
+            json!(label_ids(cache, Some(&team_key), &args.label)?),
+        );
+    }
+    if let Some(name) = &args.project {
+        let project_id = cache
+            .project_id(name)
+            .ok_or_else(|| LinearError::ProjectNotFound(name.clone()))?;
+        input.insert("projectId".to_string(), json!(project_id));
+    }
+    if let Some(assignee) = &assignee {
+        input.insert("assigneeId".to_string(), json!(assignee.id(cache)?));
     }
 
     let variables = json!({ "input": input });
-    let response: CreateIssueResponse =
-        client.query(CREATE_ISSUE_MUTATION, Some(variables)).await?;
+    let response: CreateIssueResponse = client
+        .query(CREATE_ISSUE_MUTATION, Some(variables))
+        .await
+        .inspect_err(|e| {
+            let used = [
+                Entity::Teams,
+                Entity::Cycles,
+                Entity::Labels,
+                Entity::Projects,
+                Entity::Users,
+            ];
+            cache.forget_if_rejected(e, &used)
+        })?;
 
     if response.issue_create.success {
         if let Some(issue) = response.issue_create.issue {
@@ -642,7 +1147,27 @@ pub async fn create(client: &LinearClient, config: &Config, args: IssueCreateArg
     Ok(())
 }
 
+/// Map label names to cached IDs for an issue in `team_key`
+fn label_ids(cache: &Cache, team_key: Option<&str>, names: &[String]) -> Result<Vec<String>> {
+    names
+        .iter()
+        .map(|name| {
+            cache
+                .label_id(team_key, name)
+                .ok_or_else(|| LinearError::LabelNotFound(name.clone()))
+        })
+        .collect()
+}
+
 pub async fn update(client: &LinearClient, args: IssueUpdateArgs) -> Result<()> {
+    update_with(client, &mut Cache::load(), args).await
+}
+
+async fn update_with(
+    client: &LinearClient,
+    cache: &mut Cache,
+    args: IssueUpdateArgs,
+) -> Result<()> {
     let mut input = serde_json::Map::new();
 
     if let Some(title) = args.title {
@@ -658,82 +1183,89 @@ pub async fn update(client: &LinearClient, args: IssueUpdateArgs) -> Result<()>
         input.insert("estimate".to_string(), json!(estimate));
     }
 
-    // Handle status change - need to resolve name to ID
-    if let Some(status_name) = &args.status {
-        // First get the issue to find its team
-        let issue_response: IssueResponse = client
-            .query(GET_ISSUE_QUERY, Some(json!({ "id": args.id })))
-            .await?;
-
-        let issue = issue_response
-            .issue
-            .ok_or_else(|| LinearError::IssueNotFound(args.id.clone()))?;
-
-        // Get workflow states for the team
-        let states_response: WorkflowStatesResponse = client
-            .query(GET_STATES_QUERY, Some(json!({ "teamId": issue.team.id })))
-            .await?;
-
-        let status_lower = status_name.to_lowercase();
-        let state_id = states_response
-            .workflow_states
-            .nodes
-            .iter()
-            .find(|s| s.name.to_lowercase() == status_lower)
-            .map(|s| s.id.clone());
+    // Status and cycle belong to the issue's team, which the identifier
+    // names; whatever isn't cached is fetched in a single request
+    let mut team_key = team_key_of(&args.id).map(str::to_string);
+    let with_states = args.status.as_deref().is_some_and(|status| {
+        team_key
+            .as_deref()
+            .is_none_or(|key| cache.state(key, status).is_none())
+    });
+    let with_cycle = args.cycle.is_some_and(|number| {
+        team_key
+            .as_deref()
+            .is_none_or(|key| cache.cycle_id(key, number).is_none())
+    });
+    let missing_labels: Vec<String> = args
+        .add_label
+        .iter()
+        .chain(&args.remove_label)
+        .filter(|name| cache.label_id(team_key.as_deref(), name).is_none())
+        .cloned()
+        .collect();
+    let assignee = args.assignee.as_deref().map(Assignee::parse);
+    let (with_viewer, user_query) = assignee.as_ref().map_or((false, None), |a| a.lookup(cache));
+    // Labels may belong to the issue's team, so a UUID's team is looked up too
+    let with_issue =
+        with_states || with_cycle || (team_key.is_none() && !missing_labels.is_empty());
+
+    if with_issue || !missing_labels.is_empty() || with_viewer || user_query.is_some() {
+        let variables = json!({
+            "id": args.id,
+            "withIssue": with_issue,
+            "withStates": with_states,
+            "withCycle": with_cycle,
+            "cycleNumber": args.cycle,
+            "withLabels": !missing_labels.is_empty(),
+            "labelFilter": labels::names_filter(&missing_labels, team_key.as_deref()),
+            "withUser": user_query.is_some(),
+            "userFilter": user_filter(user_query),
+            "withViewer": with_viewer,
+        });
+        let resolved: ResolveUpdateResponse =
+            client.query(RESOLVE_UPDATE_QUERY, Some(variables)).await?;
 
-        if let Some(id) = state_id {
-            input.insert("stateId".to_string(), json!(id));
+        if let Some(issue) = resolved.issue {
+            team_key = Some(issue.team.store(cache));
+        } else if with_issue {
+            return Err(LinearError::IssueNotFound(args.id.clone()));
         }
+        store_lookups(cache, resolved.labels, resolved.users, resolved.viewer);
+        cache.save();
     }
 
-    // Handle assignee
-    if let Some(assignee) = &args.assignee {
-        if assignee == "me" {
-            let viewer: ViewerResponse = client.query(GET_VIEWER_QUERY, None).await?;
-            input.insert("assigneeId".to_string(), json!(viewer.viewer.id));
-        } else {
-            // Treat as user ID directly
-            input.insert("assigneeId".to_string(), json!(assignee));
+    if let Some(status_name) = &args.status {
+        // An unknown status is left unchanged
+        if let Some(state) = team_key
+            .as_deref()
+            .and_then(|key| cache.state(key, status_name))
+        {
+            input.insert("stateId".to_string(), json!(state.id));
         }
     }
-
-    // Handle cycle - need team key to resolve cycle number
-    if let Some(cycle_number) = args.cycle {
-        let issue_response: IssueResponse = client
-            .query(GET_ISSUE_QUERY, Some(json!({ "id": args.id })))
-            .await?;
-
-        let issue = issue_response
-            .issue
-            .ok_or_else(|| LinearError::IssueNotFound(args.id.clone()))?;
-
-        let cycle_id =
-            cycles::resolve_cycle_id(client, &issue.team.key, cycle_number).await?;
+    if let Some(number) = args.cycle {
+        let cycle_id = team_key
+            .as_deref()
+            .and_then(|key| cache.cycle_id(key, number))
+            .ok_or_else(|| LinearError::CycleNotFound(number.to_string()))?;
         input.insert("cycleId".to_string(), json!(cycle_id));
     }
 
-    // Handle label changes
-    if !args.add_label.is_empty() || !args.remove_label.is_empty() {
-        let mut current_ids = labels::get_issue_label_ids(client, &args.id).await?;
-
-        // Add new labels
-        if !args.add_label.is_empty() {
-            let add_ids = labels::resolve_label_ids(client, &args.add_label).await?;
-            for id in add_ids {
-                if !current_ids.contains(&id) {
-                    current_ids.push(id);
-                }
-            }
-        }
-
-        // Remove labels
-        if !args.remove_label.is_empty() {
-            let remove_ids = labels::resolve_label_ids(client, &args.remove_label).await?;
-            current_ids.retain(|id| !remove_ids.contains(id));
-        }
-
-        input.insert("labelIds".to_string(), json!(current_ids));
+    // Added/removed IDs spare fetching the issue's current labels
+    if !args.add_label.is_empty() {
+        input.insert(
+            "addedLabelIds".to_string(),
+            json!(label_ids(cache, team_key.as_deref(), &args.add_label)?),
+        );
+    }
+    if !args.remove_label.is_empty() {
+        input.insert(
+            "removedLabelIds".to_string(),
+            json!(label_ids(cache, team_key.as_deref(), &args.remove_label)?),
+        );
+    }
+    if let Some(assignee) = &assignee {
+        input.insert("assigneeId".to_string(), json!(assignee.id(cache)?));
     }
 
     if input.is_empty() {
@@ -746,8 +1278,18 @@ pub async fn update(client: &LinearClient, args: IssueUpdateArgs) -> Result<()>
         "input": input
     });
 
-    let response: UpdateIssueResponse =
-        client.query(UPDATE_ISSUE_MUTATION, Some(variables)).await?;
+    let response: UpdateIssueResponse = client
+        .query(UPDATE_ISSUE_MUTATION, Some(variables))
+        .await
+        .inspect_err(|e| {
+            let used = [
+                Entity::States,
+                Entity::Cycles,
+                Entity::Labels,
+                Entity::Users,
+            ];
+            cache.forget_if_rejected(e, &used)
+        })?;
 
     if response.issue_update.success {
         if let Some(issue) = response.issue_update.issue {
@@ -760,26 +1302,40 @@ pub async fn update(client: &LinearClient, args: IssueUpdateArgs) -> Result<()>
 
 /// Close an issue by setting its status to a "done" state
 pub async fn close(client: &LinearClient, id: &str) -> Result<()> {
-    // First get the issue to find its team
-    let issue_response: IssueResponse = client
-        .query(GET_ISSUE_QUERY, Some(json!({ "id": id })))
-        .await?;
+    let mut cache = Cache::load();
 
-    let issue = issue_response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(id.to_string()))?;
+    // Find a completed state using the state type, fetching the issue's team
+    // states unless they are cached
+    let mut team_key = team_key_of(id).map(str::to_string);
+    let cached = team_key
+        .as_deref()
+        .is_some_and(|key| cache.state_of_type(key, "completed").is_some());
+
+    if !cached {
+        let variables = json!({
+            "id": id,
+            "withIssue": true,
+            "withStates": true,
+            "withCycle": false,
+            "withLabels": false,
+            "withUser": false,
+            "withViewer": false,
+        });
+        let resolved: ResolveUpdateResponse =
+            client.query(RESOLVE_UPDATE_QUERY, Some(variables)).await?;
 
-    // Get workflow states for the team
-    let states_response: WorkflowStatesResponse = client
-        .query(GET_STATES_QUERY, Some(json!({ "teamId": issue.team.id })))
-        .await?;
+        let team = resolved
+            .issue
+            .map(|issue| issue.team)
+            .ok_or_else(|| LinearError::IssueNotFound(id.to_string()))?;
+        team_key = Some(team.store(&mut cache));
+        cache.save();
+    }
 
-    // Find a completed state using the state type
-    let done_state = states_response
-        .workflow_states
-        .nodes
-        .iter()
-        .find(|s| s.state_type == "completed")
+    let done_state_id = team_key
+        .as_deref()
+        .and_then(|key| cache.state_of_type(key, "completed"))
+        .map(|state| state.id.clone())
         .ok_or_else(|| {
             LinearError::WorkflowStateNotFound("No completed state found for team".to_string())
         })?;
@@ -787,12 +1343,14 @@ pub async fn close(client: &LinearClient, id: &str) -> Result<()> {
     let variables = json!({
         "id": id,
         "input": {
-            "stateId": done_state.id
+            "stateId": done_state_id
         }
     });
 
-    let response: UpdateIssueResponse =
-        client.query(UPDATE_ISSUE_MUTATION, Some(variables)).await?;
+    let response: UpdateIssueResponse = client
+        .query(UPDATE_ISSUE_MUTATION, Some(variables))
+        .await
+        .inspect_err(|e| cache.forget_if_rejected(e, &[Entity::States]))?;
 
     if response.issue_update.success {
         if let Some(updated_issue) = response.issue_update.issue {
@@ -805,3 +1363,123 @@ pub async fn close(client: &LinearClient, id: &str) -> Result<()> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Credentials;
+    use crate::cassette::{Cassette, CassetteMode};
+    use crate::schema;
+
+    fn update_mutation() -> serde_json::Value {
+        json!({
+            "request": {
+                "query": UPDATE_ISSUE_MUTATION,
+                "variables": {
+                    "id": "ENG-1",
+                    "input": {
+                        "stateId": "s1",
+                        "cycleId": "c4",
+                        "addedLabelIds": ["l1"],
+                        "assigneeId": "u1"
+                    }
+                }
+            },
+            "response": { "status": 200, "body": { "data": { "issueUpdate": {
+                "success": true,
+                "issue": { "id": "i1", "identifier": "ENG-1", "title": "Bug" }
+            } } } }
+        })
+    }
+
+    fn update_args() -> IssueUpdateArgs {
+        IssueUpdateArgs {
+            id: "ENG-1".to_string(),
+            title: None,
+            description: None,
+            status: Some("done".to_string()),
+            priority: None,
+            assignee: Some("me".to_string()),
+            estimate: None,
+            add_label: vec!["bug".to_string()],
+            remove_label: Vec::new(),
+            cycle: Some(4),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_update_resolves_once_then_uses_cache() {
+        let path =
+            std::env::temp_dir().join(format!("linear-cli-{}-update.json", std::process::id()));
+        let resolve = json!({
+            "request": {
+                "query": RESOLVE_UPDATE_QUERY,
+                "variables": {
+                    "id": "ENG-1",
+                    "withIssue": true,
+                    "withStates": true,
+                    "withCycle": true,
+                    "cycleNumber": 4,
+                    "withLabels": true,
+                    "labelFilter": { "and": [
+                        { "or": [{ "name": { "eqIgnoreCase": "bug" } }] },
+                        { "or": [
+                            { "team": { "key": { "eqIgnoreCase": "ENG" } } },
+                            { "team": { "null": true } }
+                        ] }
+                    ] },
+                    "withUser": false,
+                    "userFilter": null,
+                    "withViewer": true
+                }
+            },
+            "response": { "status": 200, "body": { "data": {
+                "issue": { "team": {
+                    "id": "t1", "key": "ENG", "name": "Engineering",
+                    "states": { "nodes": [{ "id": "s1", "name": "Done", "type": "completed" }] },
+                    "cycles": { "nodes": [{ "id": "c4", "number": 4, "name": null }] }
+                } },
+                "labels": { "nodes": [{ "id": "l1", "name": "Bug", "team": { "key": "ENG" } }] },
+                "viewer": {
+                    "id": "u1", "name": "Ada", "displayName": "ada",
+                    "email": "ada@example.com", "isMe": true
+                }
+            } } }
+        });
+        let interactions = json!([resolve, update_mutation(), update_mutation()]);
+        std::fs::write(&path, json!({ "interactions": interactions }).to_string()).unwrap();
+
+        let client = LinearClient::new(Credentials::ApiKey(String::new()))
+            .with_cassette(Cassette::open(&path, CassetteMode::Replay).unwrap());
+        let mut cache = Cache::default();
+
+        // Any request beyond the recorded ones fails with CassetteMismatch:
+        // the first update needs one lookup, the second none at all
+        update_with(&client, &mut cache, update_args())
+            .await
+            .unwrap();
+        update_with(&client, &mut cache, update_args())
+            .await
+            .unwrap();
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_team_key_of() {
+        assert_eq!(team_key_of("ENG-123"), Some("ENG"));
+        assert_eq!(team_key_of("eng-7"), Some("eng"));
+        assert_eq!(team_key_of("2b5e4c8a-1f0e-4d3a-9c6b-7e8f9a0b1c2d"), None);
+    }
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<IssuesResponse>(LIST_ISSUES_QUERY);
+        schema::assert_response::<IssueResponse>(GET_ISSUE_QUERY);
+        schema::assert_response::<CreateIssueResponse>(CREATE_ISSUE_MUTATION);
+        schema::assert_response::<UpdateIssueResponse>(UPDATE_ISSUE_MUTATION);
+        schema::assert_response::<ViewerResponse>(GET_VIEWER_QUERY);
+        schema::assert_response::<ResolveCreateResponse>(RESOLVE_CREATE_QUERY);
+        schema::assert_response::<ResolveUpdateResponse>(RESOLVE_UPDATE_QUERY);
+    }
+}
@@ -1,11 +1,15 @@
+use std::cell::RefCell;
+
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use tabled::Tabled;
 
+use crate::cache::{Cache, CachedLabel, Entity, LabelTeam};
 use crate::client::LinearClient;
 use crate::config::Config;
 use crate::error::{LinearError, Result};
 use crate::output::{self, is_json_output, status_colored, truncate};
+use crate::pagination::{self, PageLimit};
 use crate::responses::Connection;
 
 #[derive(Tabled)]
@@ -33,14 +37,18 @@ impl LabelRow {
 }
 
 const LIST_LABELS_QUERY: &str = r#"
-query ListLabels($filter: IssueLabelFilter) {
-    issueLabels(filter: $filter) {
+query ListLabels($filter: IssueLabelFilter, $first: Int, $after: String) {
+    issueLabels(filter: $filter, first: $first, after: $after) {
         nodes {
             id
             name
             color
             description
         }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
     }
 }
 "#;
@@ -59,7 +67,12 @@ pub struct Label {
     pub description: Option<String>,
 }
 
-pub async fn list(client: &LinearClient, config: &Config, team: Option<String>) -> Result<()> {
+pub async fn list(
+    client: &LinearClient,
+    config: &Config,
+    team: Option<String>,
+    limit: PageLimit,
+) -> Result<()> {
     let team_key = config.resolve_team(team.as_deref());
 
     let variables = team_key.map(|key| {
@@ -72,8 +85,7 @@ pub async fn list(client: &LinearClient, config: &Config, team: Option<String>)
         })
     });
 
-    let response: LabelsResponse = client.query(LIST_LABELS_QUERY, variables).await?;
-    let labels = response.issue_labels.nodes;
+    let labels = fetch_labels(client, variables, limit).await?;
 
     if labels.is_empty() {
         output::print_message("No labels found");
@@ -86,17 +98,24 @@ pub async fn list(client: &LinearClient, config: &Config, team: Option<String>)
 }
 
 const GET_ISSUE_LABELS_QUERY: &str = r#"
-query GetIssue($id: String!) {
+query GetIssueLabels($id: String!, $first: Int, $after: String) {
     issue(id: $id) {
         id
         identifier
-        labels {
+        team {
+            key
+        }
+        labels(first: $first, after: $after) {
             nodes {
                 id
                 name
                 color
                 description
             }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
         }
     }
 }
@@ -109,12 +128,35 @@ struct IssueLabelResponse {
 
 #[derive(Deserialize)]
 struct IssueLabelData {
-    #[allow(dead_code)]
-    id: String,
-    identifier: String,
+    team: LabelTeam,
     labels: Connection<Label>,
 }
 
+// Labels by name with the team they belong to, for the cache
+const FIND_LABELS_QUERY: &str = r#"
+query FindLabels($filter: IssueLabelFilter, $first: Int, $after: String) {
+    issueLabels(filter: $filter, first: $first, after: $after) {
+        nodes {
+            id
+            name
+            team {
+                key
+            }
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+#[derive(Deserialize)]
+struct FoundLabelsResponse {
+    #[serde(rename = "issueLabels")]
+    issue_labels: Connection<CachedLabel>,
+}
+
 const UPDATE_ISSUE_MUTATION: &str = r#"
 mutation UpdateIssue($id: String!, $input: IssueUpdateInput!) {
     issueUpdate(id: $id, input: $input) {
@@ -139,19 +181,53 @@ struct IssueUpdateResult {
     success: bool,
 }
 
-/// List labels on an issue
-pub async fn list_for_issue(client: &LinearClient, id: &str) -> Result<()> {
-    let variables = json!({ "id": id });
-    let response: IssueLabelResponse = client.query(GET_ISSUE_LABELS_QUERY, Some(variables)).await?;
+/// Fetch workspace labels matching an optional `filter` variable
+async fn fetch_labels(
+    client: &LinearClient,
+    variables: Option<serde_json::Value>,
+    limit: PageLimit,
+) -> Result<Vec<Label>> {
+    pagination::fetch(
+        client,
+        LIST_LABELS_QUERY,
+        variables,
+        limit,
+        |response: LabelsResponse| Ok(response.issue_labels),
+    )
+    .await
+}
 
-    let issue = response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(id.to_string()))?;
+/// Fetch the labels on an issue, and the key of the issue's team
+async fn fetch_issue_labels(
+    client: &LinearClient,
+    id: &str,
+    limit: PageLimit,
+) -> Result<(String, Vec<Label>)> {
+    let team_key = RefCell::new(String::new());
+    let labels = pagination::fetch(
+        client,
+        GET_ISSUE_LABELS_QUERY,
+        Some(json!({ "id": id })),
+        limit,
+        |response: IssueLabelResponse| {
+            let issue = response
+                .issue
+                .ok_or_else(|| LinearError::IssueNotFound(id.to_string()))?;
+            team_key.replace(issue.team.key);
+            Ok(issue.labels)
+        },
+    )
+    .await?;
+
+    Ok((team_key.into_inner(), labels))
+}
 
-    let labels = issue.labels.nodes;
+/// List labels on an issue
+pub async fn list_for_issue(client: &LinearClient, id: &str, limit: PageLimit) -> Result<()> {
+    let (_, labels) = fetch_issue_labels(client, id, limit).await?;
 
     if labels.is_empty() {
-        output::print_message(&format!("No labels on {}", issue.identifier));
+        output::print_message(&format!("No labels on {id}"));
         return Ok(());
     }
 
@@ -160,59 +236,71 @@ pub async fn list_for_issue(client: &LinearClient, id: &str) -> Result<()> {
     Ok(())
 }
 
-/// Resolve a label name to its ID (case-insensitive)
-pub async fn resolve_label_id(client: &LinearClient, name: &str) -> Result<String> {
-    let response: LabelsResponse = client.query(LIST_LABELS_QUERY, None).await?;
-    let name_lower = name.to_lowercase();
-
-    response
-        .issue_labels
-        .nodes
-        .iter()
-        .find(|l| l.name.to_lowercase() == name_lower)
-        .map(|l| l.id.clone())
-        .ok_or_else(|| LinearError::LabelNotFound(name.to_string()))
-}
-
-/// Resolve multiple label names to IDs
-pub async fn resolve_label_ids(client: &LinearClient, names: &[String]) -> Result<Vec<String>> {
-    if names.is_empty() {
-        return Ok(Vec::new());
+/// Resolve a label name to its ID (case-insensitive) for an issue in
+/// `team_key`, from the cache if possible
+pub async fn resolve_label_id(
+    client: &LinearClient,
+    cache: &mut Cache,
+    team_key: &str,
+    name: &str,
+) -> Result<String> {
+    if let Some(id) = cache.label_id(Some(team_key), name) {
+        return Ok(id);
     }
 
-    let response: LabelsResponse = client.query(LIST_LABELS_QUERY, None).await?;
-    let mut ids = Vec::with_capacity(names.len());
-
-    for name in names {
-        let name_lower = name.to_lowercase();
-        let label = response
-            .issue_labels
-            .nodes
-            .iter()
-            .find(|l| l.name.to_lowercase() == name_lower)
-            .ok_or_else(|| LinearError::LabelNotFound(name.to_string()))?;
-        ids.push(label.id.clone());
+    let variables = json!({ "filter": names_filter(&[name.to_string()], Some(team_key)) });
+    let labels = pagination::fetch(
+        client,
+        FIND_LABELS_QUERY,
+        Some(variables),
+        PageLimit::All,
+        |response: FoundLabelsResponse| Ok(response.issue_labels),
+    )
+    .await?;
+    for label in labels {
+        cache.set_label(label);
     }
+    cache.save();
 
-    Ok(ids)
+    cache
+        .label_id(Some(team_key), name)
+        .ok_or_else(|| LinearError::LabelNotFound(name.to_string()))
 }
 
-/// Get current label IDs for an issue
-pub async fn get_issue_label_ids(client: &LinearClient, id: &str) -> Result<Vec<String>> {
-    let variables = json!({ "id": id });
-    let response: IssueLabelResponse = client.query(GET_ISSUE_LABELS_QUERY, Some(variables)).await?;
+/// Label filter matching any of `names`, ignoring case. With a team key only
+/// that team's labels and workspace labels match.
+pub fn names_filter(names: &[String], team_key: Option<&str>) -> serde_json::Value {
+    let names: Vec<_> = names
+        .iter()
+        .map(|name| json!({ "name": { "eqIgnoreCase": name } }))
+        .collect();
+
+    match team_key {
+        Some(key) => json!({
+            "and": [
+                { "or": names },
+                { "or": [
+                    { "team": { "key": { "eqIgnoreCase": key } } },
+                    { "team": { "null": true } }
+                ] }
+            ]
+        }),
+        None => json!({ "or": names }),
+    }
+}
 
-    let issue = response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(id.to_string()))?;
+/// Get current label IDs for an issue, and the key of the issue's team
+async fn get_issue_label_ids(client: &LinearClient, id: &str) -> Result<(String, Vec<String>)> {
+    let (team_key, labels) = fetch_issue_labels(client, id, PageLimit::All).await?;
 
-    Ok(issue.labels.nodes.iter().map(|l| l.id.clone()).collect())
+    Ok((team_key, labels.into_iter().map(|l| l.id).collect()))
 }
 
 /// Add a label to an issue
 pub async fn add_label(client: &LinearClient, id: &str, label_name: &str) -> Result<()> {
-    let label_id = resolve_label_id(client, label_name).await?;
-    let mut current_ids = get_issue_label_ids(client, id).await?;
+    let mut cache = Cache::load();
+    let (team_key, mut current_ids) = get_issue_label_ids(client, id).await?;
+    let label_id = resolve_label_id(client, &mut cache, &team_key, label_name).await?;
 
     if current_ids.contains(&label_id) {
         output::print_message(&format!("Issue already has label '{}'", label_name));
@@ -228,7 +316,10 @@ pub async fn add_label(client: &LinearClient, id: &str, label_name: &str) -> Res
         }
     });
 
-    let response: UpdateIssueResponse = client.query(UPDATE_ISSUE_MUTATION, Some(variables)).await?;
+    let response: UpdateIssueResponse = client
+        .query(UPDATE_ISSUE_MUTATION, Some(variables))
+        .await
+        .inspect_err(|e| cache.forget_if_rejected(e, &[Entity::Labels]))?;
 
     if response.issue_update.success {
         output::print_message(&format!("Added label '{}' to issue", label_name));
@@ -239,8 +330,9 @@ pub async fn add_label(client: &LinearClient, id: &str, label_name: &str) -> Res
 
 /// Remove a label from an issue
 pub async fn remove_label(client: &LinearClient, id: &str, label_name: &str) -> Result<()> {
-    let label_id = resolve_label_id(client, label_name).await?;
-    let current_ids = get_issue_label_ids(client, id).await?;
+    let mut cache = Cache::load();
+    let (team_key, current_ids) = get_issue_label_ids(client, id).await?;
+    let label_id = resolve_label_id(client, &mut cache, &team_key, label_name).await?;
 
     if !current_ids.contains(&label_id) {
         output::print_message(&format!("Issue does not have label '{}'", label_name));
@@ -256,7 +348,10 @@ pub async fn remove_label(client: &LinearClient, id: &str, label_name: &str) ->
         }
     });
 
-    let response: UpdateIssueResponse = client.query(UPDATE_ISSUE_MUTATION, Some(variables)).await?;
+    let response: UpdateIssueResponse = client
+        .query(UPDATE_ISSUE_MUTATION, Some(variables))
+        .await
+        .inspect_err(|e| cache.forget_if_rejected(e, &[Entity::Labels]))?;
 
     if response.issue_update.success {
         output::print_message(&format!("Removed label '{}' from issue", label_name));
@@ -264,3 +359,17 @@ pub async fn remove_label(client: &LinearClient, id: &str, label_name: &str) ->
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<LabelsResponse>(LIST_LABELS_QUERY);
+        schema::assert_response::<IssueLabelResponse>(GET_ISSUE_LABELS_QUERY);
+        schema::assert_response::<FoundLabelsResponse>(FIND_LABELS_QUERY);
+        schema::assert_response::<UpdateIssueResponse>(UPDATE_ISSUE_MUTATION);
+    }
+}
@@ -0,0 +1,65 @@
+use crate::client::LinearClient;
+use crate::error::Result;
+use crate::output::{self, format_date};
+
+const VIEWER_QUERY: &str = r#"
+query Viewer {
+    viewer {
+        id
+    }
+}
+"#;
+
+/// Show the rate-limit budget reported by Linear (costs one cheap request)
+pub async fn show(client: &LinearClient) -> Result<()> {
+    let _: serde_json::Value = client.query(VIEWER_QUERY, None).await?;
+
+    let Some(limits) = client.rate_limit() else {
+        output::print_message("No rate-limit headers were returned by the API");
+        return Ok(());
+    };
+
+    output::print_item(&limits, |limits| {
+        println!(
+            "Requests:   {} of {} remaining (resets {})",
+            display(limits.requests_remaining),
+            display(limits.requests_limit),
+            display_reset(limits.requests_reset)
+        );
+        println!(
+            "Complexity: {} of {} remaining (resets {})",
+            display(limits.complexity_remaining),
+            display(limits.complexity_limit),
+            display_reset(limits.complexity_reset)
+        );
+        if let Some(complexity) = limits.complexity {
+            println!("Last query complexity: {complexity}");
+        }
+    });
+
+    Ok(())
+}
+
+fn display(value: Option<u64>) -> String {
+    value
+        .map(|v| v.to_string())
+        .unwrap_or_else(|| "-".to_string())
+}
+
+fn display_reset(epoch_ms: Option<u64>) -> String {
+    epoch_ms
+        .and_then(|ms| chrono::DateTime::from_timestamp_millis(ms as i64))
+        .map(|dt| format_date(&dt.to_rfc3339()))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<serde_json::Value>(VIEWER_QUERY);
+    }
+}
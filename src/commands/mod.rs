@@ -1,10 +1,18 @@
+pub mod api;
 pub mod attachments;
+pub mod auth;
+pub mod cache;
 pub mod comments;
+pub mod config;
 pub mod cycles;
 pub mod images;
 pub mod init;
 pub mod issues;
 pub mod labels;
+pub mod limits;
 pub mod projects;
 pub mod relations;
+pub mod search;
+pub mod sync;
 pub mod teams;
+pub mod views;
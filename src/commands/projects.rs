@@ -6,6 +6,7 @@ use crate::client::LinearClient;
 use crate::config::Config;
 use crate::error::Result;
 use crate::output;
+use crate::pagination::{self, PageLimit};
 use crate::responses::Connection;
 use crate::types::Project;
 
@@ -30,13 +31,17 @@ impl From<&Project> for ProjectRow {
 }
 
 const LIST_PROJECTS_QUERY: &str = r#"
-query ListProjects($filter: ProjectFilter) {
-    projects(filter: $filter) {
+query ListProjects($filter: ProjectFilter, $first: Int, $after: String) {
+    projects(filter: $filter, first: $first, after: $after) {
         nodes {
             id
             name
             state
         }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
     }
 }
 "#;
@@ -46,23 +51,35 @@ struct ProjectsResponse {
     projects: Connection<Project>,
 }
 
-pub async fn list(client: &LinearClient, config: &Config, team: Option<String>) -> Result<()> {
+pub async fn list(
+    client: &LinearClient,
+    config: &Config,
+    team: Option<String>,
+    limit: PageLimit,
+) -> Result<()> {
     let team_key = config.resolve_team(team.as_deref());
 
     let variables = team_key.map(|key| {
         json!({
             "filter": {
                 "accessibleTeams": {
-                    "key": { "eq": key }
+                    "some": { "key": { "eq": key } }
                 }
             }
         })
     });
 
-    let response: ProjectsResponse = client.query(LIST_PROJECTS_QUERY, variables).await?;
+    let projects = pagination::fetch(
+        client,
+        LIST_PROJECTS_QUERY,
+        variables,
+        limit,
+        |response: ProjectsResponse| Ok(response.projects),
+    )
+    .await?;
 
     output::print_table(
-        &response.projects.nodes,
+        &projects,
         |project| ProjectRow::from(project),
         |project| {
             format!(
@@ -75,3 +92,14 @@ pub async fn list(client: &LinearClient, config: &Config, team: Option<String>)
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<ProjectsResponse>(LIST_PROJECTS_QUERY);
+    }
+}
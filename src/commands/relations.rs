@@ -6,15 +6,32 @@ use crate::cli::RelateArgs;
 use crate::client::LinearClient;
 use crate::error::{LinearError, Result};
 use crate::output::{self, truncate};
+use crate::pagination::{self, PageLimit};
 use crate::responses::Connection;
 use crate::types::{IssueRelation, RelatedIssueRef};
 
-const GET_ISSUE_RELATIONS_QUERY: &str = r#"
-query GetIssueRelations($id: String!) {
+const LIST_ISSUE_RELATIONS_QUERY: &str = r#"
+query ListIssueRelations($id: String!, $first: Int) {
     issue(id: $id) {
         id
         identifier
-        relations {
+        parent {
+            id
+            identifier
+            title
+        }
+        children(first: $first) {
+            nodes {
+                id
+                identifier
+                title
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
+        }
+        relations(first: $first) {
             nodes {
                 id
                 type
@@ -28,19 +45,58 @@ query GetIssueRelations($id: String!) {
                     identifier
                     title
                 }
+                createdAt
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
             }
         }
-        parent {
-            id
-            identifier
-            title
+    }
+}
+"#;
+
+const GET_ISSUE_RELATIONS_QUERY: &str = r#"
+query GetIssueRelations($id: String!, $first: Int, $after: String) {
+    issue(id: $id) {
+        relations(first: $first, after: $after) {
+            nodes {
+                id
+                type
+                issue {
+                    id
+                    identifier
+                    title
+                }
+                relatedIssue {
+                    id
+                    identifier
+                    title
+                }
+                createdAt
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
         }
-        children {
+    }
+}
+"#;
+
+const GET_ISSUE_CHILDREN_QUERY: &str = r#"
+query GetIssueChildren($id: String!, $first: Int, $after: String) {
+    issue(id: $id) {
+        children(first: $first, after: $after) {
             nodes {
                 id
                 identifier
                 title
             }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
         }
     }
 }
@@ -84,6 +140,19 @@ query GetIssueId($id: String!) {
 }
 "#;
 
+#[derive(Deserialize)]
+struct IssueRelationListResponse {
+    issue: Option<IssueRelationList>,
+}
+
+#[derive(Deserialize)]
+struct IssueRelationList {
+    identifier: String,
+    parent: Option<RelatedIssueRef>,
+    children: Connection<RelatedIssueRef>,
+    relations: Connection<IssueRelation>,
+}
+
 #[derive(Deserialize)]
 struct IssueRelationsResponse {
     issue: Option<IssueWithRelations>,
@@ -91,9 +160,16 @@ struct IssueRelationsResponse {
 
 #[derive(Deserialize)]
 struct IssueWithRelations {
-    identifier: String,
     relations: Connection<IssueRelation>,
-    parent: Option<RelatedIssueRef>,
+}
+
+#[derive(Deserialize)]
+struct IssueChildrenResponse {
+    issue: Option<IssueWithChildren>,
+}
+
+#[derive(Deserialize)]
+struct IssueWithChildren {
     children: Connection<RelatedIssueRef>,
 }
 
@@ -140,17 +216,72 @@ struct RelationRow {
     title: String,
 }
 
-/// List all relations for an issue.
-pub async fn list(client: &LinearClient, issue_id: &str) -> Result<()> {
-    let variables = json!({ "id": issue_id });
-    let response: IssueRelationsResponse = client
-        .query(GET_ISSUE_RELATIONS_QUERY, Some(variables))
+/// Fetch all relations where the issue is the source or target.
+async fn fetch_relations(client: &LinearClient, issue_id: &str) -> Result<Vec<IssueRelation>> {
+    pagination::fetch(
+        client,
+        GET_ISSUE_RELATIONS_QUERY,
+        Some(json!({ "id": issue_id })),
+        PageLimit::All,
+        |response: IssueRelationsResponse| {
+            response
+                .issue
+                .map(|issue| issue.relations)
+                .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))
+        },
+    )
+    .await
+}
+
+/// List the relations for an issue: its parent, then children, then
+/// relations, up to `limit` rows in all.
+pub async fn list(client: &LinearClient, issue_id: &str, limit: PageLimit) -> Result<()> {
+    let variables = json!({ "id": issue_id, "first": limit.page_size() });
+    let response: IssueRelationListResponse = client
+        .query(LIST_ISSUE_RELATIONS_QUERY, Some(variables))
         .await?;
 
     let issue = response
         .issue
         .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))?;
 
+    // --limit is at least 1, so the parent always fits
+    let parent_count = usize::from(issue.parent.is_some());
+    let (children, children_truncated) = pagination::fetch_after(
+        client,
+        GET_ISSUE_CHILDREN_QUERY,
+        Some(json!({ "id": issue_id })),
+        limit.remaining(parent_count),
+        issue.children,
+        |response: IssueChildrenResponse| {
+            response
+                .issue
+                .map(|issue| issue.children)
+                .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))
+        },
+    )
+    .await?;
+    let (relations, relations_truncated) = pagination::fetch_after(
+        client,
+        GET_ISSUE_RELATIONS_QUERY,
+        Some(json!({ "id": issue_id })),
+        limit.remaining(parent_count + children.len()),
+        issue.relations,
+        |response: IssueRelationsResponse| {
+            response
+                .issue
+                .map(|issue| issue.relations)
+                .ok_or_else(|| LinearError::IssueNotFound(issue_id.to_string()))
+        },
+    )
+    .await?;
+
+    if let PageLimit::First(n) = limit {
+        if children_truncated || relations_truncated {
+            pagination::warn_truncated(n);
+        }
+    }
+
     let mut rows: Vec<RelationRow> = Vec::new();
 
     // Add parent if exists
@@ -163,7 +294,7 @@ pub async fn list(client: &LinearClient, issue_id: &str) -> Result<()> {
     }
 
     // Add children
-    for child in &issue.children.nodes {
+    for child in &children {
         rows.push(RelationRow {
             relation_type: "child".to_string(),
             issue: child.identifier.clone(),
@@ -172,7 +303,7 @@ pub async fn list(client: &LinearClient, issue_id: &str) -> Result<()> {
     }
 
     // Add relations (normalizing direction)
-    for rel in &issue.relations.nodes {
+    for rel in &relations {
         let (rel_type, other) = if rel.issue.identifier == issue.identifier {
             // This issue is the source
             (rel.relation_type.to_string(), &rel.related_issue)
@@ -233,19 +364,10 @@ pub async fn relate(client: &LinearClient, args: RelateArgs) -> Result<()> {
 /// Remove a relation between two issues.
 pub async fn unrelate(client: &LinearClient, source: &str, target: &str) -> Result<()> {
     // Find the relation between these two issues
-    let variables = json!({ "id": source });
-    let response: IssueRelationsResponse = client
-        .query(GET_ISSUE_RELATIONS_QUERY, Some(variables))
-        .await?;
-
-    let issue = response
-        .issue
-        .ok_or_else(|| LinearError::IssueNotFound(source.to_string()))?;
+    let relations = fetch_relations(client, source).await?;
 
     // Find matching relation
-    let relation = issue
-        .relations
-        .nodes
+    let relation = relations
         .iter()
         .find(|r| r.related_issue.identifier == target || r.issue.identifier == target);
 
@@ -325,3 +447,20 @@ async fn resolve_issue_id(client: &LinearClient, identifier: &str) -> Result<Str
         .map(|i| i.id)
         .ok_or_else(|| LinearError::IssueNotFound(identifier.to_string()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<IssueRelationListResponse>(LIST_ISSUE_RELATIONS_QUERY);
+        schema::assert_response::<IssueRelationsResponse>(GET_ISSUE_RELATIONS_QUERY);
+        schema::assert_response::<IssueChildrenResponse>(GET_ISSUE_CHILDREN_QUERY);
+        schema::assert_response::<CreateRelationResponse>(CREATE_RELATION_MUTATION);
+        schema::assert_response::<DeleteRelationResponse>(DELETE_RELATION_MUTATION);
+        schema::assert_response::<UpdateIssueResponse>(UPDATE_ISSUE_PARENT_MUTATION);
+        schema::assert_response::<IssueIdResponse>(GET_ISSUE_ID_QUERY);
+    }
+}
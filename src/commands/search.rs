@@ -0,0 +1,164 @@
+use crate::cli::SearchArgs;
+use crate::commands::issues;
+use crate::config::Config;
+use crate::error::{LinearError, Result};
+use crate::mirror::Mirror;
+use crate::pagination::{self, PageLimit};
+
+/// Field prefixes accepted in queries, and the index column each searches
+const FIELDS: &[(&str, &str)] = &[
+    ("id", "identifier"),
+    ("title", "title"),
+    ("description", "description"),
+    ("desc", "description"),
+    ("label", "label"),
+    ("labels", "label"),
+    ("comment", "comment"),
+    ("comments", "comment"),
+];
+
+/// Search the issues and comments mirrored by `linear sync`
+pub fn run(config: &Config, args: SearchArgs) -> Result<()> {
+    let query = fts_query(&args.query)?;
+    let mirror = Mirror::open_existing()?;
+
+    let team_key = config.resolve_team(args.team.as_deref());
+    if let Some(key) = &team_key {
+        if mirror.team(key)?.is_none() {
+            return Err(LinearError::NotSynced(format!(
+                "team {key} is not mirrored"
+            )));
+        }
+    }
+
+    // Fetch one extra result so `pagination::limit` can tell it cut some off
+    let limit = args.page.page_limit();
+    let fetch = match limit {
+        PageLimit::First(n) => Some(n + 1),
+        PageLimit::All => None,
+    };
+    let results = mirror.search(&query, team_key.as_deref(), fetch)?;
+
+    issues::print_issues(&pagination::limit(results, limit), &[]);
+
+    Ok(())
+}
+
+/// Translate a search into an FTS5 query.
+///
+/// Words must all match, in any column; `OR` between two terms matches
+/// either. `"a phrase"` matches the words in order, `word*` matches a prefix,
+/// and `field:term` or `field:"a phrase"` restricts a term to one field (see
+/// `FIELDS`). Terms are always quoted, so punctuation in them is never read
+/// as FTS5 syntax. An argument like `title:login page` (the shell having
+/// removed the quotes) is taken as a phrase.
+fn fts_query(args: &[String]) -> Result<String> {
+    let mut parts: Vec<String> = Vec::new();
+
+    for token in args.iter().flat_map(|arg| split(arg)) {
+        if token == "OR" {
+            if parts.last().is_some_and(|p| p != "OR") {
+                parts.push(token);
+            }
+            continue;
+        }
+
+        let (column, term) = match token.split_once(':') {
+            Some((field, term)) if !term.is_empty() => {
+                match FIELDS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(field))
+                {
+                    Some((_, column)) => (Some(*column), term.to_string()),
+                    None => (None, token),
+                }
+            }
+            _ => (None, token),
+        };
+
+        let (term, prefix) = match term.strip_suffix('*') {
+            Some(stem) if !stem.trim_matches('"').is_empty() => (stem.to_string(), "*"),
+            _ => (term, ""),
+        };
+        let term = term.trim_matches('"');
+        if term.trim().is_empty() {
+            continue;
+        }
+
+        let quoted = format!("\"{}\"{prefix}", term.replace('"', "\"\""));
+        parts.push(match column {
+            Some(column) => format!("{column}:{quoted}"),
+            None => quoted,
+        });
+    }
+
+    if parts.last().is_some_and(|p| p == "OR") {
+        parts.pop();
+    }
+    if parts.is_empty() {
+        return Err(LinearError::InvalidSearch(args.join(" ")));
+    }
+
+    Ok(parts.join(" "))
+}
+
+/// Split one argument into terms, keeping quoted phrases together
+fn split(arg: &str) -> Vec<String> {
+    // The shell already grouped these words, e.g. `title:"login bug"`
+    if let Some((field, phrase)) = arg.split_once(':') {
+        let known = FIELDS
+            .iter()
+            .any(|(name, _)| name.eq_ignore_ascii_case(field));
+        if known && !phrase.contains('"') && phrase.trim().contains(char::is_whitespace) {
+            return vec![format!("{field}:\"{}\"", phrase.trim())];
+        }
+    }
+
+    let mut tokens = Vec::new();
+    let mut current = String::new();
+    let mut quoted = false;
+    for c in arg.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                current.push(c);
+            }
+            c if c.is_whitespace() && !quoted => {
+                if !current.is_empty() {
+                    tokens.push(std::mem::take(&mut current));
+                }
+            }
+            c => current.push(c),
+        }
+    }
+    if !current.is_empty() {
+        tokens.push(current);
+    }
+    tokens
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn query(args: &[&str]) -> String {
+        let args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        fts_query(&args).unwrap()
+    }
+
+    #[test]
+    fn test_fts_query() {
+        assert_eq!(query(&["login", "bug"]), r#""login" "bug""#);
+        assert_eq!(
+            query(&[r#"title:"login page" label:bug"#]),
+            r#"title:"login page" label:"bug""#
+        );
+        // Quotes already removed by the shell
+        assert_eq!(query(&["title:login page"]), r#"title:"login page""#);
+        assert_eq!(query(&["crash OR panic"]), r#""crash" OR "panic""#);
+        assert_eq!(query(&["OR", "auth*", "OR"]), r#""auth"*"#);
+        // Punctuation and unknown fields are just text
+        assert_eq!(query(&["ENG-123 foo:bar"]), r#""ENG-123" "foo:bar""#);
+        assert!(fts_query(&["\"\"".to_string()]).is_err());
+    }
+}
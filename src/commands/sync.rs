@@ -0,0 +1,548 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::cli::SyncArgs;
+use crate::client::LinearClient;
+use crate::commands::comments::Comment;
+use crate::commands::issues::ISSUE_FIELDS_FRAGMENT;
+use crate::commands::labels::Label;
+use crate::config::Config;
+use crate::error::{LinearError, Result};
+use crate::mirror::{EntityKind, Mirror, SyncedTeam};
+use crate::output;
+use crate::pagination::{self, PageLimit};
+use crate::responses::Connection;
+use crate::types::{Cycle, Issue, Project, Team};
+
+/// How often a sync compares a team's mirrored issues with Linear's, to drop
+/// those deleted or moved to another team
+const RECONCILE_INTERVAL: chrono::TimeDelta = chrono::TimeDelta::hours(24);
+
+const SYNC_VIEWER_QUERY: &str = r#"
+query SyncViewer {
+    viewer {
+        id
+        organization {
+            id
+        }
+    }
+}
+"#;
+
+const SYNC_TEAMS_QUERY: &str = r#"
+query SyncTeams($keys: [String!]) {
+    teams(filter: { key: { in: $keys } }) {
+        nodes {
+            id
+            key
+            name
+        }
+    }
+}
+"#;
+
+const SYNC_STATES_QUERY: &str = r#"
+query SyncStates($teamId: String!, $first: Int, $after: String) {
+    team(id: $teamId) {
+        states(first: $first, after: $after) {
+            nodes {
+                id
+                name
+                type
+                color
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
+        }
+    }
+}
+"#;
+
+const SYNC_CYCLES_QUERY: &str = r#"
+query SyncCycles($teamId: String!, $first: Int, $after: String) {
+    team(id: $teamId) {
+        cycles(first: $first, after: $after) {
+            nodes {
+                id
+                name
+                number
+                startsAt
+                endsAt
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
+        }
+    }
+}
+"#;
+
+const SYNC_PROJECTS_QUERY: &str = r#"
+query SyncProjects($teamId: String!, $first: Int, $after: String) {
+    team(id: $teamId) {
+        projects(first: $first, after: $after) {
+            nodes {
+                id
+                name
+                state
+            }
+            pageInfo {
+                hasNextPage
+                endCursor
+            }
+        }
+    }
+}
+"#;
+
+const SYNC_LABELS_QUERY: &str = r#"
+query SyncLabels($first: Int, $after: String) {
+    issueLabels(first: $first, after: $after) {
+        nodes {
+            id
+            name
+            color
+            description
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+// Incremental syncs include archived issues so the mirror can drop them
+const SYNC_ISSUES_QUERY: &str = const_format::concatcp!(
+    r#"
+query SyncIssues($filter: IssueFilter, $includeArchived: Boolean, $first: Int, $after: String) {
+    issues(filter: $filter, includeArchived: $includeArchived, first: $first, after: $after) {
+        nodes {
+            ...IssueFields
+            archivedAt
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#,
+    ISSUE_FIELDS_FRAGMENT
+);
+
+const SYNC_ISSUE_IDS_QUERY: &str = r#"
+query SyncIssueIds($filter: IssueFilter, $first: Int, $after: String) {
+    issues(filter: $filter, first: $first, after: $after) {
+        nodes {
+            id
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+const SYNC_COMMENTS_QUERY: &str = r#"
+query SyncComments($filter: CommentFilter, $includeArchived: Boolean, $first: Int, $after: String) {
+    comments(filter: $filter, includeArchived: $includeArchived, first: $first, after: $after) {
+        nodes {
+            id
+            body
+            createdAt
+            updatedAt
+            archivedAt
+            user {
+                id
+                name
+            }
+            issue {
+                id
+            }
+        }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
+    }
+}
+"#;
+
+#[derive(Deserialize)]
+struct SyncViewerResponse {
+    viewer: Viewer,
+}
+
+#[derive(Deserialize)]
+struct Viewer {
+    id: String,
+    organization: Organization,
+}
+
+#[derive(Deserialize)]
+struct Organization {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct SyncTeamsResponse {
+    teams: Connection<Team>,
+}
+
+#[derive(Deserialize)]
+struct TeamStatesResponse {
+    team: TeamStates,
+}
+
+#[derive(Deserialize)]
+struct TeamStates {
+    states: Connection<State>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct State {
+    id: String,
+    name: String,
+    #[serde(rename = "type")]
+    state_type: String,
+    color: String,
+}
+
+#[derive(Deserialize)]
+struct TeamCyclesResponse {
+    team: TeamCycles,
+}
+
+#[derive(Deserialize)]
+struct TeamCycles {
+    cycles: Connection<Cycle>,
+}
+
+#[derive(Deserialize)]
+struct TeamProjectsResponse {
+    team: TeamProjects,
+}
+
+#[derive(Deserialize)]
+struct TeamProjects {
+    projects: Connection<Project>,
+}
+
+#[derive(Deserialize)]
+struct LabelsResponse {
+    #[serde(rename = "issueLabels")]
+    issue_labels: Connection<Label>,
+}
+
+#[derive(Deserialize)]
+struct SyncIssuesResponse {
+    issues: Connection<SyncedIssue>,
+}
+
+#[derive(Deserialize)]
+struct SyncedIssue {
+    #[serde(flatten)]
+    issue: Issue,
+    #[serde(rename = "archivedAt")]
+    archived_at: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct IssueIdsResponse {
+    issues: Connection<IssueRef>,
+}
+
+#[derive(Deserialize)]
+struct CommentsResponse {
+    comments: Connection<SyncedComment>,
+}
+
+#[derive(Deserialize)]
+struct SyncedComment {
+    #[serde(flatten)]
+    comment: Comment,
+    #[serde(rename = "updatedAt")]
+    updated_at: String,
+    #[serde(rename = "archivedAt")]
+    archived_at: Option<String>,
+    issue: Option<IssueRef>,
+}
+
+#[derive(Deserialize)]
+struct IssueRef {
+    id: String,
+}
+
+/// Mirror the selected teams' issues, comments and lookups locally.
+///
+/// Issues and comments are fetched incrementally: only those updated since
+/// the last sync of each team, unless `--full` is given.
+pub async fn run(client: &LinearClient, config: &Config, args: SyncArgs) -> Result<()> {
+    let mut mirror = Mirror::open()?;
+
+    // A mirror filled from another workspace would keep serving its issues
+    // and resume from its sync marks
+    let viewer = client
+        .query::<SyncViewerResponse>(SYNC_VIEWER_QUERY, None)
+        .await?
+        .viewer;
+    let organization_id = viewer.organization.id;
+    if mirror
+        .meta("organization_id")?
+        .is_some_and(|id| id != organization_id)
+    {
+        eprintln!("Warning: the mirror holds another workspace's issues; starting over");
+        mirror.clear()?;
+    }
+    mirror.set_meta("organization_id", &organization_id)?;
+    mirror.set_meta("viewer_id", &viewer.id)?;
+
+    let mut keys: Vec<String> = args.team.iter().map(|k| k.to_uppercase()).collect();
+    if keys.is_empty() {
+        keys = mirror.teams()?.into_iter().map(|t| t.key).collect();
+    }
+    if keys.is_empty() {
+        keys.extend(config.resolve_team(None));
+    }
+    if keys.is_empty() {
+        return Err(LinearError::NoTeam);
+    }
+
+    let response: SyncTeamsResponse = client
+        .query(SYNC_TEAMS_QUERY, Some(json!({ "keys": keys })))
+        .await?;
+    for key in &keys {
+        if !response.teams.nodes.iter().any(|t| &t.key == key) {
+            return Err(LinearError::TeamNotFound(key.clone()));
+        }
+    }
+
+    let labels = pagination::fetch(
+        client,
+        SYNC_LABELS_QUERY,
+        None,
+        PageLimit::All,
+        |r: LabelsResponse| Ok(r.issue_labels),
+    )
+    .await?;
+    mirror.replace_entities(
+        EntityKind::Label,
+        None,
+        &named(labels, |l| (l.id.clone(), l.name.clone())),
+    )?;
+
+    for team in response.teams.nodes {
+        if args.full {
+            mirror.forget_team(&team.key)?;
+        }
+        sync_team(client, &mut mirror, team).await?;
+    }
+
+    Ok(())
+}
+
+async fn sync_team(client: &LinearClient, mirror: &mut Mirror, team: Team) -> Result<()> {
+    let started_at = chrono::Utc::now().to_rfc3339();
+    let previous = mirror.team(&team.key)?;
+    let team_vars = json!({ "teamId": team.id });
+
+    let states = pagination::fetch(
+        client,
+        SYNC_STATES_QUERY,
+        Some(team_vars.clone()),
+        PageLimit::All,
+        |r: TeamStatesResponse| Ok(r.team.states),
+    )
+    .await?;
+    mirror.replace_entities(
+        EntityKind::State,
+        Some(&team.key),
+        &named(states, |s| (s.id.clone(), s.name.clone())),
+    )?;
+
+    let cycles = pagination::fetch(
+        client,
+        SYNC_CYCLES_QUERY,
+        Some(team_vars.clone()),
+        PageLimit::All,
+        |r: TeamCyclesResponse| Ok(r.team.cycles),
+    )
+    .await?;
+    mirror.replace_entities(
+        EntityKind::Cycle,
+        Some(&team.key),
+        &named(cycles, |c| {
+            let name = c
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("Cycle {}", c.number));
+            (c.id.clone(), name)
+        }),
+    )?;
+
+    let projects = pagination::fetch(
+        client,
+        SYNC_PROJECTS_QUERY,
+        Some(team_vars),
+        PageLimit::All,
+        |r: TeamProjectsResponse| Ok(r.team.projects),
+    )
+    .await?;
+    mirror.replace_entities(
+        EntityKind::Project,
+        Some(&team.key),
+        &named(projects, |p| (p.id.clone(), p.name.clone())),
+    )?;
+
+    let issues_since = previous
+        .as_ref()
+        .and_then(|t| t.issues_synced_through.clone());
+    let mut filter = json!({ "team": { "key": { "eq": team.key } } });
+    if let Some(since) = &issues_since {
+        filter["updatedAt"] = json!({ "gte": since });
+    }
+    let full = previous.is_none();
+    let issues = pagination::fetch(
+        client,
+        SYNC_ISSUES_QUERY,
+        Some(json!({ "filter": filter, "includeArchived": !full })),
+        PageLimit::All,
+        |r: SyncIssuesResponse| Ok(r.issues),
+    )
+    .await?;
+    let issues_synced_through = latest(issues_since, issues.iter().map(|i| &i.issue.updated_at));
+    let issue_count = issues.len();
+    let (archived, issues): (Vec<_>, Vec<_>) =
+        issues.into_iter().partition(|i| i.archived_at.is_some());
+    let mut gone: Vec<_> = archived.into_iter().map(|i| i.issue.id).collect();
+    mirror.save_issues(&issues.into_iter().map(|i| i.issue).collect::<Vec<_>>())?;
+
+    // Deleted issues and those moved to a team that isn't mirrored never show
+    // up as updated here, so now and then compare the whole team's IDs
+    let reconciled_key = format!("reconciled_at:{}", team.key);
+    let reconciled_at = mirror
+        .meta(&reconciled_key)?
+        .and_then(|at| chrono::DateTime::parse_from_rfc3339(&at).ok());
+    let reconcile = !full
+        && reconciled_at.is_none_or(|at| chrono::Utc::now() - at.to_utc() > RECONCILE_INTERVAL);
+    if reconcile {
+        let current: HashSet<_> = pagination::fetch(
+            client,
+            SYNC_ISSUE_IDS_QUERY,
+            Some(json!({ "filter": { "team": { "key": { "eq": team.key } } } })),
+            PageLimit::All,
+            |r: IssueIdsResponse| Ok(r.issues),
+        )
+        .await?
+        .into_iter()
+        .map(|i| i.id)
+        .collect();
+        gone.extend(
+            mirror
+                .issue_ids(&team.key)?
+                .into_iter()
+                .filter(|id| !current.contains(id)),
+        );
+    }
+    let removed = mirror.remove_issues(&gone)?;
+
+    let comments_since = previous.and_then(|t| t.comments_synced_through);
+    let mut filter = json!({ "issue": { "team": { "key": { "eq": team.key } } } });
+    if let Some(since) = &comments_since {
+        filter["updatedAt"] = json!({ "gte": since });
+    }
+    let comments = pagination::fetch(
+        client,
+        SYNC_COMMENTS_QUERY,
+        Some(json!({ "filter": filter, "includeArchived": !full })),
+        PageLimit::All,
+        |r: CommentsResponse| Ok(r.comments),
+    )
+    .await?;
+    let comments_synced_through = latest(comments_since, comments.iter().map(|c| &c.updated_at));
+    let comment_count = comments.len();
+    let (archived, comments): (Vec<_>, Vec<_>) =
+        comments.into_iter().partition(|c| c.archived_at.is_some());
+    let comments: Vec<_> = comments
+        .into_iter()
+        .filter_map(|c| Some((c.issue?.id, c.comment)))
+        .collect();
+    mirror.save_comments(&team.key, &comments)?;
+    mirror.remove_comments(
+        &archived
+            .into_iter()
+            .map(|c| c.comment.id)
+            .collect::<Vec<_>>(),
+    )?;
+
+    // Recorded last, so an interrupted sync is simply retried from the old marks
+    if full || reconcile {
+        mirror.set_meta(&reconciled_key, &started_at)?;
+    }
+    mirror.save_team(&SyncedTeam {
+        key: team.key.clone(),
+        id: team.id,
+        name: team.name,
+        issues_synced_through,
+        comments_synced_through,
+        synced_at: started_at,
+    })?;
+
+    let mut message = format!(
+        "Synced {}: {issue_count} issues, {comment_count} comments",
+        team.key
+    );
+    if removed > 0 {
+        message.push_str(&format!(", {removed} removed"));
+    }
+    output::print_message(&message);
+
+    Ok(())
+}
+
+/// Pair each entity with its ID and display name for the mirror
+fn named<T>(items: Vec<T>, key: impl Fn(&T) -> (String, String)) -> Vec<(String, String, T)> {
+    items
+        .into_iter()
+        .map(|item| {
+            let (id, name) = key(&item);
+            (id, name, item)
+        })
+        .collect()
+}
+
+/// The most recent of the previous mark and the fetched `updatedAt` values
+///
+/// Linear's ISO 8601 timestamps are all UTC, so they compare as strings.
+fn latest<'a>(
+    previous: Option<String>,
+    updated: impl Iterator<Item = &'a String>,
+) -> Option<String> {
+    updated.max().cloned().max(previous)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<SyncViewerResponse>(SYNC_VIEWER_QUERY);
+        schema::assert_response::<SyncTeamsResponse>(SYNC_TEAMS_QUERY);
+        schema::assert_response::<TeamStatesResponse>(SYNC_STATES_QUERY);
+        schema::assert_response::<TeamCyclesResponse>(SYNC_CYCLES_QUERY);
+        schema::assert_response::<TeamProjectsResponse>(SYNC_PROJECTS_QUERY);
+        schema::assert_response::<LabelsResponse>(SYNC_LABELS_QUERY);
+        schema::assert_response::<SyncIssuesResponse>(SYNC_ISSUES_QUERY);
+        schema::assert_response::<IssueIdsResponse>(SYNC_ISSUE_IDS_QUERY);
+        schema::assert_response::<CommentsResponse>(SYNC_COMMENTS_QUERY);
+    }
+}
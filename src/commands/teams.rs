@@ -4,6 +4,7 @@ use tabled::Tabled;
 use crate::client::LinearClient;
 use crate::error::Result;
 use crate::output;
+use crate::pagination::{self, PageLimit};
 use crate::responses::Connection;
 use crate::types::Team;
 
@@ -27,14 +28,18 @@ impl From<&Team> for TeamRow {
     }
 }
 
-const LIST_TEAMS_QUERY: &str = r#"
-query ListTeams {
-    teams {
+pub(crate) const LIST_TEAMS_QUERY: &str = r#"
+query ListTeams($first: Int, $after: String) {
+    teams(first: $first, after: $after) {
         nodes {
             id
             key
             name
         }
+        pageInfo {
+            hasNextPage
+            endCursor
+        }
     }
 }
 "#;
@@ -44,14 +49,32 @@ struct TeamsResponse {
     teams: Connection<Team>,
 }
 
-pub async fn list(client: &LinearClient) -> Result<()> {
-    let response: TeamsResponse = client.query(LIST_TEAMS_QUERY, None).await?;
+pub async fn list(client: &LinearClient, limit: PageLimit) -> Result<()> {
+    let teams = pagination::fetch(
+        client,
+        LIST_TEAMS_QUERY,
+        None,
+        limit,
+        |response: TeamsResponse| Ok(response.teams),
+    )
+    .await?;
 
     output::print_table(
-        &response.teams.nodes,
+        &teams,
         |team| TeamRow::from(team),
         |team| format!("{} | {}", team.key, team.name),
     );
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema;
+
+    #[test]
+    fn test_responses_match_schema() {
+        schema::assert_response::<TeamsResponse>(LIST_TEAMS_QUERY);
+    }
+}
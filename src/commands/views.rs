@@ -0,0 +1,213 @@
+use serde::Serialize;
+use tabled::Tabled;
+use toml_edit::{DocumentMut, Item, Table};
+
+use crate::cli::{IssueFilterArgs, IssueListArgs, PageArgs, ViewSaveArgs};
+use crate::config::{self, Config, View};
+use crate::error::{LinearError, Result};
+use crate::output;
+
+#[derive(Serialize)]
+struct NamedView<'a> {
+    name: &'a str,
+    #[serde(flatten)]
+    view: &'a View,
+}
+
+#[derive(Tabled)]
+struct ViewRow {
+    #[tabled(rename = "Name")]
+    name: String,
+    #[tabled(rename = "Filters")]
+    filters: String,
+}
+
+/// List the views saved in config.toml
+pub fn list(config: &Config) -> Result<()> {
+    let views: Vec<NamedView> = config
+        .views
+        .iter()
+        .map(|(name, view)| NamedView { name, view })
+        .collect();
+
+    output::print_table(
+        &views,
+        |v| ViewRow {
+            name: v.name.to_string(),
+            filters: describe(v.view),
+        },
+        |v| format!("{} | {}", v.name, describe(v.view)),
+    );
+
+    Ok(())
+}
+
+/// Show one view's filters
+pub fn show(config: &Config, name: &str) -> Result<()> {
+    let view = config
+        .views
+        .get(name)
+        .ok_or_else(|| LinearError::UnknownView(name.to_string()))?;
+
+    output::print_item(&NamedView { name, view }, |v| {
+        print!("{}", toml::to_string(v.view).unwrap_or_default());
+    });
+
+    Ok(())
+}
+
+/// Save the given filters as `[views.<name>]`, replacing any existing view
+pub fn save(args: ViewSaveArgs) -> Result<()> {
+    let filter = args.filter;
+    let view = View {
+        team: filter.team,
+        status: filter.status,
+        project: filter.project,
+        labels: filter.label,
+        cycle: filter.cycle,
+        mine: filter.mine,
+        limit: args.limit,
+        sort: filter.sort,
+        columns: filter.columns,
+    };
+
+    let path = Config::config_path()?;
+    let edit_error = |message: String| LinearError::ConfigEdit {
+        path: path.clone(),
+        message,
+    };
+
+    let table = toml::to_string(&view)
+        .map_err(|e| edit_error(e.to_string()))?
+        .parse::<DocumentMut>()
+        .map_err(|e| edit_error(e.to_string()))?
+        .as_table()
+        .clone();
+    if table.is_empty() {
+        return Err(LinearError::InvalidConfigValue {
+            key: format!("views.{}", args.name),
+            message: "no filters given".to_string(),
+        });
+    }
+
+    let mut document = config::read_document(&path)?;
+    let views = document
+        .entry("views")
+        .or_insert_with(|| {
+            let mut views = Table::new();
+            views.set_implicit(true);
+            Item::Table(views)
+        })
+        .as_table_mut()
+        .ok_or_else(|| edit_error("`views` is not a table".to_string()))?;
+    let replaced = views.insert(&args.name, Item::Table(table)).is_some();
+    config::write_document(&path, &document)?;
+
+    let verb = if replaced { "Replaced" } else { "Saved" };
+    output::print_message(&format!("{verb} view '{}'", args.name));
+    Ok(())
+}
+
+/// Remove `[views.<name>]` from config.toml
+pub fn delete(name: &str) -> Result<()> {
+    let path = Config::config_path()?;
+    let mut document = config::read_document(&path)?;
+
+    let removed = document
+        .get_mut("views")
+        .and_then(Item::as_table_like_mut)
+        .and_then(|views| views.remove(name));
+    if removed.is_none() {
+        return Err(LinearError::UnknownView(name.to_string()));
+    }
+
+    config::write_document(&path, &document)?;
+    output::print_message(&format!("Deleted view '{name}'"));
+    Ok(())
+}
+
+/// `args` with the filters it doesn't set taken from its `--view`, if any
+pub(crate) fn apply(config: &Config, args: IssueListArgs) -> Result<IssueListArgs> {
+    let Some(name) = &args.view else {
+        return Ok(args);
+    };
+    let view = config
+        .views
+        .get(name)
+        .cloned()
+        .ok_or_else(|| LinearError::UnknownView(name.clone()))?;
+
+    let filter = args.filter;
+    Ok(IssueListArgs {
+        filter: IssueFilterArgs {
+            mine: filter.mine || view.mine,
+            team: filter.team.or(view.team),
+            status: filter.status.or(view.status),
+            project: filter.project.or(view.project),
+            label: if filter.label.is_empty() {
+                view.labels
+            } else {
+                filter.label
+            },
+            cycle: filter.cycle.or(view.cycle),
+            sort: filter.sort.or(view.sort),
+            columns: if filter.columns.is_empty() {
+                view.columns
+            } else {
+                filter.columns
+            },
+        },
+        page: PageArgs {
+            limit: args.page.limit.or(view.limit),
+            all: args.page.all,
+        },
+        ..args
+    })
+}
+
+/// One-line summary of a view, e.g. `team = "ENG", mine = true`
+fn describe(view: &View) -> String {
+    toml::to_string(view)
+        .unwrap_or_default()
+        .lines()
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::*;
+    use crate::cli::{Cli, Commands, IssueColumn};
+
+    #[test]
+    fn test_apply_fills_unset_flags() {
+        let config: Config = toml::from_str(
+            r#"
+            [views.triage]
+            team = "ENG"
+            status = "Triage"
+            labels = ["bug"]
+            limit = 20
+            columns = ["id", "title"]
+            "#,
+        )
+        .unwrap();
+        let cli = Cli::try_parse_from([
+            "linear", "issues", "--view", "triage", "--status", "Todo", "--mine",
+        ])
+        .unwrap();
+        let Commands::Issues(args) = cli.command else {
+            panic!("expected issues");
+        };
+
+        let args = apply(&config, args).unwrap();
+        assert_eq!(args.filter.team.as_deref(), Some("ENG"));
+        assert_eq!(args.filter.status.as_deref(), Some("Todo"));
+        assert_eq!(args.filter.label, ["bug"]);
+        assert!(args.filter.mine);
+        assert_eq!(args.page.limit, Some(20));
+        assert_eq!(args.filter.columns, [IssueColumn::Id, IssueColumn::Title]);
+    }
+}
@@ -1,22 +1,523 @@
-use std::path::PathBuf;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
+use clap::ValueEnum;
+use const_format::formatcp;
 use directories::ProjectDirs;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use toml_edit::{DocumentMut, Item, Table};
 
+use crate::auth::{self, write_private, Credentials, OAuthTokens};
+use crate::cli::{IssueColumn, IssueSort, OutputFormat};
+use crate::client::DEFAULT_API_ENDPOINT;
+use crate::download;
 use crate::error::{LinearError, Result};
+use crate::http;
+use crate::secret;
 
 #[derive(Deserialize, Default)]
 pub struct Config {
     pub api_key: Option<String>,
+    /// Shell command that prints the API key (e.g. "pass show linear")
+    pub api_key_command: Option<String>,
+    /// Read the API key from the OS keyring (set by `linear init`)
+    #[serde(default)]
+    pub keyring: bool,
     pub default_team: Option<String>,
+    /// Project for `issue create` and the `issues` filter when --project isn't
+    /// given (`issues --any-project` skips it)
+    pub default_project: Option<String>,
+    /// Labels for `issue create` when no --label is given
+    #[serde(default)]
+    pub default_labels: Vec<String>,
+    /// Assignee for `issue create` when --assignee isn't given
+    pub default_assignee: Option<String>,
+    /// GraphQL endpoint override (e.g. a staging proxy or local mock)
+    pub api_url: Option<String>,
+    /// Output format when --format isn't given
+    pub format: Option<OutputFormat>,
+    #[serde(default)]
+    pub network: NetworkConfig,
+    #[serde(default)]
+    pub oauth: OAuthConfig,
+    /// `[profiles.<name>]` tables, selected with --profile / LINEAR_PROFILE
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+    /// `[views.<name>]` tables, used with `linear issues --view <name>`
+    #[serde(default)]
+    pub views: BTreeMap<String, View>,
+}
+
+/// A saved set of `issues` filters
+#[derive(Deserialize, Serialize, Default, Clone)]
+pub struct View {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub team: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub labels: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cycle: Option<String>,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub mine: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sort: Option<IssueSort>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<IssueColumn>,
+}
+
+/// Keys a `[views.<name>]` table may hold
+const VIEW_KEYS: &[&str] = &[
+    "team", "status", "project", "labels", "cycle", "mine", "limit", "sort", "columns",
+];
+
+/// Per-workspace settings. When a profile is selected these replace the
+/// top-level ones entirely, so keys and teams never mix between workspaces.
+#[derive(Deserialize, Default)]
+pub struct Profile {
+    pub api_key: Option<String>,
+    pub api_key_command: Option<String>,
+    #[serde(default)]
+    pub keyring: bool,
+    pub default_team: Option<String>,
+    pub default_project: Option<String>,
+    #[serde(default)]
+    pub default_labels: Vec<String>,
+    pub default_assignee: Option<String>,
+    pub api_url: Option<String>,
+}
+
+/// `.linear.toml`: per-repository defaults layered over the global config.
+/// Only the keys marked `repo` in `SETTINGS` are read, so a checked-in file
+/// can't point the API key at another endpoint.
+#[derive(Deserialize, Default)]
+struct RepoConfig {
+    default_team: Option<String>,
+    default_project: Option<String>,
+    default_labels: Option<Vec<String>>,
+    default_assignee: Option<String>,
+    format: Option<OutputFormat>,
+}
+
+/// Name of the per-repository config file, found by walking up from the cwd
+const REPO_CONFIG_FILE: &str = ".linear.toml";
+
+/// Profile selected for this process (None = top-level settings)
+static PROFILE: Mutex<Option<String>> = Mutex::new(None);
+
+pub fn set_profile(name: Option<String>) -> Result<()> {
+    if let Some(name) = &name {
+        if !is_valid_profile_name(name) {
+            return Err(LinearError::InvalidProfileName(name.clone()));
+        }
+    }
+
+    *PROFILE.lock().unwrap_or_else(|e| e.into_inner()) = name;
+    Ok(())
+}
+
+pub fn profile() -> Option<String> {
+    PROFILE.lock().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// `[oauth]` table: the OAuth application used by `linear auth login`.
+#[derive(Deserialize, Default)]
+pub struct OAuthConfig {
+    pub client_id: Option<String>,
+    /// Only needed for OAuth apps that don't accept PKCE-only token exchange
+    pub client_secret: Option<String>,
+    /// Loopback port for the redirect URI (http://localhost:<port>/callback)
+    pub redirect_port: Option<u16>,
+    /// Comma-separated scopes (default "read,write")
+    pub scopes: Option<String>,
+}
+
+impl OAuthConfig {
+    /// Client ID with env var taking precedence over config file
+    pub fn client_id(&self) -> Result<String> {
+        std::env::var("LINEAR_OAUTH_CLIENT_ID")
+            .ok()
+            .or_else(|| self.client_id.clone())
+            .ok_or(LinearError::OAuthNotConfigured)
+    }
+}
+
+/// `[network]` table: proxy, TLS and timeout settings for all HTTP traffic.
+#[derive(Deserialize, Default)]
+pub struct NetworkConfig {
+    /// Proxy URL for all requests (e.g. "http://proxy.corp:3128")
+    pub proxy: Option<String>,
+    /// Comma-separated hosts that bypass `proxy`
+    pub no_proxy: Option<String>,
+    /// Extra PEM files with root certificates to trust
+    #[serde(default)]
+    pub ca_certs: Vec<PathBuf>,
+    pub connect_timeout_secs: Option<u64>,
+    /// Max idle time between reads of a response
+    pub read_timeout_secs: Option<u64>,
+    /// Total time limit per request (none by default, so large downloads can finish)
+    pub timeout_secs: Option<u64>,
+    pub user_agent: Option<String>,
+    /// Files downloaded in parallel by download commands
+    pub max_concurrent_downloads: Option<usize>,
+}
+
+/// Type of a setting's value, checked by `linear config set`
+#[derive(Debug, Clone, Copy)]
+pub enum ValueKind {
+    String,
+    Bool,
+    /// Integer in an inclusive range
+    Integer(i64, i64),
+    /// Array of strings, given to `config set` comma-separated
+    List,
+    /// One of a fixed set of strings
+    Choice(&'static [&'static str]),
+}
+
+/// A key `config.toml` understands, and where else its value can come from
+#[derive(Debug)]
+pub struct Setting {
+    /// Dotted path, e.g. "network.proxy"
+    pub key: &'static str,
+    pub kind: ValueKind,
+    /// Command-line flag that overrides everything else
+    pub flag: Option<&'static str>,
+    /// Environment variable that overrides the file
+    pub env: Option<&'static str>,
+    /// Value used when the key isn't set anywhere
+    pub default: Option<&'static str>,
+    /// Can be set in `[profiles.<name>]`, replacing the top-level value
+    pub per_profile: bool,
+    /// Redacted by `linear config list`
+    pub secret: bool,
+    /// Can be set in `.linear.toml`, overriding `config.toml`
+    pub repo: bool,
+}
+
+impl Setting {
+    const fn new(key: &'static str, kind: ValueKind) -> Self {
+        Self {
+            key,
+            kind,
+            flag: None,
+            env: None,
+            default: None,
+            per_profile: false,
+            secret: false,
+            repo: false,
+        }
+    }
+
+    const fn flag(self, flag: &'static str) -> Self {
+        Self {
+            flag: Some(flag),
+            ..self
+        }
+    }
+
+    const fn env(self, env: &'static str) -> Self {
+        Self {
+            env: Some(env),
+            ..self
+        }
+    }
+
+    const fn default(self, default: &'static str) -> Self {
+        Self {
+            default: Some(default),
+            ..self
+        }
+    }
+
+    const fn per_profile(self) -> Self {
+        Self {
+            per_profile: true,
+            ..self
+        }
+    }
+
+    const fn secret(self) -> Self {
+        Self {
+            secret: true,
+            ..self
+        }
+    }
+
+    const fn repo(self) -> Self {
+        Self { repo: true, ..self }
+    }
+}
+
+/// Every key `Config` reads, in the order `linear config list` shows them
+pub const SETTINGS: &[Setting] = &[
+    Setting::new("api_key", ValueKind::String)
+        .env("LINEAR_API_KEY")
+        .per_profile()
+        .secret(),
+    Setting::new("api_key_command", ValueKind::String).per_profile(),
+    Setting::new("keyring", ValueKind::Bool)
+        .default("false")
+        .per_profile(),
+    Setting::new("default_team", ValueKind::String)
+        .env("LINEAR_TEAM")
+        .per_profile()
+        .repo(),
+    Setting::new("default_project", ValueKind::String)
+        .per_profile()
+        .repo(),
+    Setting::new("default_labels", ValueKind::List)
+        .per_profile()
+        .repo(),
+    Setting::new("default_assignee", ValueKind::String)
+        .per_profile()
+        .repo(),
+    Setting::new("api_url", ValueKind::String)
+        .env("LINEAR_API_URL")
+        .default(DEFAULT_API_ENDPOINT)
+        .per_profile(),
+    Setting::new("format", ValueKind::Choice(&["table", "json", "compact"]))
+        .flag("--format")
+        .env("LINEAR_FORMAT")
+        .default("json")
+        .repo(),
+    Setting::new("network.proxy", ValueKind::String),
+    Setting::new("network.no_proxy", ValueKind::String),
+    Setting::new("network.ca_certs", ValueKind::List),
+    Setting::new("network.connect_timeout_secs", ValueKind::Integer(1, 3600))
+        .default(formatcp!("{}", http::DEFAULT_CONNECT_TIMEOUT_SECS)),
+    Setting::new("network.read_timeout_secs", ValueKind::Integer(1, 3600))
+        .default(formatcp!("{}", http::DEFAULT_READ_TIMEOUT_SECS)),
+    Setting::new("network.timeout_secs", ValueKind::Integer(1, 86400)),
+    Setting::new("network.user_agent", ValueKind::String).default(http::DEFAULT_USER_AGENT),
+    Setting::new(
+        "network.max_concurrent_downloads",
+        ValueKind::Integer(1, 64),
+    )
+    .default(formatcp!("{}", download::DEFAULT_CONCURRENCY)),
+    Setting::new("oauth.client_id", ValueKind::String).env("LINEAR_OAUTH_CLIENT_ID"),
+    Setting::new("oauth.client_secret", ValueKind::String).secret(),
+    Setting::new("oauth.redirect_port", ValueKind::Integer(1, 65535))
+        .default(formatcp!("{}", auth::DEFAULT_REDIRECT_PORT)),
+    Setting::new("oauth.scopes", ValueKind::String).default(auth::DEFAULT_SCOPES),
+];
+
+/// Look up a setting by its dotted key
+pub fn setting(key: &str) -> Option<&'static Setting> {
+    SETTINGS.iter().find(|s| s.key == key)
+}
+
+/// Unknown-key message suffix naming the closest known key, if any is close
+pub fn suggest(key: &str) -> String {
+    // Compare `profiles.<name>.<key>` by its last part
+    match key
+        .strip_prefix("profiles.")
+        .and_then(|k| k.split_once('.'))
+    {
+        Some((name, key)) => closest(key, &format!("profiles.{name}."), |s| s.per_profile),
+        None => closest(key, "", |_| true),
+    }
+}
+
+fn closest(key: &str, prefix: &str, allowed: impl Fn(&Setting) -> bool) -> String {
+    SETTINGS
+        .iter()
+        .filter(|s| allowed(s))
+        .map(|s| (strsim::jaro_winkler(key, s.key), s.key))
+        .filter(|(score, _)| *score > 0.8)
+        .max_by(|a, b| a.0.total_cmp(&b.0))
+        .map(|(_, known)| format!(" (did you mean '{prefix}{known}'?)"))
+        .unwrap_or_default()
+}
+
+/// Dotted paths of keys in `table` that `Config` doesn't read
+pub fn unknown_keys(table: &toml::Table) -> Vec<String> {
+    let mut unknown = Vec::new();
+    collect_unknown_keys("", table, &mut unknown);
+    unknown
+}
+
+fn collect_unknown_keys(prefix: &str, table: &toml::Table, unknown: &mut Vec<String>) {
+    for (key, value) in table {
+        let path = format!("{prefix}{key}");
+        match value {
+            toml::Value::Table(profiles) if path == "profiles" => {
+                for (name, profile) in profiles {
+                    let Some(profile) = profile.as_table() else {
+                        continue;
+                    };
+                    for key in profile.keys() {
+                        if !setting(key).is_some_and(|s| s.per_profile) {
+                            unknown.push(format!("profiles.{name}.{key}"));
+                        }
+                    }
+                }
+            }
+            // Alias names are the user's own
+            toml::Value::Table(_) if path == "alias" => {}
+            toml::Value::Table(views) if path == "views" => {
+                for (name, view) in views {
+                    let Some(view) = view.as_table() else {
+                        continue;
+                    };
+                    for key in view.keys() {
+                        if !VIEW_KEYS.contains(&key.as_str()) {
+                            unknown.push(format!("views.{name}.{key}"));
+                        }
+                    }
+                }
+            }
+            toml::Value::Table(section)
+                if SETTINGS
+                    .iter()
+                    .any(|s| s.key.starts_with(&format!("{path}."))) =>
+            {
+                collect_unknown_keys(&format!("{path}."), section, unknown);
+            }
+            _ if setting(&path).is_none() => unknown.push(path),
+            _ => {}
+        }
+    }
+}
+
+/// `[alias]` from config.toml, read before the command line is parsed (so
+/// before a profile is selected). Only the alias table is read, so a mistake
+/// elsewhere in the file doesn't disable aliases.
+pub fn aliases() -> BTreeMap<String, String> {
+    let Ok(path) = Config::config_path() else {
+        return BTreeMap::new();
+    };
+    let Ok(contents) = std::fs::read_to_string(&path) else {
+        return BTreeMap::new();
+    };
+
+    parse_aliases(&contents).unwrap_or_else(|e| {
+        eprintln!(
+            "Warning: ignoring aliases in {}: {}",
+            path.display(),
+            e.message()
+        );
+        BTreeMap::new()
+    })
+}
+
+fn parse_aliases(contents: &str) -> std::result::Result<BTreeMap<String, String>, toml::de::Error> {
+    let mut table: toml::Table = toml::from_str(contents)?;
+    match table.remove("alias") {
+        Some(alias) => alias.try_into(),
+        None => Ok(BTreeMap::new()),
+    }
+}
+
+/// The nearest `.linear.toml` in the current directory or one of its parents
+pub fn repo_config_path() -> Option<PathBuf> {
+    let cwd = std::env::current_dir().ok()?;
+    cwd.ancestors()
+        .map(|dir| dir.join(REPO_CONFIG_FILE))
+        .find(|path| path.is_file())
+}
+
+/// Read `config.toml` for editing, keeping comments and layout
+pub fn read_document(path: &Path) -> Result<DocumentMut> {
+    if !path.exists() {
+        return Ok(DocumentMut::new());
+    }
+
+    std::fs::read_to_string(path)
+        .map_err(|e| LinearError::ConfigRead {
+            path: path.to_path_buf(),
+            source: e,
+        })?
+        .parse::<DocumentMut>()
+        .map_err(|e| LinearError::ConfigEdit {
+            path: path.to_path_buf(),
+            message: e.to_string(),
+        })
+}
+
+/// Write an edited config file, creating its directory, readable only by the user
+pub fn write_document(path: &Path, document: &DocumentMut) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| LinearError::ConfigRead {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+    }
+    write_private(path, document.to_string().as_bytes())
+}
+
+/// The top-level table, or `[profiles.<name>]` (created if missing)
+pub fn target_table<'a>(
+    document: &'a mut DocumentMut,
+    profile: Option<&str>,
+    path: &Path,
+) -> Result<&'a mut Table> {
+    let root = document.as_table_mut();
+    let Some(name) = profile else {
+        return Ok(root);
+    };
+
+    let not_a_table = |key: &str| LinearError::ConfigEdit {
+        path: path.to_path_buf(),
+        message: format!("`{key}` is not a table"),
+    };
+
+    let profiles = root
+        .entry("profiles")
+        .or_insert_with(|| {
+            let mut table = Table::new();
+            table.set_implicit(true);
+            Item::Table(table)
+        })
+        .as_table_mut()
+        .ok_or_else(|| not_a_table("profiles"))?;
+
+    profiles
+        .entry(name)
+        .or_insert_with(|| Item::Table(Table::new()))
+        .as_table_mut()
+        .ok_or_else(|| not_a_table(&format!("profiles.{name}")))
 }
 
 impl Config {
+    /// Load `config.toml` (and the selected profile), then layer the nearest
+    /// `.linear.toml` and the environment over it
     pub fn load() -> Result<Self> {
+        Self::load_layered(false)
+    }
+
+    /// Like `load`, but a selected profile that isn't in config.toml yet
+    /// counts as empty, for `linear init` to create it
+    pub fn load_or_new_profile() -> Result<Self> {
+        Self::load_layered(true)
+    }
+
+    fn load_layered(new_profile: bool) -> Result<Self> {
+        let mut config = Self::load_file(new_profile)?;
+
+        if let Some(path) = repo_config_path() {
+            config.apply_repo(RepoConfig::load(&path)?);
+        }
+        config.apply_env()?;
+
+        Ok(config)
+    }
+
+    fn load_file(new_profile: bool) -> Result<Self> {
         let config_path = Self::config_path()?;
+        let profile = profile();
 
         if !config_path.exists() {
-            return Ok(Config::default());
+            return match profile {
+                Some(name) if !new_profile => Err(LinearError::UnknownProfile(name)),
+                _ => Ok(Config::default()),
+            };
         }
 
         let contents =
@@ -25,10 +526,88 @@ impl Config {
                 source: e,
             })?;
 
-        toml::from_str(&contents).map_err(|e| LinearError::ConfigParse {
-            path: config_path,
-            source: e,
-        })
+        let table: toml::Table =
+            toml::from_str(&contents).map_err(|e| LinearError::ConfigParse {
+                path: config_path.clone(),
+                source: e,
+            })?;
+        for key in unknown_keys(&table) {
+            eprintln!(
+                "Warning: unknown key '{key}' in {}{}",
+                config_path.display(),
+                suggest(&key)
+            );
+        }
+
+        let mut config: Config =
+            toml::Value::Table(table)
+                .try_into()
+                .map_err(|e| LinearError::ConfigParse {
+                    path: config_path.clone(),
+                    source: e,
+                })?;
+
+        if config.api_key.is_some() || config.profiles.values().any(|p| p.api_key.is_some()) {
+            warn_if_shared(&config_path);
+        }
+
+        if let Some(name) = profile {
+            let selected = match config.profiles.remove(&name) {
+                Some(selected) => selected,
+                None if new_profile => Profile::default(),
+                None => return Err(LinearError::UnknownProfile(name)),
+            };
+            config.apply_profile(selected);
+        }
+
+        Ok(config)
+    }
+
+    fn apply_profile(&mut self, profile: Profile) {
+        self.api_key = profile.api_key;
+        self.api_key_command = profile.api_key_command;
+        self.keyring = profile.keyring;
+        self.default_team = profile.default_team;
+        self.default_project = profile.default_project;
+        self.default_labels = profile.default_labels;
+        self.default_assignee = profile.default_assignee;
+        self.api_url = profile.api_url;
+    }
+
+    fn apply_repo(&mut self, repo: RepoConfig) {
+        if let Some(team) = repo.default_team {
+            self.default_team = Some(team);
+        }
+        if let Some(project) = repo.default_project {
+            self.default_project = Some(project);
+        }
+        if let Some(labels) = repo.default_labels {
+            self.default_labels = labels;
+        }
+        if let Some(assignee) = repo.default_assignee {
+            self.default_assignee = Some(assignee);
+        }
+        if let Some(format) = repo.format {
+            self.format = Some(format);
+        }
+    }
+
+    fn apply_env(&mut self) -> Result<()> {
+        let var = |name| std::env::var(name).ok().filter(|v: &String| !v.is_empty());
+
+        if let Some(team) = var("LINEAR_TEAM") {
+            self.default_team = Some(team);
+        }
+        if let Some(format) = var("LINEAR_FORMAT") {
+            let parsed = OutputFormat::from_str(&format, true).map_err(|_| {
+                LinearError::InvalidConfigValue {
+                    key: "LINEAR_FORMAT".to_string(),
+                    message: format!("expected one of table, json, compact, got '{format}'"),
+                }
+            })?;
+            self.format = Some(parsed);
+        }
+        Ok(())
     }
 
     pub fn config_path() -> Result<PathBuf> {
@@ -37,19 +616,238 @@ impl Config {
             .ok_or(LinearError::NoConfigDir)
     }
 
-    /// Get API key with env var taking precedence over config file
+    /// Path for per-profile state (cache, tokens) next to the config file.
+    /// The top-level settings use the config directory itself; a named
+    /// profile uses `profiles/<name>/` below it.
+    pub fn profile_path(file_name: &str) -> Result<PathBuf> {
+        let dir = Self::config_path()?
+            .parent()
+            .map(PathBuf::from)
+            .ok_or(LinearError::NoConfigDir)?;
+
+        Ok(match profile() {
+            Some(name) => dir.join("profiles").join(name).join(file_name),
+            None => dir.join(file_name),
+        })
+    }
+
+    /// Get API key: env var, then api_key_command, then keyring, then plaintext api_key
     pub fn api_key(&self) -> Result<String> {
         if let Ok(key) = std::env::var("LINEAR_API_KEY") {
             return Ok(key);
         }
 
+        self.stored_api_key()
+    }
+
+    /// The API key configured in the file (or keyring), ignoring LINEAR_API_KEY
+    pub fn stored_api_key(&self) -> Result<String> {
+        if let Some(command) = &self.api_key_command {
+            return secret::run_command(command);
+        }
+
+        if self.keyring {
+            if let Some(key) = secret::keyring_get()? {
+                return Ok(key);
+            }
+        }
+
         self.api_key.clone().ok_or(LinearError::MissingApiKey)
     }
 
-    /// Get team, preferring explicit argument over default
+    /// Where a configured (non-env) API key comes from, if any
+    pub fn api_key_source(&self) -> Option<&'static str> {
+        if self.api_key_command.is_some() {
+            Some("api_key_command")
+        } else if self.keyring {
+            Some("keyring")
+        } else if self.api_key.is_some() {
+            Some("config_api_key")
+        } else {
+            None
+        }
+    }
+
+    /// Resolve credentials: LINEAR_API_KEY, then OAuth login, then config api_key
+    pub fn credentials(&self) -> Result<Credentials> {
+        if let Ok(key) = std::env::var("LINEAR_API_KEY") {
+            return Ok(Credentials::ApiKey(key));
+        }
+
+        if let Some(tokens) = OAuthTokens::load()? {
+            return Ok(Credentials::OAuth(tokens));
+        }
+
+        self.api_key().map(Credentials::ApiKey)
+    }
+
+    /// Get API endpoint override with env var taking precedence over config file
+    pub fn api_url(&self) -> Option<String> {
+        std::env::var("LINEAR_API_URL")
+            .ok()
+            .or_else(|| self.api_url.clone())
+    }
+
+    /// Get team, preferring explicit argument over default (which may come
+    /// from `.linear.toml` or LINEAR_TEAM)
     pub fn resolve_team(&self, explicit: Option<&str>) -> Option<String> {
         explicit
             .map(String::from)
             .or_else(|| self.default_team.clone())
     }
 }
+
+impl RepoConfig {
+    fn load(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| LinearError::ConfigRead {
+            path: path.to_path_buf(),
+            source: e,
+        })?;
+        let table: toml::Table =
+            toml::from_str(&contents).map_err(|e| LinearError::ConfigParse {
+                path: path.to_path_buf(),
+                source: e,
+            })?;
+
+        let mut repo = toml::Table::new();
+        for (key, value) in table {
+            match setting(&key) {
+                Some(s) if s.repo => {
+                    repo.insert(key, value);
+                }
+                Some(_) => eprintln!(
+                    "Warning: ignoring '{key}' in {}; set it in config.toml instead",
+                    path.display()
+                ),
+                None => eprintln!(
+                    "Warning: unknown key '{key}' in {}{}",
+                    path.display(),
+                    closest(&key, "", |s| s.repo)
+                ),
+            }
+        }
+
+        toml::Value::Table(repo)
+            .try_into()
+            .map_err(|e| LinearError::ConfigParse {
+                path: path.to_path_buf(),
+                source: e,
+            })
+    }
+}
+
+/// Profile names become directory names, so keep them simple
+fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Warn when a config file holding a plaintext API key is readable by others
+#[cfg(unix)]
+fn warn_if_shared(path: &std::path::Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    let Ok(metadata) = std::fs::metadata(path) else {
+        return;
+    };
+
+    if metadata.permissions().mode() & 0o077 != 0 {
+        eprintln!(
+            "Warning: {} contains an API key and is readable by other users. Run `chmod 600 {}` or move the key to api_key_command or the keyring.",
+            path.display(),
+            path.display()
+        );
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_shared(_path: &std::path::Path) {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_aliases_ignore_other_settings() {
+        let aliases = parse_aliases(
+            r#"
+            format = "yaml-ish"
+
+            [alias]
+            mine = "issues --mine"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(aliases["mine"], "issues --mine");
+
+        assert!(parse_aliases("[alias]\nmine = 1\n").is_err());
+        assert!(parse_aliases("format = \"json\"\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_profile_replaces_top_level_settings() {
+        let mut config: Config = toml::from_str(
+            r#"
+            api_key = "lin_api_company"
+            default_team = "ENG"
+
+            [profiles.oss]
+            api_key_command = "pass show linear-oss"
+            "#,
+        )
+        .unwrap();
+
+        let oss = config.profiles.remove("oss").unwrap();
+        config.apply_profile(oss);
+
+        assert_eq!(config.api_key, None);
+        assert_eq!(
+            config.api_key_command.as_deref(),
+            Some("pass show linear-oss")
+        );
+        assert_eq!(config.default_team, None);
+        assert_eq!(config.api_key_source(), Some("api_key_command"));
+    }
+
+    #[test]
+    fn test_repo_config_overrides_only_repo_keys() {
+        let path =
+            std::env::temp_dir().join(format!("linear-cli-{}-repo.toml", std::process::id()));
+        std::fs::write(
+            &path,
+            r#"
+            default_team = "MOB"
+            default_labels = ["mobile"]
+            format = "table"
+            api_url = "https://evil.example/graphql"
+            "#,
+        )
+        .unwrap();
+        let repo = RepoConfig::load(&path);
+        std::fs::remove_file(&path).unwrap();
+
+        let mut config: Config = toml::from_str(
+            r#"
+            default_team = "ENG"
+            default_project = "Platform"
+            "#,
+        )
+        .unwrap();
+        config.apply_repo(repo.unwrap());
+
+        assert_eq!(config.default_team.as_deref(), Some("MOB"));
+        assert_eq!(config.default_project.as_deref(), Some("Platform"));
+        assert_eq!(config.default_labels, ["mobile"]);
+        assert!(matches!(config.format, Some(OutputFormat::Table)));
+        assert_eq!(config.api_url, None);
+    }
+
+    #[test]
+    fn test_profile_names() {
+        assert!(is_valid_profile_name("oss-work_2"));
+        assert!(!is_valid_profile_name(""));
+        assert!(!is_valid_profile_name("../evil"));
+    }
+}
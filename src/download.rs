@@ -0,0 +1,747 @@
+//! Streaming file downloads with bounded concurrency and a progress line.
+//!
+//! Every download directory gets a `manifest.json` recording what was fetched
+//! (source URL, local path, size, sha256, fetch time). Re-runs skip files the
+//! manifest shows are already present, and interrupted downloads resume from
+//! their `.part` file with an HTTP range request. The range is sent with
+//! `If-Range`, so a file changed since then is downloaded again in full.
+
+use std::collections::{BTreeMap, HashSet};
+use std::future::Future;
+use std::io::{IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Utc};
+use futures_util::stream::{self, StreamExt};
+use reqwest::header::{CONTENT_RANGE, ETAG, IF_RANGE, LAST_MODIFIED, RANGE};
+use reqwest::{RequestBuilder, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+
+use crate::cli::OutputFormat;
+use crate::error::{LinearError, Result};
+use crate::output;
+use crate::trace;
+
+pub const MANIFEST_FILE: &str = "manifest.json";
+const MANIFEST_VERSION: u32 = 1;
+
+/// Files fetched at once unless `[network] max_concurrent_downloads` says otherwise
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+const PROGRESS_WIDTH: usize = 100;
+
+/// Run `download` for every item with at most `limit` in flight, keeping input order
+pub async fn bounded<T, F, Fut>(items: Vec<T>, limit: usize, download: F) -> Vec<Fut::Output>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future,
+{
+    stream::iter(items)
+        .map(download)
+        .buffered(limit.max(1))
+        .collect()
+        .await
+}
+
+/// File names for numbered items, with repeats made unique by appending the
+/// item's number (`ENG-1__screenshot_2.png`). Concurrent downloads to one path
+/// would share its `.part` file and manifest entry.
+pub fn unique_filenames(names: Vec<(usize, String)>) -> Vec<String> {
+    // Compared without case, for case-insensitive file systems
+    let mut taken = HashSet::new();
+    names
+        .into_iter()
+        .map(|(index, name)| {
+            let mut unique = name.clone();
+            let mut suffix = index.to_string();
+            while !taken.insert(unique.to_lowercase()) {
+                unique = match name.rsplit_once('.') {
+                    Some((stem, ext)) => format!("{stem}_{suffix}.{ext}"),
+                    None => format!("{name}_{suffix}"),
+                };
+                suffix.push_str("_1");
+            }
+            unique
+        })
+        .collect()
+}
+
+/// Outcome of [`fetch`] for a single file
+pub enum Fetched {
+    Downloaded,
+    /// Already present and recorded in the manifest; no request was made
+    Unchanged,
+}
+
+/// Download `url` to `path`, skipping it when the manifest shows an intact
+/// copy and resuming a previous partial download when one exists.
+///
+/// `request` is a GET for `url` with any auth headers already set;
+/// `status_error` builds the error for a non-success response.
+pub async fn fetch(
+    request: RequestBuilder,
+    url: &str,
+    path: &Path,
+    manifest: &Manifest,
+    progress: &Progress,
+    status_error: impl FnOnce(u16) -> LinearError,
+) -> Result<Fetched> {
+    if manifest.is_unchanged(url, path) {
+        trace::log(format!("{} unchanged, skipping", path.display()));
+        progress.skip();
+        return Ok(Fetched::Unchanged);
+    }
+
+    let part_path = part_path(path);
+    let validator_path = validator_path(path);
+    // A partial file is only resumed if the server can tell whether it changed
+    let resume_from = std::fs::read_to_string(&validator_path)
+        .ok()
+        .and_then(|validator| {
+            let offset = std::fs::metadata(&part_path).ok()?.len();
+            (offset > 0).then_some((offset, validator))
+        });
+    let offset = resume_from.as_ref().map_or(0, |(offset, _)| *offset);
+    let retry = request.try_clone();
+
+    let mut response = send(request, url, resume_from.as_ref()).await?;
+    let mut resume = offset > 0 && resumes_at(&response, offset);
+
+    // The partial file no longer fits the remote one; start over
+    if offset > 0 && response.status() == StatusCode::RANGE_NOT_SATISFIABLE {
+        if let Some(retry) = retry {
+            response = send(retry, url, None).await?;
+            resume = false;
+        }
+    }
+
+    if !response.status().is_success() {
+        return Err(status_error(response.status().as_u16()));
+    }
+
+    // A fresh body: remember what identifies it, for resuming it later
+    if !resume {
+        match validator(&response) {
+            Some(validator) => std::fs::write(&validator_path, validator)?,
+            None => remove_if_present(&validator_path)?,
+        }
+    }
+
+    let (size, sha256) = save_response(response, path, &part_path, resume, progress).await?;
+    remove_if_present(&validator_path)?;
+    manifest.record(url, path, size, sha256)?;
+
+    Ok(Fetched::Downloaded)
+}
+
+/// GET `url`, asking for the rest of the body from `offset` if it is still
+/// the one identified by `validator`
+async fn send(
+    request: RequestBuilder,
+    url: &str,
+    resume_from: Option<&(u64, String)>,
+) -> Result<Response> {
+    let request = match resume_from {
+        Some((offset, validator)) => {
+            trace::log(format!("GET {} range={offset}-", trace::redact_url(url)));
+            request
+                .header(RANGE, format!("bytes={offset}-"))
+                .header(IF_RANGE, validator)
+        }
+        None => {
+            trace::log(format!("GET {}", trace::redact_url(url)));
+            request
+        }
+    };
+
+    let response = request.send().await?;
+    trace::log(format!(
+        "GET {} status={}",
+        trace::redact_url(url),
+        response.status()
+    ));
+    Ok(response)
+}
+
+/// Whether a response continues the body exactly at `offset`
+fn resumes_at(response: &Response, offset: u64) -> bool {
+    response.status() == StatusCode::PARTIAL_CONTENT
+        && response
+            .headers()
+            .get(CONTENT_RANGE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.strip_prefix("bytes "))
+            .and_then(|v| v.split_once('-'))
+            .and_then(|(start, _)| start.parse::<u64>().ok())
+            == Some(offset)
+}
+
+/// What `If-Range` can compare against: a strong ETag, else Last-Modified
+fn validator(response: &Response) -> Option<String> {
+    let header = |name| response.headers().get(name)?.to_str().ok();
+    header(ETAG)
+        .filter(|etag| !etag.starts_with("W/"))
+        .or_else(|| header(LAST_MODIFIED))
+        .map(String::from)
+}
+
+/// Stream a response body into `part_path` (appending when `resume` is set),
+/// then rename it to `path` once the whole body has arrived. Returns the
+/// final size and sha256.
+async fn save_response(
+    mut response: Response,
+    path: &Path,
+    part_path: &Path,
+    resume: bool,
+    progress: &Progress,
+) -> Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut written = 0u64;
+    let mut file = if resume {
+        written = hash_file(part_path, &mut hasher)?;
+        tokio::fs::OpenOptions::new()
+            .append(true)
+            .open(part_path)
+            .await?
+    } else {
+        tokio::fs::File::create(part_path).await?
+    };
+
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let size = response.content_length().map(|len| len + written);
+    let id = progress.start(name, written, size);
+
+    let result: Result<()> = async {
+        while let Some(chunk) = response.chunk().await? {
+            file.write_all(&chunk).await?;
+            hasher.update(&chunk);
+            written += chunk.len() as u64;
+            progress.advance(id, written);
+        }
+        file.flush().await?;
+        drop(file);
+        tokio::fs::rename(part_path, path).await?;
+        Ok(())
+    }
+    .await;
+
+    // A failed transfer keeps its `.part` file so the next run can resume it
+    progress.finish_file(id);
+    result?;
+
+    trace::log(format!("saved {written} bytes to {}", path.display()));
+    Ok((written, format!("{:x}", hasher.finalize())))
+}
+
+/// Hidden partial-download name next to `path`
+fn part_path(path: &Path) -> PathBuf {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    path.with_file_name(format!(".{name}.part"))
+}
+
+/// Where the validator of a partial download is kept, next to its `.part` file
+fn validator_path(path: &Path) -> PathBuf {
+    let mut part = part_path(path).into_os_string();
+    part.push(".validator");
+    PathBuf::from(part)
+}
+
+fn remove_if_present(path: &Path) -> std::io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+/// Feed a file into `hasher`, returning its length
+fn hash_file(path: &Path, hasher: &mut Sha256) -> std::io::Result<u64> {
+    let mut file = std::fs::File::open(path)?;
+    std::io::copy(&mut file, hasher)
+}
+
+/// One downloaded file as recorded in `manifest.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ManifestEntry {
+    pub url: String,
+    /// Path relative to the manifest's directory, with `/` separators
+    pub path: String,
+    pub size: u64,
+    pub sha256: String,
+    pub fetched_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ManifestFile {
+    version: u32,
+    files: Vec<ManifestEntry>,
+}
+
+/// Record of the files downloaded into a directory, rewritten after every
+/// completed download so an interrupted run loses nothing.
+pub struct Manifest {
+    root: PathBuf,
+    entries: Mutex<BTreeMap<String, ManifestEntry>>,
+}
+
+/// Result of checking one manifest entry against the file on disk
+#[derive(Debug, Serialize)]
+pub struct VerifyResult {
+    pub path: String,
+    pub status: VerifyStatus,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VerifyStatus {
+    Ok,
+    Missing,
+    SizeMismatch,
+    ChecksumMismatch,
+}
+
+impl VerifyStatus {
+    pub fn label(self) -> &'static str {
+        match self {
+            VerifyStatus::Ok => "ok",
+            VerifyStatus::Missing => "missing",
+            VerifyStatus::SizeMismatch => "size mismatch",
+            VerifyStatus::ChecksumMismatch => "checksum mismatch",
+        }
+    }
+}
+
+impl Manifest {
+    /// Load the manifest in `root`, or start an empty one
+    pub fn load(root: &Path) -> Result<Self> {
+        let path = root.join(MANIFEST_FILE);
+        let entries = match std::fs::read_to_string(&path) {
+            Ok(contents) => {
+                let file: ManifestFile =
+                    serde_json::from_str(&contents).map_err(|e| LinearError::ManifestInvalid {
+                        path: path.clone(),
+                        message: e.to_string(),
+                    })?;
+                file.files
+                    .into_iter()
+                    .map(|entry| (entry.path.clone(), entry))
+                    .collect()
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => BTreeMap::new(),
+            Err(e) => return Err(e.into()),
+        };
+
+        Ok(Self {
+            root: root.to_path_buf(),
+            entries: Mutex::new(entries),
+        })
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.root.join(MANIFEST_FILE)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, ManifestEntry>> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Manifest key for a file inside (or below) the manifest's directory
+    fn key(&self, path: &Path) -> String {
+        let relative = path.strip_prefix(&self.root).unwrap_or(path);
+        relative
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/")
+    }
+
+    /// Whether `path` was downloaded from `url` and still has the recorded size
+    fn is_unchanged(&self, url: &str, path: &Path) -> bool {
+        let Some(entry) = self.lock().get(&self.key(path)).cloned() else {
+            return false;
+        };
+        entry.url == url && std::fs::metadata(path).is_ok_and(|m| m.len() == entry.size)
+    }
+
+    fn record(&self, url: &str, path: &Path, size: u64, sha256: String) -> Result<()> {
+        let key = self.key(path);
+        let mut entries = self.lock();
+        entries.insert(
+            key.clone(),
+            ManifestEntry {
+                url: url.to_string(),
+                path: key,
+                size,
+                sha256,
+                fetched_at: Utc::now(),
+            },
+        );
+
+        let file = ManifestFile {
+            version: MANIFEST_VERSION,
+            files: entries.values().cloned().collect(),
+        };
+        let contents = serde_json::to_string_pretty(&file)?;
+
+        let path = self.path();
+        let temp = part_path(&path);
+        std::fs::write(&temp, contents)?;
+        std::fs::rename(&temp, &path)?;
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// Re-hash every recorded file and compare it with the manifest
+    pub fn verify(&self) -> Result<Vec<VerifyResult>> {
+        let entries: Vec<ManifestEntry> = self.lock().values().cloned().collect();
+        let mut results = Vec::with_capacity(entries.len());
+
+        for entry in entries {
+            let path = self.root.join(&entry.path);
+            let status = match std::fs::metadata(&path) {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => VerifyStatus::Missing,
+                Err(e) => return Err(e.into()),
+                Ok(meta) if meta.len() != entry.size => VerifyStatus::SizeMismatch,
+                Ok(_) => {
+                    let mut hasher = Sha256::new();
+                    hash_file(&path, &mut hasher)?;
+                    if format!("{:x}", hasher.finalize()) == entry.sha256 {
+                        VerifyStatus::Ok
+                    } else {
+                        VerifyStatus::ChecksumMismatch
+                    }
+                }
+            };
+            results.push(VerifyResult {
+                path: entry.path,
+                status,
+            });
+        }
+
+        Ok(results)
+    }
+}
+
+/// A single stderr status line for a batch of downloads, shown only in table
+/// mode on a terminal.
+pub struct Progress {
+    enabled: bool,
+    state: Mutex<ProgressState>,
+}
+
+struct ProgressState {
+    total: usize,
+    done: usize,
+    next_id: usize,
+    active: Vec<ActiveFile>,
+    last_draw: Option<Instant>,
+}
+
+struct ActiveFile {
+    id: usize,
+    name: String,
+    received: u64,
+    size: Option<u64>,
+}
+
+impl Progress {
+    pub fn new(total: usize) -> Self {
+        let enabled = matches!(output::get_format(), OutputFormat::Table)
+            && !output::is_quiet()
+            && std::io::stderr().is_terminal();
+
+        Self {
+            enabled,
+            state: Mutex::new(ProgressState {
+                total,
+                done: 0,
+                next_id: 0,
+                active: Vec::new(),
+                last_draw: None,
+            }),
+        }
+    }
+
+    fn start(&self, name: String, received: u64, size: Option<u64>) -> usize {
+        let mut state = self.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.active.push(ActiveFile {
+            id,
+            name,
+            received,
+            size,
+        });
+        self.draw(&mut state, true);
+        id
+    }
+
+    fn advance(&self, id: usize, received: u64) {
+        let mut state = self.lock();
+        if let Some(file) = state.active.iter_mut().find(|f| f.id == id) {
+            file.received = received;
+        }
+        self.draw(&mut state, false);
+    }
+
+    /// Count a file that needed no download
+    fn skip(&self) {
+        let mut state = self.lock();
+        state.done += 1;
+        self.draw(&mut state, false);
+    }
+
+    fn finish_file(&self, id: usize) {
+        let mut state = self.lock();
+        state.active.retain(|f| f.id != id);
+        state.done += 1;
+        self.draw(&mut state, true);
+    }
+
+    /// Clear the status line so regular output starts on a clean line
+    pub fn finish(&self) {
+        if self.enabled && self.lock().last_draw.is_some() {
+            eprint!("\r\x1b[2K");
+            let _ = std::io::stderr().flush();
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, ProgressState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn draw(&self, state: &mut ProgressState, force: bool) {
+        if !self.enabled {
+            return;
+        }
+        let now = Instant::now();
+        if !force && state.last_draw.is_some_and(|t| now - t < REDRAW_INTERVAL) {
+            return;
+        }
+        state.last_draw = Some(now);
+
+        let files: Vec<String> = state
+            .active
+            .iter()
+            .map(|f| match f.size {
+                Some(size) => format!(
+                    "{} {}/{}",
+                    f.name,
+                    format_bytes(f.received),
+                    format_bytes(size)
+                ),
+                None => format!("{} {}", f.name, format_bytes(f.received)),
+            })
+            .collect();
+        let line = format!(
+            "Downloading [{}/{}] {}",
+            state.done,
+            state.total,
+            files.join(", ")
+        );
+
+        eprint!("\r\x1b[2K{}", output::truncate(&line, PROGRESS_WIDTH));
+        let _ = std::io::stderr().flush();
+    }
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: &[&str] = &["B", "KB", "MB", "GB"];
+
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{value:.1} {}", UNITS[unit])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn test_format_bytes() {
+        assert_eq!(format_bytes(512), "512 B");
+        assert_eq!(format_bytes(1536), "1.5 KB");
+        assert_eq!(format_bytes(500 * 1024 * 1024), "500.0 MB");
+    }
+
+    #[test]
+    fn test_manifest_records_and_verifies() {
+        let dir = std::env::temp_dir().join(format!("linear-cli-{}-manifest", std::process::id()));
+        let images = dir.join("images");
+        std::fs::create_dir_all(&images).unwrap();
+
+        let path = images.join("ENG-1__a.png");
+        std::fs::write(&path, b"hello").unwrap();
+        let sha256 = format!("{:x}", Sha256::digest(b"hello"));
+
+        let manifest = Manifest::load(&dir).unwrap();
+        manifest
+            .record("https://uploads.linear.app/a", &path, 5, sha256)
+            .unwrap();
+
+        let manifest = Manifest::load(&dir).unwrap();
+        assert!(manifest.is_unchanged("https://uploads.linear.app/a", &path));
+        assert!(!manifest.is_unchanged("https://uploads.linear.app/b", &path));
+        assert_eq!(manifest.verify().unwrap()[0].path, "images/ENG-1__a.png");
+        assert_eq!(manifest.verify().unwrap()[0].status, VerifyStatus::Ok);
+
+        std::fs::write(&path, b"jello").unwrap();
+        assert_eq!(
+            manifest.verify().unwrap()[0].status,
+            VerifyStatus::ChecksumMismatch
+        );
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(manifest.verify().unwrap()[0].status, VerifyStatus::Missing);
+        assert!(!manifest.is_unchanged("https://uploads.linear.app/a", &path));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_unique_filenames() {
+        let names = unique_filenames(vec![
+            (1, "ENG-1__screenshot.png".to_string()),
+            (2, "ENG-1__Screenshot.png".to_string()),
+            (3, "ENG-1__log".to_string()),
+            (4, "ENG-1__log".to_string()),
+            (5, "ENG-1__other.png".to_string()),
+        ]);
+        assert_eq!(
+            names,
+            [
+                "ENG-1__screenshot.png",
+                "ENG-1__Screenshot_2.png",
+                "ENG-1__log",
+                "ENG-1__log_4",
+                "ENG-1__other.png",
+            ]
+        );
+    }
+
+    /// Serve `body` with `etag` on a local port, honouring a range only when
+    /// `If-Range` matches. Returns the URL and each request's head.
+    async fn serve(body: &'static [u8], etag: &'static str) -> (String, Arc<Mutex<Vec<String>>>) {
+        use tokio::io::AsyncReadExt;
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/file", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let seen = requests.clone();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut buffer = vec![0; 4096];
+                let read = stream.read(&mut buffer).await.unwrap();
+                let head = String::from_utf8_lossy(&buffer[..read]).to_lowercase();
+                let header = |name: &str| {
+                    head.lines()
+                        .find_map(|line| line.strip_prefix(&format!("{name}: ")))
+                        .map(str::to_string)
+                };
+                let offset = match (header("range"), header("if-range")) {
+                    (Some(range), Some(if_range)) if if_range == etag => range
+                        .trim_start_matches("bytes=")
+                        .trim_end_matches('-')
+                        .parse::<usize>()
+                        .unwrap(),
+                    _ => 0,
+                };
+                seen.lock().unwrap().push(head.clone());
+
+                let status = if offset > 0 {
+                    format!(
+                        "206 Partial Content\r\ncontent-range: bytes {offset}-{}/{}",
+                        body.len() - 1,
+                        body.len()
+                    )
+                } else {
+                    "200 OK".to_string()
+                };
+                let response = format!(
+                    "HTTP/1.1 {status}\r\netag: {etag}\r\ncontent-length: {}\r\nconnection: close\r\n\r\n",
+                    body.len() - offset
+                );
+                stream.write_all(response.as_bytes()).await.unwrap();
+                stream.write_all(&body[offset..]).await.unwrap();
+            }
+        });
+        (url, requests)
+    }
+
+    #[tokio::test]
+    async fn test_resume_only_continues_an_unchanged_file() {
+        let (url, requests) = serve(b"new body", "\"v2\"").await;
+        let dir = std::env::temp_dir().join(format!("linear-cli-{}-resume", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest = Manifest::load(&dir).unwrap();
+        let progress = Progress::new(2);
+        let http = reqwest::Client::new();
+        let download = |path: PathBuf| {
+            let request = http.get(&url);
+            let (url, manifest, progress) = (&url, &manifest, &progress);
+            async move {
+                fetch(request, url, &path, manifest, progress, |status| {
+                    LinearError::ImageDownloadFailed {
+                        url: url.clone(),
+                        status,
+                    }
+                })
+                .await
+                .unwrap();
+                std::fs::read(&path).unwrap()
+            }
+        };
+
+        // Left over from an older version of the file: fetched again in full
+        let changed = dir.join("changed.txt");
+        std::fs::write(part_path(&changed), b"old ").unwrap();
+        std::fs::write(validator_path(&changed), "\"v1\"").unwrap();
+        assert_eq!(download(changed.clone()).await, b"new body");
+        assert!(!validator_path(&changed).exists());
+
+        // Left over from this version: only the rest is fetched
+        let unchanged = dir.join("unchanged.txt");
+        std::fs::write(part_path(&unchanged), b"new ").unwrap();
+        std::fs::write(validator_path(&unchanged), "\"v2\"").unwrap();
+        assert_eq!(download(unchanged).await, b"new body");
+
+        let requests = requests.lock().unwrap();
+        assert!(requests[0].contains("if-range: \"v1\""));
+        assert!(requests[1].contains("range: bytes=4-"));
+        drop(requests);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_bounded_keeps_order() {
+        let results = bounded(vec![3u64, 1, 2], 2, |n| async move {
+            tokio::time::sleep(Duration::from_millis(n * 5)).await;
+            n
+        })
+        .await;
+        assert_eq!(results, [3, 1, 2]);
+    }
+}
@@ -1,4 +1,7 @@
+use std::fmt;
 use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -15,8 +18,11 @@ pub enum LinearError {
     #[error("API error (status {status}): {message}")]
     ApiError { status: u16, message: String },
 
-    #[error("GraphQL errors: {}", messages.join(", "))]
-    GraphQL { messages: Vec<String> },
+    #[error("GraphQL errors: {}", errors.iter().map(ToString::to_string).collect::<Vec<_>>().join(", "))]
+    GraphQL { errors: Vec<GraphQLError> },
+
+    #[error("Rate limited by the Linear API; try again later")]
+    RateLimited,
 
     #[error("Empty response from API")]
     EmptyResponse,
@@ -35,14 +41,53 @@ pub enum LinearError {
         source: toml::de::Error,
     },
 
+    #[error("Failed to update config file at {path}: {message}")]
+    ConfigEdit { path: PathBuf, message: String },
+
+    #[error("Unknown config key '{key}'{hint}")]
+    UnknownConfigKey { key: String, hint: String },
+
+    #[error("Invalid value for {key}: {message}")]
+    InvalidConfigValue { key: String, message: String },
+
+    #[error("Alias '{name}' {message}")]
+    InvalidAlias { name: String, message: String },
+
     #[error("Could not determine config directory")]
     NoConfigDir,
 
     #[error(
-        "No API key found. Set LINEAR_API_KEY env var or add api_key to ~/.config/linear/config.toml"
+        "No API key found. Set LINEAR_API_KEY env var, run `linear init` or `linear auth login`, or set api_key_command in ~/.config/linear/config.toml"
     )]
     MissingApiKey,
 
+    #[error("api_key_command failed: {0}")]
+    ApiKeyCommand(String),
+
+    #[error("Keyring error: {0}")]
+    Keyring(#[from] keyring::Error),
+
+    #[error("OAuth error: {0}")]
+    OAuth(String),
+
+    #[error("OAuth session expired. Run `linear auth login` again")]
+    OAuthExpired,
+
+    #[error("Login timed out waiting for the browser. Run `linear auth login` again")]
+    OAuthTimeout,
+
+    #[error("OAuth client ID not configured. Set LINEAR_OAUTH_CLIENT_ID or oauth.client_id in config.toml")]
+    OAuthNotConfigured,
+
+    #[error("Profile '{0}' not found in config.toml")]
+    UnknownProfile(String),
+
+    #[error("View '{0}' not found in config.toml. Run `linear view list` to see saved views")]
+    UnknownView(String),
+
+    #[error("Invalid profile name '{0}' (use letters, digits, '-' and '_')")]
+    InvalidProfileName(String),
+
     #[error("Team not specified and no default_team in config")]
     NoTeam,
 
@@ -103,6 +148,417 @@ pub enum LinearError {
 
     #[error("Label not found: {0}")]
     LabelNotFound(String),
+
+    #[error("Project not found: {0}")]
+    ProjectNotFound(String),
+
+    #[error("User not found: {0}")]
+    UserNotFound(String),
+
+    #[error("Failed to read cassette file at {path}: {source}")]
+    CassetteRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("No recorded response for operation {operation} in cassette {path}")]
+    CassetteMismatch { path: PathBuf, operation: String },
+
+    #[error("Invalid cassette mode '{0}' (expected 'record' or 'replay')")]
+    InvalidCassetteMode(String),
+
+    #[error("Failed to read CA certificate {path}: {source}")]
+    CaCertRead {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid network configuration: {0}")]
+    NetworkConfig(String),
+
+    #[error("Invalid api request: {0}")]
+    ApiUsage(String),
+
+    #[error("Invalid download manifest {path}: {message}")]
+    ManifestInvalid { path: PathBuf, message: String },
+
+    #[error("{failed} of {total} items failed to download")]
+    PartialFailure { failed: usize, total: usize },
+
+    #[error("{failed} of {total} files failed verification")]
+    VerifyFailed { failed: usize, total: usize },
+
+    #[error("Offline mirror error: {0}")]
+    Mirror(#[from] rusqlite::Error),
+
+    #[error("Offline mirror unavailable: {0}. Run `linear sync --team <KEY>` first")]
+    NotSynced(String),
+
+    #[error("Nothing to search for in '{0}'")]
+    InvalidSearch(String),
+}
+
+/// Broad class of failure, mapped to a stable process exit code.
+///
+/// | Code | Class          |
+/// |------|----------------|
+/// | 1    | general        |
+/// | 2    | usage (clap)   |
+/// | 3    | config / auth  |
+/// | 4    | not found      |
+/// | 5    | validation     |
+/// | 6    | network        |
+/// | 7    | rate limited   |
+/// | 8    | partial failure|
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorClass {
+    General,
+    Config,
+    NotFound,
+    Validation,
+    Network,
+    RateLimited,
+    PartialFailure,
+}
+
+impl ErrorClass {
+    pub fn exit_code(self) -> i32 {
+        match self {
+            ErrorClass::General => 1,
+            ErrorClass::Config => 3,
+            ErrorClass::NotFound => 4,
+            ErrorClass::Validation => 5,
+            ErrorClass::Network => 6,
+            ErrorClass::RateLimited => 7,
+            ErrorClass::PartialFailure => 8,
+        }
+    }
+}
+
+impl LinearError {
+    pub fn class(&self) -> ErrorClass {
+        match self {
+            LinearError::ConfigRead { .. }
+            | LinearError::ConfigParse { .. }
+            | LinearError::ConfigEdit { .. }
+            | LinearError::NoConfigDir
+            | LinearError::MissingApiKey
+            | LinearError::ApiKeyCommand(_)
+            | LinearError::Keyring(_)
+            | LinearError::OAuth(_)
+            | LinearError::OAuthExpired
+            | LinearError::OAuthTimeout
+            | LinearError::OAuthNotConfigured
+            | LinearError::UnknownProfile(_)
+            | LinearError::UnknownView(_)
+            | LinearError::NoTeam
+            | LinearError::NotSynced(_)
+            | LinearError::CaCertRead { .. }
+            | LinearError::NetworkConfig(_) => ErrorClass::Config,
+
+            LinearError::IssueNotFound(_)
+            | LinearError::CycleNotFound(_)
+            | LinearError::TeamNotFound(_)
+            | LinearError::WorkflowStateNotFound(_)
+            | LinearError::FileNotFound(_)
+            | LinearError::OutputDirNotFound(_)
+            | LinearError::NoAttachments(_)
+            | LinearError::RelationNotFound(_, _)
+            | LinearError::CommentNotFound { .. }
+            | LinearError::LabelNotFound(_)
+            | LinearError::ProjectNotFound(_)
+            | LinearError::UserNotFound(_) => ErrorClass::NotFound,
+
+            LinearError::InvalidUrl(_)
+            | LinearError::ImageIndexOutOfBounds { .. }
+            | LinearError::AttachmentIndexOutOfBounds { .. }
+            | LinearError::InvalidCassetteMode(_)
+            | LinearError::InvalidProfileName(_)
+            | LinearError::ManifestInvalid { .. }
+            | LinearError::InvalidSearch(_)
+            | LinearError::UnknownConfigKey { .. }
+            | LinearError::InvalidConfigValue { .. }
+            | LinearError::InvalidAlias { .. }
+            | LinearError::ApiUsage(_) => ErrorClass::Validation,
+
+            LinearError::Http(_)
+            | LinearError::UploadFailed { .. }
+            | LinearError::ImageDownloadFailed { .. }
+            | LinearError::AttachmentDownloadFailed { .. } => ErrorClass::Network,
+
+            LinearError::RateLimited => ErrorClass::RateLimited,
+
+            LinearError::PartialFailure { .. } | LinearError::VerifyFailed { .. } => {
+                ErrorClass::PartialFailure
+            }
+
+            LinearError::ApiError { status, .. } => match status {
+                401 | 403 => ErrorClass::Config,
+                404 => ErrorClass::NotFound,
+                429 => ErrorClass::RateLimited,
+                400 | 422 => ErrorClass::Validation,
+                s if *s >= 500 => ErrorClass::Network,
+                _ => ErrorClass::General,
+            },
+
+            LinearError::GraphQL { .. } => match self.graphql_kind() {
+                Some(GraphQLErrorKind::Authentication | GraphQLErrorKind::Forbidden) => {
+                    ErrorClass::Config
+                }
+                Some(GraphQLErrorKind::NotFound) => ErrorClass::NotFound,
+                Some(GraphQLErrorKind::InvalidInput) => ErrorClass::Validation,
+                Some(GraphQLErrorKind::RateLimited) => ErrorClass::RateLimited,
+                Some(GraphQLErrorKind::Other) | None => ErrorClass::General,
+            },
+
+            LinearError::Io(_)
+            | LinearError::Json(_)
+            | LinearError::EmptyResponse
+            | LinearError::FileRead { .. }
+            | LinearError::MutationFailed(_)
+            | LinearError::CassetteRead { .. }
+            | LinearError::CassetteMismatch { .. }
+            | LinearError::Mirror(_) => ErrorClass::General,
+        }
+    }
+
+    /// Whether the server rejected our credentials
+    pub fn is_authentication_failure(&self) -> bool {
+        matches!(self, LinearError::ApiError { status: 401, .. })
+            || self.graphql_kind() == Some(GraphQLErrorKind::Authentication)
+    }
+
+    /// Process exit code for this error
+    pub fn exit_code(&self) -> i32 {
+        self.class().exit_code()
+    }
+
+    /// The kind of the first GraphQL error, if this is a GraphQL error
+    pub fn graphql_kind(&self) -> Option<GraphQLErrorKind> {
+        match self {
+            LinearError::GraphQL { errors } => errors.first().map(GraphQLError::kind),
+            _ => None,
+        }
+    }
+
+    /// Suggested next step for the user, if there is an obvious one
+    pub fn hint(&self) -> Option<&'static str> {
+        match self.graphql_kind()? {
+            GraphQLErrorKind::Authentication => {
+                Some("Check your API key (LINEAR_API_KEY or api_key in config.toml)")
+            }
+            GraphQLErrorKind::Forbidden => {
+                Some("Your API key does not have access to this resource")
+            }
+            GraphQLErrorKind::NotFound => {
+                Some("Check the identifier; it may have been deleted or moved")
+            }
+            GraphQLErrorKind::RateLimited => Some("Wait a moment and try again"),
+            GraphQLErrorKind::InvalidInput | GraphQLErrorKind::Other => None,
+        }
+    }
+
+    /// Whether retrying the same request may succeed.
+    ///
+    /// Mutations are only retried when the request certainly wasn't applied
+    /// (rate limiting or a failed connection), to avoid duplicating writes.
+    pub fn is_retryable(&self, is_mutation: bool) -> bool {
+        match self {
+            LinearError::RateLimited => true,
+            LinearError::ApiError { status: 429, .. } => true,
+            LinearError::ApiError { status, .. } => *status >= 500 && !is_mutation,
+            LinearError::Http(e) => e.is_connect() || (!is_mutation && e.is_timeout()),
+            _ => false,
+        }
+    }
+}
+
+/// A single entry from a GraphQL response's `errors` array.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GraphQLError {
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<Vec<serde_json::Value>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extensions: Option<GraphQLErrorExtensions>,
+}
+
+/// Linear-specific error details.
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct GraphQLErrorExtensions {
+    pub code: Option<String>,
+    #[serde(rename = "type")]
+    pub error_type: Option<String>,
+    #[serde(rename = "userPresentableMessage")]
+    pub user_presentable_message: Option<String>,
+    #[serde(rename = "userError")]
+    pub user_error: Option<bool>,
+    #[serde(rename = "statusCode")]
+    pub status_code: Option<u16>,
+}
+
+/// Broad class of a GraphQL error, for deciding how to react to it.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphQLErrorKind {
+    Authentication,
+    Forbidden,
+    NotFound,
+    InvalidInput,
+    RateLimited,
+    Other,
+}
+
+impl GraphQLError {
+    /// Message suitable for end users, preferring Linear's presentable text
+    pub fn display_message(&self) -> &str {
+        self.extensions
+            .as_ref()
+            .and_then(|ext| ext.user_presentable_message.as_deref())
+            .unwrap_or(&self.message)
+    }
+
+    /// Classify using the extension code/type, falling back to status code and message
+    pub fn kind(&self) -> GraphQLErrorKind {
+        let ext = self.extensions.as_ref();
+        let normalize = |s: &str| s.to_lowercase().replace(['_', ' ', '-'], "");
+
+        let tags = ext
+            .into_iter()
+            .flat_map(|e| [e.code.as_deref(), e.error_type.as_deref()])
+            .flatten()
+            .map(normalize);
+
+        for tag in tags {
+            match tag.as_str() {
+                "authenticationerror" | "unauthenticated" => {
+                    return GraphQLErrorKind::Authentication
+                }
+                "forbidden" | "featurenotaccessible" => return GraphQLErrorKind::Forbidden,
+                "entitynotfound" | "notfound" => return GraphQLErrorKind::NotFound,
+                "ratelimited" => return GraphQLErrorKind::RateLimited,
+                "invalidinput" | "baduserinput" | "graphqlvalidationfailed" => {
+                    // Linear reports missing entities as invalid input
+                    if self.message.starts_with("Entity not found") {
+                        return GraphQLErrorKind::NotFound;
+                    }
+                    return GraphQLErrorKind::InvalidInput;
+                }
+                _ => {}
+            }
+        }
+
+        match ext.and_then(|e| e.status_code) {
+            Some(401) => GraphQLErrorKind::Authentication,
+            Some(403) => GraphQLErrorKind::Forbidden,
+            Some(404) => GraphQLErrorKind::NotFound,
+            Some(429) => GraphQLErrorKind::RateLimited,
+            _ if self.message.starts_with("Entity not found") => GraphQLErrorKind::NotFound,
+            _ => GraphQLErrorKind::Other,
+        }
+    }
+}
+
+impl fmt::Display for GraphQLError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.display_message())?;
+
+        if let Some(path) = &self.path {
+            let path: Vec<String> = path
+                .iter()
+                .map(|p| match p {
+                    serde_json::Value::String(s) => s.clone(),
+                    other => other.to_string(),
+                })
+                .collect();
+            write!(f, " (at {})", path.join("."))?;
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for GraphQLErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            GraphQLErrorKind::Authentication => "authentication",
+            GraphQLErrorKind::Forbidden => "forbidden",
+            GraphQLErrorKind::NotFound => "not found",
+            GraphQLErrorKind::InvalidInput => "invalid input",
+            GraphQLErrorKind::RateLimited => "rate limited",
+            GraphQLErrorKind::Other => "other",
+        };
+        write!(f, "{label}")
+    }
 }
 
 pub type Result<T> = std::result::Result<T, LinearError>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(json: serde_json::Value) -> GraphQLError {
+        serde_json::from_value(json).unwrap()
+    }
+
+    #[test]
+    fn test_kind_from_extensions() {
+        let auth = error(serde_json::json!({
+            "message": "Authentication required, not authenticated",
+            "extensions": { "type": "authentication error", "code": "AUTHENTICATION_ERROR" }
+        }));
+        assert_eq!(auth.kind(), GraphQLErrorKind::Authentication);
+
+        let not_found = error(serde_json::json!({
+            "message": "Entity not found: Issue",
+            "extensions": { "type": "invalid input", "code": "INPUT_ERROR" }
+        }));
+        assert_eq!(not_found.kind(), GraphQLErrorKind::NotFound);
+
+        let invalid = error(serde_json::json!({
+            "message": "Argument Validation Error",
+            "extensions": { "type": "invalid input" }
+        }));
+        assert_eq!(invalid.kind(), GraphQLErrorKind::InvalidInput);
+
+        let plain = error(serde_json::json!({ "message": "Something broke" }));
+        assert_eq!(plain.kind(), GraphQLErrorKind::Other);
+    }
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(LinearError::MissingApiKey.exit_code(), 3);
+        assert_eq!(LinearError::IssueNotFound("ENG-1".into()).exit_code(), 4);
+        assert_eq!(LinearError::InvalidUrl("x".into()).exit_code(), 5);
+        assert_eq!(LinearError::RateLimited.exit_code(), 7);
+        assert_eq!(
+            LinearError::PartialFailure {
+                failed: 1,
+                total: 3
+            }
+            .exit_code(),
+            8
+        );
+
+        let not_found = LinearError::GraphQL {
+            errors: vec![error(
+                serde_json::json!({ "message": "Entity not found: Issue" }),
+            )],
+        };
+        assert_eq!(not_found.exit_code(), 4);
+    }
+
+    #[test]
+    fn test_display_prefers_user_presentable_message() {
+        let err = error(serde_json::json!({
+            "message": "Argument Validation Error",
+            "path": ["issueUpdate", 0],
+            "extensions": { "userPresentableMessage": "Title is too long." }
+        }));
+        assert_eq!(err.to_string(), "Title is too long. (at issueUpdate.0)");
+    }
+}
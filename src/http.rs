@@ -0,0 +1,60 @@
+//! Shared HTTP client construction from network configuration.
+
+use std::time::Duration;
+
+use reqwest::{Certificate, Client, NoProxy, Proxy};
+
+use crate::config::NetworkConfig;
+use crate::error::{LinearError, Result};
+
+pub(crate) const DEFAULT_CONNECT_TIMEOUT_SECS: u64 = 10;
+/// Max idle time between reads; catches hung downloads without capping large ones
+pub(crate) const DEFAULT_READ_TIMEOUT_SECS: u64 = 60;
+pub(crate) const DEFAULT_USER_AGENT: &str = concat!("linear-cli/", env!("CARGO_PKG_VERSION"));
+
+/// Build the HTTP client used for API requests, downloads and uploads.
+///
+/// Without an explicit `proxy`, the standard `HTTPS_PROXY`/`HTTP_PROXY`/`NO_PROXY`
+/// environment variables are honored.
+pub fn build_client(network: &NetworkConfig) -> Result<Client> {
+    let mut builder = Client::builder()
+        .user_agent(network.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT))
+        .connect_timeout(Duration::from_secs(
+            network
+                .connect_timeout_secs
+                .unwrap_or(DEFAULT_CONNECT_TIMEOUT_SECS),
+        ))
+        .read_timeout(Duration::from_secs(
+            network
+                .read_timeout_secs
+                .unwrap_or(DEFAULT_READ_TIMEOUT_SECS),
+        ));
+
+    if let Some(secs) = network.timeout_secs {
+        builder = builder.timeout(Duration::from_secs(secs));
+    }
+
+    if let Some(proxy_url) = &network.proxy {
+        let proxy = Proxy::all(proxy_url)
+            .map_err(|e| LinearError::NetworkConfig(format!("invalid proxy {proxy_url}: {e}")))?
+            .no_proxy(network.no_proxy.as_deref().and_then(NoProxy::from_string));
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &network.ca_certs {
+        let pem = std::fs::read(path).map_err(|e| LinearError::CaCertRead {
+            path: path.clone(),
+            source: e,
+        })?;
+        let certs = Certificate::from_pem_bundle(&pem).map_err(|e| {
+            LinearError::NetworkConfig(format!("invalid CA bundle {}: {e}", path.display()))
+        })?;
+        for cert in certs {
+            builder = builder.add_root_certificate(cert);
+        }
+    }
+
+    builder
+        .build()
+        .map_err(|e| LinearError::NetworkConfig(e.to_string()))
+}
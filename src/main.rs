@@ -1,48 +1,75 @@
+mod alias;
+mod auth;
 mod cache;
+mod cassette;
 mod cli;
 mod client;
 mod commands;
 mod config;
+mod download;
 mod error;
+mod http;
+mod mirror;
 mod output;
+mod pagination;
+mod rate_limit;
 mod responses;
+#[cfg(test)]
+mod schema;
+mod secret;
+mod trace;
 mod types;
 
 use std::io;
 
-use clap::{CommandFactory, Parser};
+use clap::{CommandFactory, FromArgMatches};
 use clap_complete::generate;
 
-use cli::{AttachmentCommands, Cli, Commands, CycleCommands, ImageCommands, IssueCommands};
+use auth::Credentials;
+use cassette::{Cassette, CassetteMode};
+use cli::{
+    AttachmentCommands, AuthCommands, CacheCommands, Cli, Commands, ConfigCommands, CycleCommands,
+    ImageCommands, IssueCommands, ViewCommands,
+};
 use client::LinearClient;
 use config::Config;
-use error::Result;
-use std::error::Error;
+use error::{LinearError, Result};
 
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     if let Err(e) = run().await {
-        eprintln!("Error: {e}");
-
         // Show error chain if verbose flag was passed
-        if std::env::args().any(|arg| arg == "--verbose" || arg == "-v") {
-            let mut source = e.source();
-            while let Some(cause) = source {
-                eprintln!("Caused by: {cause}");
-                source = std::error::Error::source(cause);
-            }
-        }
+        let verbose = std::env::args().any(|arg| arg == "--verbose" || arg == "-v");
+        output::print_error(&e, verbose);
 
-        std::process::exit(1);
+        std::process::exit(e.exit_code());
     }
 }
 
 async fn run() -> Result<()> {
-    let cli = Cli::parse();
+    let args: Vec<_> = std::env::args_os().collect();
+    // Errors before the config is read still honour --format
+    output::set_format(Cli::raw_format_flag(&args).unwrap_or_default());
+
+    let aliases = config::aliases();
+    let args = alias::expand(args, &aliases)?;
+    let matches = alias::command(&aliases).get_matches_from(args);
+    let cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
 
-    // Set global output format
-    output::set_format(cli.output_format());
     output::set_quiet(cli.quiet);
+    config::set_profile(cli.profile.clone())?;
+
+    // Commands that don't need the config still run when it's broken
+    let config = Config::load();
+
+    // Set global output format
+    let format_flag = cli.format_flag();
+    let config_format = config.as_ref().ok().and_then(|c| c.format);
+    output::set_format(format_flag.or(config_format).unwrap_or_default());
+
+    if cli.debug || cli.debug_log.is_some() {
+        trace::init(cli.debug_log.as_deref())?;
+    }
 
     match cli.command {
         // Commands that don't require config/client
@@ -50,20 +77,88 @@ async fn run() -> Result<()> {
             let mut cmd = Cli::command();
             generate(shell, &mut cmd, "linear", &mut io::stdout());
         }
-        Commands::Init => {
-            commands::init::run().await?;
+        Commands::Init(args) => {
+            commands::init::run(args).await?;
+        }
+        // Verifying a download only reads local files
+        Commands::Issue {
+            action: IssueCommands::Download(args),
+        } if args.verify => {
+            commands::issues::verify_download(&args)?;
+        }
+        // Clearing the whole cache doesn't depend on the workspace
+        Commands::Cache {
+            action: CacheCommands::Clear { entities },
+        } if entities.is_empty() => {
+            commands::cache::clear(&entities)?;
+        }
+        Commands::Config { action } => {
+            let flags = commands::config::Flags::new(format_flag);
+            match action {
+                ConfigCommands::Get { key } => commands::config::get(&key, &flags)?,
+                ConfigCommands::Set { key, value } => commands::config::set(&key, &value)?,
+                ConfigCommands::Unset { key } => commands::config::unset(&key)?,
+                ConfigCommands::List => commands::config::list(&flags)?,
+                ConfigCommands::Edit => commands::config::edit()?,
+                ConfigCommands::Path => commands::config::path()?,
+            }
+        }
+        Commands::View { action } => {
+            let config = config?;
+            match action {
+                ViewCommands::List => commands::views::list(&config)?,
+                ViewCommands::Show { name } => commands::views::show(&config, &name)?,
+                ViewCommands::Save(args) => commands::views::save(args)?,
+                ViewCommands::Delete { name } => commands::views::delete(&name)?,
+            }
+        }
+        Commands::Auth { action } => {
+            let config = config?;
+            match action {
+                AuthCommands::Login => commands::auth::login(&config).await?,
+                AuthCommands::Status => commands::auth::status(&config).await?,
+                AuthCommands::Logout => commands::auth::logout(&config).await?,
+            }
         }
         // Commands that require config and client
         command => {
-            let config = Config::load()?;
-            let client = LinearClient::new(config.api_key()?);
+            let config = config?;
+            let cassette = Cassette::from_env()?;
+
+            // Replaying a cassette never hits the network, so no key is needed
+            let replaying = cassette
+                .as_ref()
+                .is_some_and(|c| c.mode() == CassetteMode::Replay);
+            let mut credentials = match config.credentials() {
+                Err(LinearError::MissingApiKey) if replaying => Credentials::ApiKey(String::new()),
+                result => result?,
+            };
+
+            // Logins from before tokens recorded their workspace
+            if matches!(&credentials, Credentials::OAuth(t) if t.organization_id.is_none())
+                && !replaying
+            {
+                let client = LinearClient::from_config(credentials.clone(), &config)?;
+                match commands::auth::store_organization(&client).await {
+                    Ok(tokens) => credentials = Credentials::OAuth(tokens),
+                    Err(e) => eprintln!(
+                        "Warning: could not look up the OAuth workspace ({e}); the cache is off"
+                    ),
+                }
+            }
+            cache::set_workspace(&credentials, config.api_url().as_deref());
+
+            let mut client = LinearClient::from_config(credentials, &config)?;
+            if let Some(cassette) = cassette {
+                client = client.with_cassette(cassette);
+            }
 
             match command {
-                Commands::Teams => {
-                    commands::teams::list(&client).await?;
+                Commands::Teams { page } => {
+                    commands::teams::list(&client, page.page_limit()).await?;
                 }
-                Commands::Projects { team } => {
-                    commands::projects::list(&client, &config, team).await?;
+                Commands::Projects { team, page } => {
+                    commands::projects::list(&client, &config, team, page.page_limit()).await?;
                 }
                 Commands::Cycles(args) => {
                     commands::cycles::list(&client, &config, args).await?;
@@ -79,8 +174,27 @@ async fn run() -> Result<()> {
                 Commands::Issues(args) => {
                     commands::issues::list(&client, &config, args).await?;
                 }
-                Commands::Labels { team } => {
-                    commands::labels::list(&client, &config, team).await?;
+                Commands::Labels { team, page } => {
+                    commands::labels::list(&client, &config, team, page.page_limit()).await?;
+                }
+                Commands::Api(args) => {
+                    commands::api::run(&client, args).await?;
+                }
+                Commands::Cache { action } => match action {
+                    CacheCommands::Status => commands::cache::status(),
+                    CacheCommands::Refresh { entities } => {
+                        commands::cache::refresh(&client, &entities).await?;
+                    }
+                    CacheCommands::Clear { entities } => commands::cache::clear(&entities)?,
+                },
+                Commands::Search(args) => {
+                    commands::search::run(&config, args)?;
+                }
+                Commands::Sync(args) => {
+                    commands::sync::run(&client, &config, args).await?;
+                }
+                Commands::Limits => {
+                    commands::limits::show(&client).await?;
                 }
                 Commands::Issue { action } => match action {
                     IssueCommands::List(args) => {
@@ -107,8 +221,8 @@ async fn run() -> Result<()> {
                         commands::issues::close(&client, &id).await?;
                     }
                     IssueCommands::Attachments { action } => match action {
-                        AttachmentCommands::List { id } => {
-                            commands::attachments::list(&client, &id).await?;
+                        AttachmentCommands::List { id, page } => {
+                            commands::attachments::list(&client, &id, page.page_limit()).await?;
                         }
                         AttachmentCommands::Download(args) => {
                             commands::attachments::download(&client, args).await?;
@@ -120,8 +234,12 @@ async fn run() -> Result<()> {
                             commands::attachments::upload_file(&client, args).await?;
                         }
                     },
-                    IssueCommands::Comments { id } => {
-                        commands::comments::list(&client, &id).await?;
+                    IssueCommands::Comments { id, offline, page } => {
+                        if offline {
+                            commands::comments::list_offline(&id, page.page_limit())?;
+                        } else {
+                            commands::comments::list(&client, &id, page.page_limit()).await?;
+                        }
                     }
                     IssueCommands::Comment(args) => {
                         if let Some(index) = args.edit {
@@ -130,8 +248,8 @@ async fn run() -> Result<()> {
                             commands::comments::add(&client, args).await?;
                         }
                     }
-                    IssueCommands::Relations { id } => {
-                        commands::relations::list(&client, &id).await?;
+                    IssueCommands::Relations { id, page } => {
+                        commands::relations::list(&client, &id, page.page_limit()).await?;
                     }
                     IssueCommands::Relate(args) => {
                         commands::relations::relate(&client, args).await?;
@@ -145,8 +263,8 @@ async fn run() -> Result<()> {
                     IssueCommands::Unparent { id } => {
                         commands::relations::remove_parent(&client, &id).await?;
                     }
-                    IssueCommands::Labels { id } => {
-                        commands::labels::list_for_issue(&client, &id).await?;
+                    IssueCommands::Labels { id, page } => {
+                        commands::labels::list_for_issue(&client, &id, page.page_limit()).await?;
                     }
                     IssueCommands::Label(args) => {
                         commands::labels::add_label(&client, &args.id, &args.label).await?;
@@ -155,7 +273,11 @@ async fn run() -> Result<()> {
                         commands::labels::remove_label(&client, &args.id, &args.label).await?;
                     }
                 },
-                Commands::Completions { .. } | Commands::Init => {
+                Commands::Completions { .. }
+                | Commands::Init(_)
+                | Commands::Auth { .. }
+                | Commands::Config { .. }
+                | Commands::View { .. } => {
                     // Already handled above
                 }
             }
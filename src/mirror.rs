@@ -0,0 +1,644 @@
+//! Local SQLite copy of issues, comments and related entities, filled by
+//! `linear sync` and read by commands run with `--offline`.
+
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use rusqlite::{params, Connection, OptionalExtension, TransactionBehavior};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use crate::cache;
+use crate::commands::comments::Comment;
+use crate::config::Config;
+use crate::error::{LinearError, Result};
+use crate::types::Issue;
+
+/// Schema version, kept in SQLite's `user_version`
+const MIRROR_VERSION: i32 = 2;
+
+/// Version 1: issues, comments and supporting entities
+const SCHEMA_V1: &str = "
+CREATE TABLE teams (
+    key TEXT PRIMARY KEY,
+    id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    issues_synced_through TEXT,
+    comments_synced_through TEXT,
+    synced_at TEXT NOT NULL
+);
+CREATE TABLE issues (
+    id TEXT PRIMARY KEY,
+    identifier TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    team_key TEXT NOT NULL,
+    updated_at TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX issues_by_team ON issues (team_key, updated_at);
+CREATE TABLE comments (
+    id TEXT PRIMARY KEY,
+    issue_id TEXT NOT NULL,
+    team_key TEXT NOT NULL,
+    created_at TEXT NOT NULL,
+    data TEXT NOT NULL
+);
+CREATE INDEX comments_by_issue ON comments (issue_id, created_at);
+CREATE TABLE entities (
+    kind TEXT NOT NULL,
+    id TEXT NOT NULL,
+    team_key TEXT,
+    name TEXT NOT NULL,
+    data TEXT NOT NULL,
+    PRIMARY KEY (kind, team_key, id)
+);
+CREATE TABLE meta (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+";
+
+/// Version 2: full-text index over issues, one row per issue keyed by its rowid
+const SCHEMA_V2: &str = "
+CREATE VIRTUAL TABLE issue_search USING fts5 (
+    identifier,
+    title,
+    description,
+    label,
+    comment,
+    tokenize = 'porter unicode61 remove_diacritics 2'
+);
+";
+
+/// Drop the search row of the issue `?1` (every issue if NULL)
+const UNINDEX_ISSUES: &str = "
+DELETE FROM issue_search
+WHERE ?1 IS NULL OR rowid IN (SELECT rowid FROM issues WHERE id = ?1)
+";
+
+/// Build the search row of the issue `?1` (every issue if NULL) from the
+/// stored JSON and comments
+const INDEX_ISSUES: &str = "
+INSERT INTO issue_search (rowid, identifier, title, description, label, comment)
+SELECT
+    i.rowid,
+    i.identifier,
+    json_extract(i.data, '$.title'),
+    json_extract(i.data, '$.description'),
+    (SELECT group_concat(json_extract(l.value, '$.name'), ' ')
+     FROM json_each(i.data, '$.labels.nodes') AS l),
+    (SELECT group_concat(json_extract(c.data, '$.body'), char(10))
+     FROM comments AS c WHERE c.issue_id = i.id)
+FROM issues AS i
+WHERE ?1 IS NULL OR i.id = ?1
+";
+
+/// Weights for `bm25()`, in column order: matches in the identifier or title
+/// count most, then labels, then the description and comments
+const SEARCH_WEIGHTS: &str = "10.0, 10.0, 2.0, 5.0, 1.0";
+
+/// Kinds of supporting entities kept alongside issues
+#[derive(Debug, Clone, Copy)]
+pub enum EntityKind {
+    Label,
+    State,
+    Cycle,
+    Project,
+}
+
+impl EntityKind {
+    fn as_str(self) -> &'static str {
+        match self {
+            EntityKind::Label => "label",
+            EntityKind::State => "state",
+            EntityKind::Cycle => "cycle",
+            EntityKind::Project => "project",
+        }
+    }
+}
+
+/// A team whose issues are mirrored, with the sync high-water marks
+pub struct SyncedTeam {
+    pub key: String,
+    pub id: String,
+    pub name: String,
+    /// Largest `updatedAt` among mirrored issues
+    pub issues_synced_through: Option<String>,
+    /// Largest `updatedAt` among mirrored comments
+    pub comments_synced_through: Option<String>,
+    pub synced_at: String,
+}
+
+pub struct Mirror {
+    conn: Connection,
+}
+
+impl Mirror {
+    /// Mirror database for the current profile and workspace
+    pub fn path() -> Result<PathBuf> {
+        let workspace = cache::workspace().unwrap_or("default");
+        Config::profile_path(&format!("mirror-{workspace}.db"))
+    }
+
+    /// Open the mirror, creating it if needed
+    pub fn open() -> Result<Self> {
+        let path = Self::path()?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        Self::connect(Connection::open(&path)?)
+    }
+
+    /// Open an existing mirror for reading; fails if `linear sync` never ran
+    pub fn open_existing() -> Result<Self> {
+        let path = Self::path()?;
+        if !path.exists() {
+            return Err(LinearError::NotSynced(
+                "no issues have been synced".to_string(),
+            ));
+        }
+
+        Self::connect(Connection::open(&path)?)
+    }
+
+    fn connect(mut conn: Connection) -> Result<Self> {
+        // Several `linear` processes may share the file
+        conn.busy_timeout(Duration::from_secs(10))?;
+
+        let tx = conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let version: i32 = tx.pragma_query_value(None, "user_version", |row| row.get(0))?;
+        if version > MIRROR_VERSION {
+            return Err(LinearError::NotSynced(format!(
+                "the mirror was written by a newer version (schema {version})"
+            )));
+        }
+        if version < 1 {
+            tx.execute_batch(SCHEMA_V1)?;
+        }
+        if version < 2 {
+            tx.execute_batch(SCHEMA_V2)?;
+            index_issues(&tx, None)?;
+        }
+        tx.pragma_update(None, "user_version", MIRROR_VERSION)?;
+        tx.commit()?;
+
+        Ok(Self { conn })
+    }
+
+    pub fn team(&self, key: &str) -> Result<Option<SyncedTeam>> {
+        let team = self
+            .conn
+            .query_row(
+                "SELECT key, id, name, issues_synced_through, comments_synced_through, synced_at
+                 FROM teams WHERE key = ?1 COLLATE NOCASE",
+                [key],
+                team_from_row,
+            )
+            .optional()?;
+        Ok(team)
+    }
+
+    pub fn teams(&self) -> Result<Vec<SyncedTeam>> {
+        let mut statement = self.conn.prepare(
+            "SELECT key, id, name, issues_synced_through, comments_synced_through, synced_at
+             FROM teams ORDER BY key",
+        )?;
+        let teams = statement
+            .query_map([], team_from_row)?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(teams)
+    }
+
+    pub fn save_team(&self, team: &SyncedTeam) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO teams
+             (key, id, name, issues_synced_through, comments_synced_through, synced_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                team.key,
+                team.id,
+                team.name,
+                team.issues_synced_through,
+                team.comments_synced_through,
+                team.synced_at
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Drop a team's issues, comments and sync marks, for a full re-sync
+    pub fn forget_team(&mut self, key: &str) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM issue_search
+             WHERE rowid IN (SELECT rowid FROM issues WHERE team_key = ?1)",
+            [key],
+        )?;
+        tx.execute("DELETE FROM issues WHERE team_key = ?1", [key])?;
+        tx.execute("DELETE FROM comments WHERE team_key = ?1", [key])?;
+        tx.execute("DELETE FROM entities WHERE team_key = ?1", [key])?;
+        tx.execute("DELETE FROM teams WHERE key = ?1", [key])?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn save_issues(&mut self, issues: &[Issue]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            // An identifier only moves to another issue if the old one was
+            // deleted or moved teams; drop the stale copy
+            let mut evict_search = tx.prepare(
+                "DELETE FROM issue_search
+                 WHERE rowid IN (SELECT rowid FROM issues WHERE identifier = ?1 AND id != ?2)",
+            )?;
+            let mut evict = tx.prepare("DELETE FROM issues WHERE identifier = ?1 AND id != ?2")?;
+            // An upsert keeps the rowid, which is also the search row's key
+            let mut upsert = tx.prepare(
+                "INSERT INTO issues (id, identifier, team_key, updated_at, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)
+                 ON CONFLICT (id) DO UPDATE SET
+                     identifier = excluded.identifier,
+                     team_key = excluded.team_key,
+                     updated_at = excluded.updated_at,
+                     data = excluded.data",
+            )?;
+            for issue in issues {
+                evict_search.execute([&issue.identifier, &issue.id])?;
+                evict.execute([&issue.identifier, &issue.id])?;
+                upsert.execute(params![
+                    issue.id,
+                    issue.identifier,
+                    issue.team.key,
+                    issue.updated_at,
+                    serde_json::to_string(issue)?
+                ])?;
+                index_issues(&tx, Some(&issue.id))?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// IDs of a team's mirrored issues
+    pub fn issue_ids(&self, team_key: &str) -> Result<Vec<String>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT id FROM issues WHERE team_key = ?1 COLLATE NOCASE")?;
+        let ids = statement
+            .query_map([team_key], |row| row.get(0))?
+            .collect::<rusqlite::Result<_>>()?;
+        Ok(ids)
+    }
+
+    /// Drop issues, with their comments, that were archived, deleted or moved
+    /// to another team; returns how many were mirrored
+    pub fn remove_issues(&mut self, ids: &[String]) -> Result<usize> {
+        let tx = self.conn.transaction()?;
+        let mut removed = 0;
+        for id in ids {
+            tx.execute(UNINDEX_ISSUES, [id])?;
+            tx.execute("DELETE FROM comments WHERE issue_id = ?1", [id])?;
+            removed += tx.execute("DELETE FROM issues WHERE id = ?1", [id])?;
+        }
+        tx.commit()?;
+        Ok(removed)
+    }
+
+    /// Drop archived comments and re-index their issues
+    pub fn remove_comments(&mut self, ids: &[String]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        let mut issue_ids = BTreeSet::new();
+        for id in ids {
+            let issue_id: Option<String> = tx
+                .query_row(
+                    "DELETE FROM comments WHERE id = ?1 RETURNING issue_id",
+                    [id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            issue_ids.extend(issue_id);
+        }
+        for issue_id in &issue_ids {
+            index_issues(&tx, Some(issue_id))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    pub fn save_comments(&mut self, team_key: &str, comments: &[(String, Comment)]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        {
+            let mut statement = tx.prepare(
+                "INSERT OR REPLACE INTO comments (id, issue_id, team_key, created_at, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (issue_id, comment) in comments {
+                statement.execute(params![
+                    comment.id,
+                    issue_id,
+                    team_key,
+                    comment.created_at,
+                    serde_json::to_string(comment)?
+                ])?;
+            }
+        }
+        let issue_ids: BTreeSet<_> = comments.iter().map(|(issue_id, _)| issue_id).collect();
+        for issue_id in issue_ids {
+            index_issues(&tx, Some(issue_id))?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Replace all entities of `kind` belonging to `team_key` (or to no team)
+    pub fn replace_entities<T: Serialize>(
+        &mut self,
+        kind: EntityKind,
+        team_key: Option<&str>,
+        entities: &[(String, String, T)],
+    ) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        tx.execute(
+            "DELETE FROM entities WHERE kind = ?1 AND team_key IS ?2",
+            params![kind.as_str(), team_key],
+        )?;
+        {
+            let mut statement = tx.prepare(
+                "INSERT OR REPLACE INTO entities (kind, id, team_key, name, data)
+                 VALUES (?1, ?2, ?3, ?4, ?5)",
+            )?;
+            for (id, name, data) in entities {
+                statement.execute(params![
+                    kind.as_str(),
+                    id,
+                    team_key,
+                    name,
+                    serde_json::to_string(data)?
+                ])?;
+            }
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Mirrored issues, most recently updated first
+    pub fn issues(&self, team_key: Option<&str>) -> Result<Vec<Issue>> {
+        let mut statement = self.conn.prepare(
+            "SELECT data FROM issues WHERE ?1 IS NULL OR team_key = ?1 COLLATE NOCASE
+             ORDER BY updated_at DESC",
+        )?;
+        let rows = statement
+            .query_map([team_key], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.iter().map(|data| decode(data)).collect()
+    }
+
+    /// Find an issue by identifier (e.g. ENG-123) or ID
+    pub fn issue(&self, id: &str) -> Result<Option<Issue>> {
+        let data: Option<String> = self
+            .conn
+            .query_row(
+                "SELECT data FROM issues WHERE identifier = ?1 OR id = ?1",
+                [id],
+                |row| row.get(0),
+            )
+            .optional()?;
+        data.as_deref().map(decode).transpose()
+    }
+
+    /// Issues matching the FTS5 `query`, best match first
+    pub fn search(
+        &self,
+        query: &str,
+        team_key: Option<&str>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Issue>> {
+        let mut statement = self.conn.prepare(&format!(
+            "SELECT i.data FROM issue_search AS s JOIN issues AS i ON i.rowid = s.rowid
+             WHERE issue_search MATCH ?1 AND (?2 IS NULL OR i.team_key = ?2 COLLATE NOCASE)
+             ORDER BY bm25(issue_search, {SEARCH_WEIGHTS}), i.updated_at DESC
+             LIMIT ?3"
+        ))?;
+        // A negative LIMIT means no limit
+        let limit = limit.map_or(-1, |n| n as i64);
+        let rows = statement
+            .query_map(params![query, team_key, limit], |row| {
+                row.get::<_, String>(0)
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.iter().map(|data| decode(data)).collect()
+    }
+
+    /// Comments on an issue, oldest first
+    pub fn comments(&self, issue_id: &str) -> Result<Vec<Comment>> {
+        let mut statement = self
+            .conn
+            .prepare("SELECT data FROM comments WHERE issue_id = ?1 ORDER BY created_at")?;
+        let rows = statement
+            .query_map([issue_id], |row| row.get::<_, String>(0))?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        rows.iter().map(|data| decode(data)).collect()
+    }
+
+    /// Drop everything mirrored, for a mirror of another workspace
+    pub fn clear(&mut self) -> Result<()> {
+        self.conn.execute_batch(
+            "BEGIN;
+             DELETE FROM issue_search;
+             DELETE FROM issues;
+             DELETE FROM comments;
+             DELETE FROM entities;
+             DELETE FROM teams;
+             DELETE FROM meta;
+             COMMIT;",
+        )?;
+        Ok(())
+    }
+
+    pub fn meta(&self, key: &str) -> Result<Option<String>> {
+        let value = self
+            .conn
+            .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+                row.get(0)
+            })
+            .optional()?;
+        Ok(value)
+    }
+
+    pub fn set_meta(&self, key: &str, value: &str) -> Result<()> {
+        self.conn.execute(
+            "INSERT OR REPLACE INTO meta (key, value) VALUES (?1, ?2)",
+            [key, value],
+        )?;
+        Ok(())
+    }
+}
+
+fn index_issues(conn: &Connection, issue_id: Option<&str>) -> Result<()> {
+    conn.execute(UNINDEX_ISSUES, [issue_id])?;
+    conn.execute(INDEX_ISSUES, [issue_id])?;
+    Ok(())
+}
+
+fn team_from_row(row: &rusqlite::Row) -> rusqlite::Result<SyncedTeam> {
+    Ok(SyncedTeam {
+        key: row.get(0)?,
+        id: row.get(1)?,
+        name: row.get(2)?,
+        issues_synced_through: row.get(3)?,
+        comments_synced_through: row.get(4)?,
+        synced_at: row.get(5)?,
+    })
+}
+
+fn decode<T: DeserializeOwned>(data: &str) -> Result<T> {
+    Ok(serde_json::from_str(data)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issue(identifier: &str, updated_at: &str) -> Issue {
+        serde_json::from_value(serde_json::json!({
+            "id": format!("id-{identifier}"),
+            "identifier": identifier,
+            "title": "Title",
+            "description": null,
+            "priority": 0,
+            "estimate": null,
+            "state": null,
+            "assignee": null,
+            "team": { "id": "t1", "key": "ENG", "name": "Engineering" },
+            "project": null,
+            "cycle": null,
+            "labels": null,
+            "createdAt": "2024-01-01T00:00:00.000Z",
+            "updatedAt": updated_at
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_issues_round_trip() {
+        let mut mirror = Mirror::connect(Connection::open_in_memory().unwrap()).unwrap();
+        mirror
+            .save_issues(&[
+                issue("ENG-1", "2024-01-02T00:00:00.000Z"),
+                issue("ENG-2", "2024-01-03T00:00:00.000Z"),
+            ])
+            .unwrap();
+        // A re-synced issue replaces its old copy
+        mirror
+            .save_issues(&[issue("ENG-1", "2024-01-04T00:00:00.000Z")])
+            .unwrap();
+
+        let identifiers: Vec<_> = mirror
+            .issues(Some("eng"))
+            .unwrap()
+            .into_iter()
+            .map(|i| i.identifier)
+            .collect();
+        assert_eq!(identifiers, ["ENG-1", "ENG-2"]);
+
+        assert!(mirror.issue("eng-2").unwrap().is_some());
+        assert!(mirror.issues(Some("OPS")).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_search_ranks_and_tracks_updates() {
+        let mut mirror = Mirror::connect(Connection::open_in_memory().unwrap()).unwrap();
+        let mut login = issue("ENG-1", "2024-01-02T00:00:00.000Z");
+        login.title = "Login fails on Safari".to_string();
+        let mut other = issue("ENG-2", "2024-01-03T00:00:00.000Z");
+        other.description = Some("Unrelated, though the login page is mentioned".to_string());
+        mirror.save_issues(&[login.clone(), other]).unwrap();
+
+        let search = |mirror: &Mirror, query: &str| -> Vec<String> {
+            let results = mirror.search(query, None, None).unwrap();
+            results.into_iter().map(|i| i.identifier).collect()
+        };
+        // Title matches outrank description matches; stemming matches "fails"
+        assert_eq!(search(&mirror, "login"), ["ENG-1", "ENG-2"]);
+        assert_eq!(search(&mirror, "title:\"login fail\""), ["ENG-1"]);
+
+        login.title = "Signup fails".to_string();
+        mirror.save_issues(&[login]).unwrap();
+        assert_eq!(search(&mirror, "title:login"), Vec::<String>::new());
+
+        let comment: Comment = serde_json::from_value(serde_json::json!({
+            "id": "c1",
+            "body": "Reproduced on Firefox",
+            "createdAt": "2024-01-05T00:00:00.000Z",
+            "user": null
+        }))
+        .unwrap();
+        mirror
+            .save_comments("ENG", &[("id-ENG-2".to_string(), comment)])
+            .unwrap();
+        assert_eq!(search(&mirror, "comment:firefox"), ["ENG-2"]);
+    }
+
+    #[test]
+    fn test_remove_issues_and_comments() {
+        let mut mirror = Mirror::connect(Connection::open_in_memory().unwrap()).unwrap();
+        let mut gone = issue("ENG-1", "2024-01-02T00:00:00.000Z");
+        gone.title = "Archived".to_string();
+        mirror
+            .save_issues(&[gone, issue("ENG-2", "2024-01-03T00:00:00.000Z")])
+            .unwrap();
+        let comment = |id: &str| -> Comment {
+            serde_json::from_value(serde_json::json!({
+                "id": id,
+                "body": format!("Comment {id}"),
+                "createdAt": "2024-01-05T00:00:00.000Z",
+                "user": null
+            }))
+            .unwrap()
+        };
+        mirror
+            .save_comments(
+                "ENG",
+                &[
+                    ("id-ENG-1".to_string(), comment("c1")),
+                    ("id-ENG-2".to_string(), comment("c2")),
+                ],
+            )
+            .unwrap();
+
+        mirror.remove_issues(&["id-ENG-1".to_string()]).unwrap();
+        assert_eq!(mirror.issue_ids("ENG").unwrap(), ["id-ENG-2"]);
+        assert!(mirror.comments("id-ENG-1").unwrap().is_empty());
+        assert!(mirror.search("archived", None, None).unwrap().is_empty());
+
+        mirror.remove_comments(&["c2".to_string()]).unwrap();
+        assert!(mirror.comments("id-ENG-2").unwrap().is_empty());
+        assert!(mirror.search("comment:c2", None, None).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_clear_forgets_teams_and_marks() {
+        let mut mirror = Mirror::connect(Connection::open_in_memory().unwrap()).unwrap();
+        mirror
+            .save_issues(&[issue("ENG-1", "2024-01-02T00:00:00.000Z")])
+            .unwrap();
+        mirror
+            .save_team(&SyncedTeam {
+                key: "ENG".to_string(),
+                id: "t1".to_string(),
+                name: "Engineering".to_string(),
+                issues_synced_through: Some("2024-01-02T00:00:00.000Z".to_string()),
+                comments_synced_through: None,
+                synced_at: "2024-01-03T00:00:00.000Z".to_string(),
+            })
+            .unwrap();
+        mirror.set_meta("organization_id", "org1").unwrap();
+
+        mirror.clear().unwrap();
+        assert!(mirror.teams().unwrap().is_empty());
+        assert!(mirror.issues(None).unwrap().is_empty());
+        assert!(mirror.search("title", None, None).unwrap().is_empty());
+        assert_eq!(mirror.meta("organization_id").unwrap(), None);
+    }
+}
@@ -2,10 +2,12 @@ use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
 
 use colored::Colorize;
 use serde::Serialize;
+use tabled::builder::Builder;
 use tabled::settings::Style;
 use tabled::{Table, Tabled};
 
 use crate::cli::OutputFormat;
+use crate::error::{ErrorClass, GraphQLError, GraphQLErrorKind, LinearError};
 
 /// Global output format setting (thread-safe)
 /// 0 = Table, 1 = Json, 2 = Compact
@@ -68,6 +70,38 @@ where
     }
 }
 
+/// Like `print_table`, for tables whose columns are chosen at run time
+pub fn print_columns<T: Serialize>(
+    items: &[T],
+    headers: &[&str],
+    to_cells: impl Fn(&T) -> Vec<String>,
+    to_compact: impl Fn(&T) -> String,
+) {
+    match get_format() {
+        OutputFormat::Json => {
+            println!(
+                "{}",
+                serde_json::to_string(items)
+                    .unwrap_or_else(|_| "<serialization error>".to_string())
+            );
+        }
+        OutputFormat::Compact => {
+            for item in items {
+                println!("{}", to_compact(item));
+            }
+        }
+        OutputFormat::Table => {
+            let mut builder = Builder::default();
+            builder.push_record(headers.iter().copied());
+            for item in items {
+                builder.push_record(to_cells(item));
+            }
+            let table = builder.build().with(Style::rounded()).to_string();
+            println!("{table}");
+        }
+    }
+}
+
 /// Print a single item as JSON or custom display
 pub fn print_item<T: Serialize>(item: &T, display: impl FnOnce(&T)) {
     if is_json_output() {
@@ -93,6 +127,85 @@ pub fn print_message(message: &str) {
     }
 }
 
+#[derive(Serialize)]
+struct ErrorOutput<'a> {
+    error: ErrorBody<'a>,
+}
+
+#[derive(Serialize)]
+struct ErrorBody<'a> {
+    kind: ErrorClass,
+    exit_code: i32,
+    message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    graphql: Option<Vec<GraphQLErrorDetail<'a>>>,
+    causes: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hint: Option<&'static str>,
+}
+
+#[derive(Serialize)]
+struct GraphQLErrorDetail<'a> {
+    #[serde(flatten)]
+    error: &'a GraphQLError,
+    kind: GraphQLErrorKind,
+}
+
+/// Print an error to stderr: a JSON object in JSON mode, otherwise text
+/// (with the cause chain only when verbose)
+pub fn print_error(error: &LinearError, verbose: bool) {
+    let mut causes = Vec::new();
+    let mut source = std::error::Error::source(error);
+    while let Some(cause) = source {
+        causes.push(cause.to_string());
+        source = cause.source();
+    }
+
+    if is_json_output() {
+        let graphql = match error {
+            LinearError::GraphQL { errors } => Some(
+                errors
+                    .iter()
+                    .map(|e| GraphQLErrorDetail {
+                        error: e,
+                        kind: e.kind(),
+                    })
+                    .collect(),
+            ),
+            _ => None,
+        };
+
+        let output = ErrorOutput {
+            error: ErrorBody {
+                kind: error.class(),
+                exit_code: error.exit_code(),
+                message: error.to_string(),
+                graphql,
+                causes,
+                hint: error.hint(),
+            },
+        };
+
+        eprintln!(
+            "{}",
+            serde_json::to_string(&output).unwrap_or_else(|_| "<serialization error>".to_string())
+        );
+        return;
+    }
+
+    eprintln!("Error: {error}");
+
+    if let Some(hint) = error.hint() {
+        eprintln!("Hint: {hint}");
+    }
+
+    if verbose {
+        for cause in causes {
+            eprintln!("Caused by: {cause}");
+        }
+    }
+}
+
 /// Format status with color based on state type
 pub fn status_colored(status: &str, color: Option<&str>) -> String {
     if let Some(hex) = color {
@@ -0,0 +1,262 @@
+//! Cursor pagination over GraphQL connections.
+
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+
+use crate::client::{operation_name, LinearClient};
+use crate::error::Result;
+use crate::output;
+use crate::responses::Connection;
+use crate::trace;
+
+/// Largest `first` Linear accepts
+const MAX_PAGE_SIZE: usize = 250;
+
+/// Page size when fetching everything; smaller pages keep query complexity down
+const ALL_PAGE_SIZE: usize = 100;
+
+/// How many nodes a list query should return.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PageLimit {
+    First(usize),
+    All,
+}
+
+impl PageLimit {
+    /// What is left of the limit once `used` nodes are taken
+    pub fn remaining(self, used: usize) -> Self {
+        match self {
+            PageLimit::First(n) => PageLimit::First(n.saturating_sub(used)),
+            PageLimit::All => PageLimit::All,
+        }
+    }
+
+    /// `first` for the opening page of a connection
+    pub fn page_size(self) -> usize {
+        match self {
+            PageLimit::First(n) => n.clamp(1, MAX_PAGE_SIZE),
+            PageLimit::All => ALL_PAGE_SIZE,
+        }
+    }
+}
+
+/// Fetch nodes from a connection, following `pageInfo` cursors.
+///
+/// The query must take `$first: Int` and `$after: String` and select
+/// `pageInfo { hasNextPage endCursor }` on the connection, which `connection`
+/// extracts from the response. When `limit` cuts the results short a warning
+/// goes to stderr, so truncation is never silent.
+pub async fn fetch<R, T>(
+    client: &LinearClient,
+    query: &str,
+    variables: Option<Value>,
+    limit: PageLimit,
+    connection: impl Fn(R) -> Result<Connection<T>>,
+) -> Result<Vec<T>>
+where
+    R: DeserializeOwned,
+{
+    let (nodes, truncated) = fetch_pages(client, query, variables, limit, None, connection).await?;
+    if let (true, PageLimit::First(n)) = (truncated, limit) {
+        warn_truncated(n);
+    }
+    Ok(nodes)
+}
+
+/// Like [`fetch`] for a connection whose first page came back as part of a
+/// larger query; `query` fetches the pages after it. Returns whether `limit`
+/// cut the results short, without warning, since only the caller knows what
+/// the results add up to.
+pub async fn fetch_after<R, T>(
+    client: &LinearClient,
+    query: &str,
+    variables: Option<Value>,
+    limit: PageLimit,
+    first: Connection<T>,
+    connection: impl Fn(R) -> Result<Connection<T>>,
+) -> Result<(Vec<T>, bool)>
+where
+    R: DeserializeOwned,
+{
+    fetch_pages(client, query, variables, limit, Some(first), connection).await
+}
+
+async fn fetch_pages<R, T>(
+    client: &LinearClient,
+    query: &str,
+    variables: Option<Value>,
+    limit: PageLimit,
+    mut first: Option<Connection<T>>,
+    connection: impl Fn(R) -> Result<Connection<T>>,
+) -> Result<(Vec<T>, bool)>
+where
+    R: DeserializeOwned,
+{
+    let mut variables = variables.unwrap_or_else(|| json!({}));
+    let mut nodes = Vec::new();
+
+    loop {
+        let page = match first.take() {
+            Some(page) => page,
+            None => {
+                variables["first"] = json!(limit.remaining(nodes.len()).page_size());
+                let response: R = client.query(query, Some(variables.clone())).await?;
+                connection(response)?
+            }
+        };
+        nodes.extend(page.nodes);
+
+        let (has_next_page, end_cursor) = page
+            .page_info
+            .map(|info| (info.has_next_page, info.end_cursor))
+            .unwrap_or((false, None));
+
+        trace::log(format!(
+            "{}: {} fetched, has_next_page={}, end_cursor={}",
+            operation_name(query),
+            nodes.len(),
+            has_next_page,
+            end_cursor.as_deref().unwrap_or("-")
+        ));
+
+        // A first page from a larger query may hold more than the limit
+        if let PageLimit::First(n) = limit {
+            if nodes.len() > n || (nodes.len() == n && has_next_page) {
+                nodes.truncate(n);
+                return Ok((nodes, true));
+            }
+        }
+
+        let Some(cursor) = end_cursor.filter(|_| has_next_page) else {
+            break;
+        };
+
+        variables["after"] = json!(cursor);
+    }
+
+    Ok((nodes, false))
+}
+
+/// Apply `limit` to results that are already in memory (e.g. read offline),
+/// warning like `fetch` does when some are cut off.
+pub fn limit<T>(mut nodes: Vec<T>, limit: PageLimit) -> Vec<T> {
+    if let PageLimit::First(n) = limit {
+        if nodes.len() > n {
+            nodes.truncate(n);
+            warn_truncated(n);
+        }
+    }
+    nodes
+}
+
+/// Tell the user only the first `n` results are shown
+pub fn warn_truncated(n: usize) {
+    if !output::is_quiet() {
+        eprintln!("Warning: showing the first {n} results; use --limit or --all to see more");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::auth::Credentials;
+    use crate::cassette::{Cassette, CassetteMode};
+    use serde::Deserialize;
+
+    const QUERY: &str = "query ListThings($first: Int, $after: String) { things { nodes { id } } }";
+
+    #[derive(Deserialize)]
+    struct Thing {
+        id: String,
+    }
+
+    #[derive(Deserialize)]
+    struct ThingsResponse {
+        things: Connection<Thing>,
+    }
+
+    fn page(variables: Value, ids: &[&str], next: Option<&str>) -> Value {
+        json!({
+            "request": { "query": QUERY, "variables": variables },
+            "response": { "status": 200, "body": { "data": { "things": {
+                "nodes": ids.iter().map(|id| json!({ "id": id })).collect::<Vec<_>>(),
+                "pageInfo": { "hasNextPage": next.is_some(), "endCursor": next }
+            } } } }
+        })
+    }
+
+    fn client(name: &str, interactions: Vec<Value>) -> LinearClient {
+        let path = std::env::temp_dir().join(format!("linear-cli-{}-{name}", std::process::id()));
+        std::fs::write(&path, json!({ "interactions": interactions }).to_string()).unwrap();
+        let cassette = Cassette::open(&path, CassetteMode::Replay).unwrap();
+        std::fs::remove_file(&path).ok();
+
+        LinearClient::new(Credentials::ApiKey(String::new())).with_cassette(cassette)
+    }
+
+    async fn ids(client: &LinearClient, limit: PageLimit) -> Vec<String> {
+        fetch(client, QUERY, None, limit, |r: ThingsResponse| Ok(r.things))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|t| t.id)
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn test_follows_cursors_until_last_page() {
+        let client = client(
+            "pages-all.json",
+            vec![
+                page(json!({ "first": 100 }), &["a", "b"], Some("c1")),
+                page(json!({ "first": 100, "after": "c1" }), &["c"], None),
+            ],
+        );
+
+        assert_eq!(ids(&client, PageLimit::All).await, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_stops_at_limit() {
+        let client = client(
+            "pages-limit.json",
+            vec![
+                page(json!({ "first": 3 }), &["a", "b"], Some("c1")),
+                page(json!({ "first": 1, "after": "c1" }), &["c"], Some("c2")),
+            ],
+        );
+
+        assert_eq!(ids(&client, PageLimit::First(3)).await, ["a", "b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn test_continues_after_a_given_first_page() {
+        let client = client(
+            "pages-after.json",
+            vec![page(
+                json!({ "first": 2, "after": "c1" }),
+                &["c", "d"],
+                Some("c2"),
+            )],
+        );
+        let first: Connection<Thing> = serde_json::from_value(json!({
+            "nodes": [{ "id": "a" }, { "id": "b" }],
+            "pageInfo": { "hasNextPage": true, "endCursor": "c1" }
+        }))
+        .unwrap();
+
+        let (things, truncated) = fetch_after(
+            &client,
+            QUERY,
+            None,
+            PageLimit::First(4),
+            first,
+            |r: ThingsResponse| Ok(r.things),
+        )
+        .await
+        .unwrap();
+        let ids: Vec<_> = things.into_iter().map(|t| t.id).collect();
+        assert_eq!(ids, ["a", "b", "c", "d"]);
+        assert!(truncated);
+    }
+}
@@ -0,0 +1,135 @@
+//! Rate-limit bookkeeping from Linear's response headers.
+
+use std::time::{Duration, SystemTime};
+
+use reqwest::header::HeaderMap;
+use serde::Serialize;
+
+/// Last seen rate-limit headers. Reset times are Unix epoch milliseconds.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct RateLimitStatus {
+    pub requests_limit: Option<u64>,
+    pub requests_remaining: Option<u64>,
+    pub requests_reset: Option<u64>,
+    /// Complexity points spent by the last request
+    pub complexity: Option<u64>,
+    pub complexity_limit: Option<u64>,
+    pub complexity_remaining: Option<u64>,
+    pub complexity_reset: Option<u64>,
+}
+
+impl RateLimitStatus {
+    /// Parse rate-limit headers, returning None if the response had none.
+    pub fn from_headers(headers: &HeaderMap) -> Option<Self> {
+        let get = |name: &str| {
+            headers
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+
+        let status = Self {
+            requests_limit: get("x-ratelimit-requests-limit"),
+            requests_remaining: get("x-ratelimit-requests-remaining"),
+            requests_reset: get("x-ratelimit-requests-reset"),
+            complexity: get("x-complexity"),
+            complexity_limit: get("x-ratelimit-complexity-limit"),
+            complexity_remaining: get("x-ratelimit-complexity-remaining"),
+            complexity_reset: get("x-ratelimit-complexity-reset"),
+        };
+
+        if status.requests_limit.is_none() && status.complexity_limit.is_none() {
+            return None;
+        }
+
+        Some(status)
+    }
+
+    /// Time until the exhausted budget (requests or complexity) resets, if any is exhausted
+    pub fn wait_until_reset(&self) -> Option<Duration> {
+        let request_wait = match self.requests_remaining {
+            Some(0) => self.requests_reset.and_then(until),
+            _ => None,
+        };
+        let complexity_wait = match self.complexity_remaining {
+            Some(0) => self.complexity_reset.and_then(until),
+            _ => None,
+        };
+
+        request_wait.max(complexity_wait)
+    }
+}
+
+/// Parse a `Retry-After` header given in seconds.
+pub fn retry_after(headers: &HeaderMap) -> Option<Duration> {
+    headers
+        .get("retry-after")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.trim().parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Duration from now until the given epoch-millisecond timestamp (None if in the past)
+fn until(epoch_ms: u64) -> Option<Duration> {
+    let now = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64;
+
+    epoch_ms
+        .checked_sub(now)
+        .filter(|ms| *ms > 0)
+        .map(Duration::from_millis)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn test_from_headers() {
+        let mut headers = HeaderMap::new();
+        assert!(RateLimitStatus::from_headers(&headers).is_none());
+
+        headers.insert(
+            "x-ratelimit-requests-limit",
+            HeaderValue::from_static("1500"),
+        );
+        headers.insert(
+            "x-ratelimit-requests-remaining",
+            HeaderValue::from_static("1499"),
+        );
+        headers.insert("x-complexity", HeaderValue::from_static("12"));
+
+        let status = RateLimitStatus::from_headers(&headers).unwrap();
+        assert_eq!(status.requests_limit, Some(1500));
+        assert_eq!(status.requests_remaining, Some(1499));
+        assert_eq!(status.complexity, Some(12));
+        assert_eq!(status.complexity_limit, None);
+        assert!(status.wait_until_reset().is_none());
+    }
+
+    #[test]
+    fn test_wait_until_reset_when_exhausted() {
+        let now_ms = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+
+        let status = RateLimitStatus {
+            complexity_remaining: Some(0),
+            complexity_reset: Some(now_ms + 5_000),
+            ..Default::default()
+        };
+        let wait = status.wait_until_reset().unwrap();
+        assert!(wait <= Duration::from_secs(5) && wait > Duration::from_secs(3));
+
+        let past = RateLimitStatus {
+            requests_remaining: Some(0),
+            requests_reset: Some(now_ms - 1_000),
+            ..Default::default()
+        };
+        assert!(past.wait_until_reset().is_none());
+    }
+}
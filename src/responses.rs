@@ -6,6 +6,9 @@ use serde::Deserialize;
 #[derive(Deserialize)]
 pub struct Connection<T> {
     pub nodes: Vec<T>,
+    /// Present when the query selects `pageInfo` (see `pagination::fetch`)
+    #[serde(rename = "pageInfo")]
+    pub page_info: Option<PageInfo>,
 }
 
 /// Pagination info for cursor-based pagination.
@@ -17,33 +20,6 @@ pub struct PageInfo {
     pub end_cursor: Option<String>,
 }
 
-/// Viewer (current user) response.
-#[derive(Deserialize)]
-pub struct ViewerResponse {
-    pub viewer: Viewer,
-}
-
-#[derive(Deserialize)]
-pub struct Viewer {
-    pub id: String,
-}
-
-/// Workflow state for status lookups.
-#[derive(Deserialize)]
-pub struct WorkflowStateNode {
-    pub id: String,
-    pub name: String,
-    /// State type: "backlog", "unstarted", "started", "completed", or "canceled"
-    #[serde(rename = "type")]
-    pub state_type: String,
-}
-
-/// Team node with minimal fields for ID lookups.
-#[derive(Deserialize)]
-pub struct TeamNode {
-    pub id: String,
-}
-
 /// Minimal issue info returned after create/update.
 #[derive(Deserialize)]
 pub struct CreatedIssue {
@@ -0,0 +1,1006 @@
+//! Checks every GraphQL operation in `src/` against the vendored Linear schema
+//! (`schema/linear.graphql`), so a misspelled field, a missing argument or a
+//! wrongly typed variable fails `cargo test` instead of a user's command.
+//!
+//! Operations are found by scanning the sources for raw string literals that
+//! start with `query`, `mutation` or `fragment`; fragments are pooled so that
+//! operations assembled with `concat!` validate like the final document.
+//!
+//! Response types are checked too: [`assert_response`] builds a sample
+//! response from an operation's selection and deserializes the serde struct
+//! from it, reporting struct fields that the schema type doesn't have and any
+//! field the struct requires but the operation doesn't select. Every
+//! `*_QUERY`/`*_MUTATION` constant must be checked this way by a test in its
+//! module.
+
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use graphql_parser::query::{
+    self as q, Definition, OperationDefinition, Selection, SelectionSet, TypeCondition,
+};
+use graphql_parser::schema::{self as s, TypeDefinition};
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{self, DeserializeOwned, DeserializeSeed, Visitor};
+use serde::forward_to_deserialize_any;
+use serde_json::{json, Map, Value as Json};
+
+const SCHEMA_SDL: &str = include_str!("../schema/linear.graphql");
+
+const BUILTIN_SCALARS: &[&str] = &["Int", "Float", "String", "Boolean", "ID"];
+
+type Type = q::Type<'static, String>;
+type Value = q::Value<'static, String>;
+type Fragments = HashMap<String, q::FragmentDefinition<'static, String>>;
+
+struct Schema {
+    types: HashMap<String, TypeDefinition<'static, String>>,
+    query: String,
+    mutation: String,
+}
+
+impl Schema {
+    fn load() -> Self {
+        let document = s::parse_schema::<String>(SCHEMA_SDL).expect("schema/linear.graphql");
+
+        let mut schema = Schema {
+            types: HashMap::new(),
+            query: "Query".to_string(),
+            mutation: "Mutation".to_string(),
+        };
+        for definition in document.definitions {
+            match definition {
+                s::Definition::SchemaDefinition(roots) => {
+                    schema.query = roots.query.unwrap_or(schema.query);
+                    schema.mutation = roots.mutation.unwrap_or(schema.mutation);
+                }
+                s::Definition::TypeDefinition(def) => {
+                    schema.types.insert(type_name(&def).to_string(), def);
+                }
+                _ => {}
+            }
+        }
+        schema
+    }
+
+    fn fields(&self, type_name: &str) -> Option<&[s::Field<'static, String>]> {
+        match self.types.get(type_name)? {
+            TypeDefinition::Object(object) => Some(&object.fields),
+            TypeDefinition::Interface(interface) => Some(&interface.fields),
+            _ => None,
+        }
+    }
+
+    fn is_leaf(&self, type_name: &str) -> bool {
+        BUILTIN_SCALARS.contains(&type_name)
+            || matches!(
+                self.types.get(type_name),
+                Some(TypeDefinition::Scalar(_) | TypeDefinition::Enum(_))
+            )
+    }
+
+    fn is_input(&self, type_name: &str) -> bool {
+        self.is_leaf(type_name)
+            || matches!(
+                self.types.get(type_name),
+                Some(TypeDefinition::InputObject(_))
+            )
+    }
+
+    /// Whether a fragment on `condition` can apply to a value of `parent`
+    fn fragment_applies(&self, condition: &str, parent: &str) -> bool {
+        if condition == parent {
+            return true;
+        }
+        let implements = |child: &str, abstract_type: &str| match self.types.get(child) {
+            Some(TypeDefinition::Object(object)) => object
+                .implements_interfaces
+                .iter()
+                .any(|i| i == abstract_type),
+            _ => false,
+        };
+        let members = |union: &str, member: &str| match self.types.get(union) {
+            Some(TypeDefinition::Union(u)) => u.types.iter().any(|t| t == member),
+            _ => false,
+        };
+        implements(condition, parent)
+            || implements(parent, condition)
+            || members(parent, condition)
+            || members(condition, parent)
+    }
+
+    /// Validate one operation (or fragment) document, returning every problem found
+    fn validate(&self, source: &str, fragments: &Fragments) -> Vec<String> {
+        let document = match q::parse_query::<String>(source) {
+            Ok(document) => document.into_static(),
+            Err(e) => return vec![format!("parse error: {e}")],
+        };
+
+        let mut errors = Vec::new();
+        for definition in &document.definitions {
+            match definition {
+                Definition::Operation(operation) => {
+                    let (root, name, variables, selection_set) = match operation {
+                        OperationDefinition::Query(op) => (
+                            &self.query,
+                            op.name.as_deref(),
+                            &op.variable_definitions,
+                            &op.selection_set,
+                        ),
+                        OperationDefinition::Mutation(op) => (
+                            &self.mutation,
+                            op.name.as_deref(),
+                            &op.variable_definitions,
+                            &op.selection_set,
+                        ),
+                        OperationDefinition::SelectionSet(_) => {
+                            errors.push("anonymous operations cannot be traced; name it".into());
+                            continue;
+                        }
+                        OperationDefinition::Subscription(_) => {
+                            errors.push("subscriptions are not supported".into());
+                            continue;
+                        }
+                    };
+
+                    let mut checker = Checker {
+                        schema: self,
+                        fragments,
+                        variables: HashMap::new(),
+                        used: HashSet::new(),
+                        errors: Vec::new(),
+                    };
+                    for variable in variables {
+                        let named = named_type(&variable.var_type);
+                        if !self.is_input(named) {
+                            checker.errors.push(format!(
+                                "variable ${} has unknown or non-input type {named}",
+                                variable.name
+                            ));
+                        }
+                        checker
+                            .variables
+                            .insert(variable.name.clone(), variable.var_type.clone());
+                    }
+                    checker.selection_set(root, selection_set, root, &mut Vec::new());
+                    for variable in variables {
+                        if !checker.used.contains(&variable.name) {
+                            checker
+                                .errors
+                                .push(format!("variable ${} is never used", variable.name));
+                        }
+                    }
+
+                    let name = name.unwrap_or("<anonymous>");
+                    errors.extend(checker.errors.into_iter().map(|e| format!("{name}: {e}")));
+                }
+                Definition::Fragment(fragment) => {
+                    let TypeCondition::On(on) = &fragment.type_condition;
+                    if self.fields(on).is_none() {
+                        errors.push(format!(
+                            "fragment {} is on unknown type {on}",
+                            fragment.name
+                        ));
+                    }
+                }
+            }
+        }
+        errors
+    }
+}
+
+struct Checker<'s> {
+    schema: &'s Schema,
+    fragments: &'s Fragments,
+    variables: HashMap<String, Type>,
+    used: HashSet<String>,
+    errors: Vec<String>,
+}
+
+impl Checker<'_> {
+    fn selection_set(
+        &mut self,
+        parent: &str,
+        selection_set: &SelectionSet<'static, String>,
+        path: &str,
+        spread: &mut Vec<String>,
+    ) {
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => self.field(parent, field, path, spread),
+                Selection::FragmentSpread(fragment_spread) => {
+                    let name = &fragment_spread.fragment_name;
+                    let Some(fragment) = self.fragments.get(name) else {
+                        self.errors
+                            .push(format!("{path}: unknown fragment ...{name}"));
+                        continue;
+                    };
+                    if spread.contains(name) {
+                        self.errors
+                            .push(format!("{path}: fragment ...{name} spreads itself"));
+                        continue;
+                    }
+                    let TypeCondition::On(on) = &fragment.type_condition;
+                    if !self.schema.fragment_applies(on, parent) {
+                        self.errors.push(format!(
+                            "{path}: fragment ...{name} on {on} cannot apply to {parent}"
+                        ));
+                        continue;
+                    }
+                    spread.push(name.clone());
+                    self.selection_set(on, &fragment.selection_set, path, spread);
+                    spread.pop();
+                }
+                Selection::InlineFragment(inline) => {
+                    let on = match &inline.type_condition {
+                        Some(TypeCondition::On(on)) => on.as_str(),
+                        None => parent,
+                    };
+                    if !self.schema.fragment_applies(on, parent) {
+                        self.errors
+                            .push(format!("{path}: ... on {on} cannot apply to {parent}"));
+                        continue;
+                    }
+                    self.selection_set(on, &inline.selection_set, path, spread);
+                }
+            }
+        }
+    }
+
+    fn field(
+        &mut self,
+        parent: &str,
+        field: &q::Field<'static, String>,
+        path: &str,
+        spread: &mut Vec<String>,
+    ) {
+        if field.name == "__typename" {
+            return;
+        }
+        let path = format!("{path}.{}", field.name);
+        self.directives(&field.directives, &path);
+        let Some(definition) = self
+            .schema
+            .fields(parent)
+            .and_then(|fields| fields.iter().find(|f| f.name == field.name))
+        else {
+            self.errors
+                .push(format!("{path}: {parent} has no field `{}`", field.name));
+            return;
+        };
+
+        for (name, value) in &field.arguments {
+            match definition.arguments.iter().find(|a| &a.name == name) {
+                Some(argument) => {
+                    self.value(value, &argument.value_type, &format!("{path}({name})"))
+                }
+                None => self
+                    .errors
+                    .push(format!("{path}: unknown argument `{name}`")),
+            }
+        }
+        for argument in &definition.arguments {
+            let required = matches!(argument.value_type, Type::NonNullType(_))
+                && argument.default_value.is_none();
+            if required
+                && !field
+                    .arguments
+                    .iter()
+                    .any(|(name, _)| name == &argument.name)
+            {
+                self.errors.push(format!(
+                    "{path}: missing required argument `{}`",
+                    argument.name
+                ));
+            }
+        }
+
+        let field_type = named_type(&definition.field_type);
+        let has_selection = !field.selection_set.items.is_empty();
+        if self.schema.is_leaf(field_type) {
+            if has_selection {
+                self.errors.push(format!(
+                    "{path}: {field_type} is a leaf and takes no selection"
+                ));
+            }
+        } else if !has_selection {
+            self.errors
+                .push(format!("{path}: {field_type} needs a selection of fields"));
+        } else {
+            self.selection_set(field_type, &field.selection_set, &path, spread);
+        }
+    }
+
+    /// Only the built-in `@include(if:)` and `@skip(if:)` are supported
+    fn directives(&mut self, directives: &[q::Directive<'static, String>], path: &str) {
+        let condition = Type::NonNullType(Box::new(Type::NamedType("Boolean".to_string())));
+
+        for directive in directives {
+            let name = &directive.name;
+            if name != "include" && name != "skip" {
+                self.errors
+                    .push(format!("{path}: unknown directive @{name}"));
+                continue;
+            }
+            match directive.arguments.as_slice() {
+                [(argument, value)] if argument == "if" => {
+                    self.value(value, &condition, &format!("{path} @{name}(if)"))
+                }
+                _ => self
+                    .errors
+                    .push(format!("{path}: @{name} takes exactly one argument `if`")),
+            }
+        }
+    }
+
+    fn value(&mut self, value: &Value, expected: &Type, path: &str) {
+        let (inner, non_null) = match expected {
+            Type::NonNullType(inner) => (inner.as_ref(), true),
+            other => (other, false),
+        };
+
+        match value {
+            Value::Variable(name) => {
+                self.used.insert(name.clone());
+                match self.variables.get(name) {
+                    Some(declared) if !is_assignable(declared, expected) => {
+                        self.errors.push(format!(
+                            "{path}: variable ${name} is {} but {} is expected",
+                            display_type(declared),
+                            display_type(expected)
+                        ))
+                    }
+                    Some(_) => {}
+                    None => self
+                        .errors
+                        .push(format!("{path}: variable ${name} is not declared")),
+                }
+            }
+            Value::Null if non_null => self
+                .errors
+                .push(format!("{path}: null for {}", display_type(expected))),
+            Value::Null => {}
+            Value::List(items) => match inner {
+                Type::ListType(item_type) => {
+                    for item in items {
+                        self.value(item, item_type, path);
+                    }
+                }
+                _ => self
+                    .errors
+                    .push(format!("{path}: list given for {}", display_type(expected))),
+            },
+            // A single value is coerced to a one-element list
+            _ if matches!(inner, Type::ListType(_)) => {
+                let Type::ListType(item_type) = inner else {
+                    unreachable!()
+                };
+                self.value(value, item_type, path);
+            }
+            Value::Object(object) => {
+                let name = named_type(inner);
+                let Some(TypeDefinition::InputObject(input)) = self.schema.types.get(name) else {
+                    self.errors.push(format!(
+                        "{path}: object given for {}",
+                        display_type(expected)
+                    ));
+                    return;
+                };
+                for (key, field_value) in object {
+                    match input.fields.iter().find(|f| &f.name == key) {
+                        Some(field) => {
+                            self.value(field_value, &field.value_type, &format!("{path}.{key}"))
+                        }
+                        None => self
+                            .errors
+                            .push(format!("{path}: {name} has no field `{key}`")),
+                    }
+                }
+                for field in &input.fields {
+                    let required = matches!(field.value_type, Type::NonNullType(_))
+                        && field.default_value.is_none();
+                    if required && !object.contains_key(&field.name) {
+                        self.errors
+                            .push(format!("{path}: {name} requires field `{}`", field.name));
+                    }
+                }
+            }
+            Value::Enum(variant) => {
+                let name = named_type(inner);
+                let known = match self.schema.types.get(name) {
+                    Some(TypeDefinition::Enum(e)) => e.values.iter().any(|v| &v.name == variant),
+                    _ => false,
+                };
+                if !known {
+                    self.errors
+                        .push(format!("{path}: {variant} is not a value of {name}"));
+                }
+            }
+            Value::Int(_) | Value::Float(_) | Value::String(_) | Value::Boolean(_) => {
+                let name = named_type(inner);
+                let accepted = match value {
+                    Value::Int(_) => matches!(name, "Int" | "Float" | "ID"),
+                    Value::Float(_) => name == "Float",
+                    Value::String(_) => matches!(name, "String" | "ID"),
+                    _ => name == "Boolean",
+                };
+                // Custom scalars (DateTime, JSON, ...) accept any literal
+                let custom = matches!(self.schema.types.get(name), Some(TypeDefinition::Scalar(_)));
+                if !accepted && !custom {
+                    self.errors.push(format!(
+                        "{path}: literal does not fit {}",
+                        display_type(expected)
+                    ));
+                }
+            }
+        }
+    }
+}
+
+fn type_name<'a>(definition: &'a TypeDefinition<'static, String>) -> &'a str {
+    match definition {
+        TypeDefinition::Scalar(t) => &t.name,
+        TypeDefinition::Object(t) => &t.name,
+        TypeDefinition::Interface(t) => &t.name,
+        TypeDefinition::Union(t) => &t.name,
+        TypeDefinition::Enum(t) => &t.name,
+        TypeDefinition::InputObject(t) => &t.name,
+    }
+}
+
+fn named_type(ty: &Type) -> &str {
+    match ty {
+        Type::NamedType(name) => name,
+        Type::ListType(inner) | Type::NonNullType(inner) => named_type(inner),
+    }
+}
+
+fn display_type(ty: &Type) -> String {
+    match ty {
+        Type::NamedType(name) => name.clone(),
+        Type::ListType(inner) => format!("[{}]", display_type(inner)),
+        Type::NonNullType(inner) => format!("{}!", display_type(inner)),
+    }
+}
+
+/// Whether a variable declared as `declared` may be passed where `expected` is required
+fn is_assignable(declared: &Type, expected: &Type) -> bool {
+    match (declared, expected) {
+        (Type::NonNullType(d), Type::NonNullType(e)) => is_assignable(d, e),
+        (_, Type::NonNullType(_)) => false,
+        (Type::NonNullType(d), e) => is_assignable(d, e),
+        (Type::ListType(d), Type::ListType(e)) => is_assignable(d, e),
+        (Type::NamedType(d), Type::NamedType(e)) => d == e,
+        _ => false,
+    }
+}
+
+/// Raw string literals holding GraphQL documents, outside `#[cfg(test)]` code
+fn extract_documents(source: &str) -> Vec<String> {
+    let source = source.split("#[cfg(test)]").next().unwrap_or_default();
+    let mut documents = Vec::new();
+    let mut rest = source;
+    while let Some(start) = rest.find("r#\"") {
+        rest = &rest[start + 3..];
+        let Some(end) = rest.find("\"#") else {
+            break;
+        };
+        let literal = rest[..end].trim();
+        if ["query", "mutation", "fragment"]
+            .iter()
+            .any(|keyword| literal.starts_with(keyword))
+        {
+            documents.push(literal.to_string());
+        }
+        rest = &rest[end + 2..];
+    }
+    documents
+}
+
+fn rust_sources(dir: &Path, files: &mut Vec<PathBuf>) {
+    for entry in std::fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            rust_sources(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Every operation in the crate, paired with the file it came from
+fn crate_documents() -> Vec<(PathBuf, String)> {
+    let mut files = Vec::new();
+    rust_sources(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+        &mut files,
+    );
+    files.sort();
+
+    let mut documents = Vec::new();
+    for file in files {
+        // This module only holds deliberately broken examples
+        if file.ends_with("schema.rs") {
+            continue;
+        }
+        let source = std::fs::read_to_string(&file).unwrap();
+        for document in extract_documents(&source) {
+            documents.push((file.clone(), document));
+        }
+    }
+    documents
+}
+
+fn collect_fragments<'a>(documents: impl IntoIterator<Item = &'a str>) -> Fragments {
+    let mut fragments = Fragments::new();
+    for source in documents {
+        let Ok(document) = q::parse_query::<String>(source) else {
+            continue;
+        };
+        for definition in document.into_static().definitions {
+            if let Definition::Fragment(fragment) = definition {
+                fragments.insert(fragment.name.clone(), fragment);
+            }
+        }
+    }
+    fragments
+}
+
+/// Key holding the schema type of each object in a sample response
+const TYPE_KEY: &str = "\0type";
+
+impl Schema {
+    /// A response to `operation` with every selected field filled in, lists
+    /// holding one element and nullable values present
+    fn sample(&self, operation: &str) -> std::result::Result<Json, String> {
+        let document = q::parse_query::<String>(operation)
+            .map_err(|e| format!("parse error: {e}"))?
+            .into_static();
+        let fragments = collect_fragments([operation]);
+
+        let (root, selection_set) = document
+            .definitions
+            .iter()
+            .find_map(|definition| match definition {
+                Definition::Operation(OperationDefinition::Query(op)) => {
+                    Some((&self.query, &op.selection_set))
+                }
+                Definition::Operation(OperationDefinition::Mutation(op)) => {
+                    Some((&self.mutation, &op.selection_set))
+                }
+                _ => None,
+            })
+            .ok_or("no query or mutation in the document")?;
+
+        Ok(Json::Object(self.sample_object(
+            root,
+            selection_set,
+            &fragments,
+        )))
+    }
+
+    fn sample_object(
+        &self,
+        type_name: &str,
+        selection_set: &SelectionSet<'static, String>,
+        fragments: &Fragments,
+    ) -> Map<String, Json> {
+        let mut object = Map::new();
+        object.insert(TYPE_KEY.to_string(), json!(type_name));
+
+        for selection in &selection_set.items {
+            match selection {
+                Selection::Field(field) => {
+                    let key = field.alias.as_ref().unwrap_or(&field.name).clone();
+                    if field.name == "__typename" {
+                        object.insert(key, json!(type_name));
+                        continue;
+                    }
+                    let Some(definition) = self
+                        .fields(type_name)
+                        .and_then(|fields| fields.iter().find(|f| f.name == field.name))
+                    else {
+                        continue;
+                    };
+                    let value =
+                        self.sample_value(&definition.field_type, &field.selection_set, fragments);
+                    merge(&mut object, key, value);
+                }
+                Selection::FragmentSpread(spread) => {
+                    if let Some(fragment) = fragments.get(&spread.fragment_name) {
+                        let TypeCondition::On(on) = &fragment.type_condition;
+                        let fields = self.sample_object(on, &fragment.selection_set, fragments);
+                        merge_fields(&mut object, fields);
+                    }
+                }
+                Selection::InlineFragment(inline) => {
+                    let on = match &inline.type_condition {
+                        Some(TypeCondition::On(on)) => on.as_str(),
+                        None => type_name,
+                    };
+                    let fields = self.sample_object(on, &inline.selection_set, fragments);
+                    merge_fields(&mut object, fields);
+                }
+            }
+        }
+        object
+    }
+
+    fn sample_value(
+        &self,
+        ty: &Type,
+        selection_set: &SelectionSet<'static, String>,
+        fragments: &Fragments,
+    ) -> Json {
+        match ty {
+            Type::NonNullType(inner) => self.sample_value(inner, selection_set, fragments),
+            Type::ListType(item) => json!([self.sample_value(item, selection_set, fragments)]),
+            Type::NamedType(name) => match self.types.get(name.as_str()) {
+                _ if !self.is_leaf(name) => {
+                    Json::Object(self.sample_object(name, selection_set, fragments))
+                }
+                Some(TypeDefinition::Enum(e)) => json!(e.values[0].name),
+                _ => match name.as_str() {
+                    // JSON numbers, as Linear sends them (a whole Float has no ".0")
+                    "Int" | "Float" => json!(1),
+                    "Boolean" => json!(true),
+                    "DateTime" => json!("2024-01-01T00:00:00.000Z"),
+                    "TimelessDate" => json!("2024-01-01"),
+                    "JSON" | "JSONObject" => json!({}),
+                    _ => json!("x"),
+                },
+            },
+        }
+    }
+}
+
+/// Insert `value` at `key`, combining objects selected more than once
+fn merge(object: &mut Map<String, Json>, key: String, value: Json) {
+    match (object.get_mut(&key), value) {
+        (Some(Json::Object(existing)), Json::Object(fields)) => merge_fields(existing, fields),
+        (Some(Json::Array(existing)), Json::Array(items)) => {
+            if let (Some(Json::Object(existing)), Some(Json::Object(fields))) =
+                (existing.first_mut(), items.into_iter().next())
+            {
+                merge_fields(existing, fields);
+            }
+        }
+        (_, value) => {
+            object.insert(key, value);
+        }
+    }
+}
+
+fn merge_fields(object: &mut Map<String, Json>, fields: Map<String, Json>) {
+    for (key, value) in fields {
+        if key != TYPE_KEY {
+            merge(object, key, value);
+        }
+    }
+}
+
+/// Panic unless `T` can be deserialized from the responses to `operation`
+/// (see the module docs)
+pub(crate) fn assert_response<T: DeserializeOwned>(operation: &str) {
+    let schema = Schema::load();
+    let type_name = std::any::type_name::<T>();
+    let sample = schema
+        .sample(operation)
+        .unwrap_or_else(|e| panic!("{type_name}: {e}"));
+
+    let errors = RefCell::new(Vec::new());
+    let probe = Probe {
+        schema: &schema,
+        value: &sample,
+        path: "data".to_string(),
+        errors: &errors,
+    };
+    if let Err(e) = T::deserialize(probe) {
+        errors.borrow_mut().push(e.to_string());
+    }
+
+    let errors = errors.into_inner();
+    assert!(
+        errors.is_empty(),
+        "{type_name} does not match the operation:\n{}",
+        errors.join("\n")
+    );
+}
+
+/// Deserializer over a sample response that checks each struct's field
+/// names against the schema type of the object it reads
+struct Probe<'a> {
+    schema: &'a Schema,
+    value: &'a Json,
+    path: String,
+    errors: &'a RefCell<Vec<String>>,
+}
+
+impl<'a> Probe<'a> {
+    fn child(&self, value: &'a Json, path: String) -> Self {
+        Probe {
+            schema: self.schema,
+            value,
+            path,
+            errors: self.errors,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Probe<'de> {
+    type Error = serde_json::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Json::Array(items) => visitor.visit_seq(ProbeSeq {
+                items: items.iter(),
+                probe: self,
+            }),
+            Json::Object(object) => visitor.visit_map(ProbeMap {
+                entries: object.iter(),
+                value: None,
+                probe: self,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        match self.value {
+            Json::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        match (self.value, variants.first()) {
+            // Values of String fields (like IssueRelation.type) aren't in the
+            // schema, so any variant will do
+            (Json::String(_), Some(variant)) => {
+                visitor.visit_enum(BorrowedStrDeserializer::new(variant))
+            }
+            _ => self.value.deserialize_enum(name, variants, visitor),
+        }
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> serde_json::Result<V::Value> {
+        if let Json::Object(object) = self.value {
+            let on = object[TYPE_KEY].as_str().unwrap_or_default();
+            let known = |field: &str| {
+                self.schema
+                    .fields(on)
+                    .is_some_and(|fields| fields.iter().any(|f| f.name == field))
+            };
+            // Selected fields (and aliases) are fine; others must at least exist
+            for field in fields {
+                if !object.contains_key(*field) && !known(field) {
+                    self.errors.borrow_mut().push(format!(
+                        "{}: {name}.{field} is not a field of {on}",
+                        self.path
+                    ));
+                }
+            }
+        }
+        self.deserialize_any(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> serde_json::Result<V::Value> {
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map identifier
+    }
+}
+
+struct ProbeSeq<'a> {
+    items: std::slice::Iter<'a, Json>,
+    probe: Probe<'a>,
+}
+
+impl<'a> de::SeqAccess<'a> for ProbeSeq<'a> {
+    type Error = serde_json::Error;
+
+    fn next_element_seed<S: DeserializeSeed<'a>>(
+        &mut self,
+        seed: S,
+    ) -> serde_json::Result<Option<S::Value>> {
+        let Some(item) = self.items.next() else {
+            return Ok(None);
+        };
+        let path = format!("{}[]", self.probe.path);
+        seed.deserialize(self.probe.child(item, path)).map(Some)
+    }
+}
+
+struct ProbeMap<'a> {
+    entries: serde_json::map::Iter<'a>,
+    value: Option<(&'a String, &'a Json)>,
+    probe: Probe<'a>,
+}
+
+impl<'a> de::MapAccess<'a> for ProbeMap<'a> {
+    type Error = serde_json::Error;
+
+    fn next_key_seed<S: DeserializeSeed<'a>>(
+        &mut self,
+        seed: S,
+    ) -> serde_json::Result<Option<S::Value>> {
+        let Some((key, value)) = self.entries.find(|(key, _)| *key != TYPE_KEY) else {
+            return Ok(None);
+        };
+        self.value = Some((key, value));
+        seed.deserialize(BorrowedStrDeserializer::new(key))
+            .map(Some)
+    }
+
+    fn next_value_seed<S: DeserializeSeed<'a>>(&mut self, seed: S) -> serde_json::Result<S::Value> {
+        let (key, value) = self.value.take().expect("value after key");
+        let path = format!("{}.{key}", self.probe.path);
+        seed.deserialize(self.probe.child(value, path))
+    }
+}
+
+/// Names of the `*_QUERY`/`*_MUTATION` constants in `source`, outside tests
+fn operation_constants(source: &str) -> Vec<String> {
+    let source = source.split("#[cfg(test)]").next().unwrap_or_default();
+    source
+        .lines()
+        .filter_map(|line| {
+            let (_, rest) = line.split_once("const ")?;
+            let (name, _) = rest.split_once(": &str")?;
+            (name.ends_with("_QUERY") || name.ends_with("_MUTATION")).then(|| name.to_string())
+        })
+        .collect()
+}
+
+mod tests {
+    use super::*;
+
+    fn check(source: &str) -> Vec<String> {
+        let schema = Schema::load();
+        let documents = crate_documents();
+        let fragments = collect_fragments(documents.iter().map(|(_, d)| d.as_str()));
+        schema.validate(source, &fragments)
+    }
+
+    #[test]
+    fn test_operations_match_schema() {
+        let schema = Schema::load();
+        let documents = crate_documents();
+        assert!(
+            documents.len() > 20,
+            "found only {} operations",
+            documents.len()
+        );
+
+        let fragments = collect_fragments(documents.iter().map(|(_, d)| d.as_str()));
+        let errors: Vec<String> = documents
+            .iter()
+            .flat_map(|(file, document)| {
+                let file = file.strip_prefix(env!("CARGO_MANIFEST_DIR")).unwrap();
+                schema
+                    .validate(document, &fragments)
+                    .into_iter()
+                    .map(move |e| format!("{}: {e}", file.display()))
+            })
+            .collect();
+
+        assert!(errors.is_empty(), "\n{}", errors.join("\n"));
+    }
+
+    #[test]
+    fn test_every_operation_has_a_response_check() {
+        let mut files = Vec::new();
+        rust_sources(
+            &Path::new(env!("CARGO_MANIFEST_DIR")).join("src"),
+            &mut files,
+        );
+
+        let mut unchecked = Vec::new();
+        for file in files {
+            if file.ends_with("schema.rs") {
+                continue;
+            }
+            let source = std::fs::read_to_string(&file).unwrap();
+            let checks: String = source
+                .split_once("#[cfg(test)]")
+                .map(|(_, tests)| tests.chars().filter(|c| !c.is_whitespace()).collect())
+                .unwrap_or_default();
+            let file = file.strip_prefix(env!("CARGO_MANIFEST_DIR")).unwrap();
+            for name in operation_constants(&source) {
+                if !checks.contains(&format!(">({name})")) {
+                    unchecked.push(format!("{}: {name}", file.display()));
+                }
+            }
+        }
+
+        assert!(
+            unchecked.is_empty(),
+            "no schema::assert_response::<T>(..) test for:\n{}",
+            unchecked.join("\n")
+        );
+    }
+
+    #[test]
+    fn test_response_drift_is_reported() {
+        #[derive(serde::Deserialize)]
+        #[allow(dead_code)]
+        struct Viewer {
+            name: String,
+            #[serde(rename = "fullName")]
+            full_name: Option<String>,
+            email: String,
+        }
+        #[derive(serde::Deserialize)]
+        #[allow(dead_code)]
+        struct ViewerResponse {
+            viewer: Viewer,
+        }
+
+        let result = std::panic::catch_unwind(|| {
+            assert_response::<ViewerResponse>("query Viewer { viewer { name } }")
+        });
+        let message = *result.unwrap_err().downcast::<String>().unwrap();
+        assert!(message.ends_with(
+            "data.viewer: Viewer.fullName is not a field of User\nmissing field `email`"
+        ));
+    }
+
+    #[test]
+    fn test_schema_drift_is_reported() {
+        let errors = check("query Viewer { viewer { id fullName } }");
+        assert_eq!(
+            errors,
+            ["Viewer: Query.viewer.fullName: User has no field `fullName`"]
+        );
+
+        let errors = check(
+            "query GetStates($teamId: Int!) { workflowStates(filter: { team: { id: { eq: $teamId } } }) { nodes { id } } }",
+        );
+        assert_eq!(
+            errors,
+            ["GetStates: Query.workflowStates(filter).team.id.eq: variable $teamId is Int! but ID is expected"]
+        );
+
+        let errors = check(
+            "mutation CreateComment($body: String!) { commentCreate(input: { bdy: $body }) { success } }",
+        );
+        assert_eq!(
+            errors,
+            [
+                "CreateComment: Mutation.commentCreate(input): CommentCreateInput has no field `bdy`",
+                "CreateComment: variable $body is never used",
+            ]
+        );
+
+        let errors = check("query GetIssue { issue { ...Missing } }");
+        assert_eq!(
+            errors,
+            [
+                "GetIssue: Query.issue: missing required argument `id`",
+                "GetIssue: Query.issue: unknown fragment ...Missing",
+            ]
+        );
+    }
+}
@@ -0,0 +1,84 @@
+//! API key storage outside the plaintext config file: an external command
+//! (`api_key_command`) or the OS keyring.
+
+use std::process::Command;
+
+use crate::config;
+use crate::error::{LinearError, Result};
+
+const KEYRING_SERVICE: &str = "linear-cli";
+
+/// Run `api_key_command` through the shell and return its trimmed stdout
+pub fn run_command(command: &str) -> Result<String> {
+    let output = if cfg!(target_os = "windows") {
+        Command::new("cmd").args(["/C", command]).output()
+    } else {
+        Command::new("sh").args(["-c", command]).output()
+    }
+    .map_err(|e| LinearError::ApiKeyCommand(format!("could not run `{command}`: {e}")))?;
+
+    if !output.status.success() {
+        return Err(LinearError::ApiKeyCommand(format!(
+            "`{command}` exited with {}: {}",
+            output.status,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    // Tools like `pass` print the secret on the first line, metadata after
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let key = stdout.lines().next().unwrap_or_default().trim();
+
+    if key.is_empty() {
+        return Err(LinearError::ApiKeyCommand(format!(
+            "`{command}` printed nothing"
+        )));
+    }
+
+    Ok(key.to_string())
+}
+
+/// Read the API key from the OS keyring, if one is stored
+pub fn keyring_get() -> Result<Option<String>> {
+    match entry()?.get_password() {
+        Ok(key) => Ok(Some(key)),
+        Err(keyring::Error::NoEntry) => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Store the API key in the OS keyring
+pub fn keyring_set(key: &str) -> Result<()> {
+    Ok(entry()?.set_password(key)?)
+}
+
+/// One keyring entry per profile ("api_key" or "api_key:<profile>")
+fn entry() -> Result<keyring::Entry> {
+    let user = match config::profile() {
+        Some(name) => format!("api_key:{name}"),
+        None => "api_key".to_string(),
+    };
+    Ok(keyring::Entry::new(KEYRING_SERVICE, &user)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_run_command() {
+        assert_eq!(
+            run_command("printf 'lin_api_x\\nlogin: me\\n'").unwrap(),
+            "lin_api_x"
+        );
+        assert!(matches!(
+            run_command("exit 1"),
+            Err(LinearError::ApiKeyCommand(_))
+        ));
+        assert!(matches!(
+            run_command("true"),
+            Err(LinearError::ApiKeyCommand(_))
+        ));
+    }
+}
@@ -0,0 +1,132 @@
+//! Debug tracing of API traffic to stderr or a log file.
+//!
+//! Secrets (API keys, signed upload headers and URL signatures) are redacted
+//! before anything is written.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static LOG_FILE: Mutex<Option<File>> = Mutex::new(None);
+
+const REDACTED: &str = "<redacted>";
+
+/// Enable tracing, appending to `log_file` if given (otherwise stderr)
+pub fn init(log_file: Option<&Path>) -> std::io::Result<()> {
+    if let Some(path) = log_file {
+        let file = File::options().create(true).append(true).open(path)?;
+        *LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some(file);
+    }
+    ENABLED.store(true, Ordering::Relaxed);
+    Ok(())
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+/// Write a trace line (no-op unless tracing is enabled)
+pub fn log(message: impl AsRef<str>) {
+    if !is_enabled() {
+        return;
+    }
+
+    let line = format!(
+        "[{}] {}",
+        chrono::Local::now().format("%H:%M:%S%.3f"),
+        message.as_ref()
+    );
+
+    let mut file = LOG_FILE.lock().unwrap_or_else(|e| e.into_inner());
+    match file.as_mut() {
+        Some(f) => {
+            let _ = writeln!(f, "{line}");
+        }
+        None => eprintln!("{line}"),
+    }
+}
+
+/// Redact values of secret-looking keys in GraphQL variables
+pub fn redact_variables(value: &serde_json::Value) -> serde_json::Value {
+    match value {
+        serde_json::Value::Object(map) => map
+            .iter()
+            .map(|(k, v)| {
+                let redacted = if is_secret_name(k) {
+                    serde_json::Value::String(REDACTED.to_string())
+                } else {
+                    redact_variables(v)
+                };
+                (k.clone(), redacted)
+            })
+            .collect(),
+        serde_json::Value::Array(items) => items.iter().map(redact_variables).collect(),
+        other => other.clone(),
+    }
+}
+
+/// Redact a header value unless it is known to be harmless
+pub fn redact_header<'a>(name: &str, value: &'a str) -> &'a str {
+    if name.eq_ignore_ascii_case("content-type") || name.eq_ignore_ascii_case("content-length") {
+        value
+    } else {
+        REDACTED
+    }
+}
+
+/// Strip the query string, which carries signatures on signed upload/download URLs
+pub fn redact_url(url: &str) -> String {
+    match url.split_once('?') {
+        Some((base, _)) => format!("{base}?{REDACTED}"),
+        None => url.to_string(),
+    }
+}
+
+fn is_secret_name(name: &str) -> bool {
+    let lower = name.to_lowercase();
+    [
+        "token",
+        "secret",
+        "password",
+        "apikey",
+        "api_key",
+        "authorization",
+    ]
+    .iter()
+    .any(|s| lower.contains(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_redact_variables() {
+        let vars = json!({
+            "id": "ENG-1",
+            "input": { "refreshToken": "abc", "title": "x" },
+            "list": [{ "clientSecret": "s" }]
+        });
+        let redacted = redact_variables(&vars);
+        assert_eq!(redacted["id"], "ENG-1");
+        assert_eq!(redacted["input"]["refreshToken"], REDACTED);
+        assert_eq!(redacted["input"]["title"], "x");
+        assert_eq!(redacted["list"][0]["clientSecret"], REDACTED);
+    }
+
+    #[test]
+    fn test_redact_url_and_headers() {
+        assert_eq!(
+            redact_url("https://storage.example.com/f.png?X-Goog-Signature=abc"),
+            "https://storage.example.com/f.png?<redacted>"
+        );
+        assert_eq!(redact_url("https://a.b/c"), "https://a.b/c");
+        assert_eq!(redact_header("Authorization", "lin_api_x"), REDACTED);
+        assert_eq!(redact_header("x-goog-meta", "v"), REDACTED);
+        assert_eq!(redact_header("Content-Type", "image/png"), "image/png");
+    }
+}